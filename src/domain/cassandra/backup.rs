@@ -0,0 +1,98 @@
+use super::connection::{
+    Credentials, SCHEMA_FILE, cqlsh_args, data_dir, nodetool_args, run_tool, snapshot_dirs, tables,
+};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+
+/// Takes a `nodetool snapshot` of the keyspace and packages the snapshot
+/// SSTables as `<keyspace>/<table>/...` next to the keyspace schema, the
+/// layout `sstableloader` expects on restore.
+pub async fn run(
+    cfg: DatabaseConfig,
+    backup_dir: PathBuf,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        let keyspace = cfg.database.clone();
+        logger.log("info", format!("Starting snapshot of keyspace {} for {}", keyspace, cfg.name));
+
+        let creds = Credentials::write(&cfg)?;
+        let tag = format!("portabase-{}-{}", cfg.generated_id, chrono::Utc::now().format("%Y%m%d%H%M%S"));
+
+        let mut describe = Command::new("cqlsh");
+        describe
+            .args(cqlsh_args(&cfg, &creds))
+            .arg("-e")
+            .arg(format!("DESCRIBE KEYSPACE \"{}\"", keyspace));
+        let schema = run_tool(&logger, describe, &format!("cqlsh DESCRIBE KEYSPACE {keyspace}"))?;
+
+        let selected = tables(&cfg);
+        let mut snapshot = Command::new("nodetool");
+        snapshot.args(nodetool_args(&cfg, &creds)).args(["snapshot", "-t", &tag]);
+        if selected.is_empty() {
+            snapshot.arg(&keyspace);
+        } else {
+            let kt: Vec<String> = selected.iter().map(|t| format!("{keyspace}.{t}")).collect();
+            snapshot.arg("-kt").arg(kt.join(","));
+        }
+        run_tool(&logger, snapshot, &format!("nodetool snapshot -t {tag} {keyspace}"))?;
+
+        let result = (|| -> Result<PathBuf> {
+            let dirs = snapshot_dirs(&data_dir(&cfg), &keyspace, &tag);
+            if dirs.is_empty() {
+                anyhow::bail!(
+                    "No snapshot files found under {} for tag {}; is the node data directory mounted on the agent?",
+                    data_dir(&cfg).join(&keyspace).display(),
+                    tag
+                );
+            }
+
+            let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+            let out = std::fs::File::create(&file_path)
+                .with_context(|| format!("Failed to create {}", file_path.display()))?;
+            let mut tar = tar::Builder::new(out);
+
+            let mut header = tar::Header::new_gnu();
+            header.set_size(schema.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, SCHEMA_FILE, schema.as_bytes())?;
+
+            let mut files = 0usize;
+            for (table, dir) in &dirs {
+                for entry in std::fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if !entry.path().is_file() || name == "manifest.json" || name == SCHEMA_FILE {
+                        continue;
+                    }
+                    tar.append_path_with_name(entry.path(), format!("{keyspace}/{table}/{name}"))?;
+                    files += 1;
+                }
+            }
+            tar.finish()?;
+
+            logger.log("info", format!("Packaged {} SSTable files from {} tables", files, dirs.len()));
+            Ok(file_path)
+        })();
+
+        let mut clear = Command::new("nodetool");
+        clear
+            .args(nodetool_args(&cfg, &creds))
+            .args(["clearsnapshot", "-t", &tag, "--"])
+            .arg(&keyspace);
+        if let Err(e) = run_tool(&logger, clear, &format!("nodetool clearsnapshot -t {tag}")) {
+            logger.log("warn", format!("Failed to clear snapshot {}: {}", tag, e));
+        }
+
+        if result.is_ok() {
+            logger.log("info", format!("Snapshot backup completed for {}", cfg.name));
+        }
+        result
+    })
+    .await?
+}
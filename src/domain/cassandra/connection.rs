@@ -0,0 +1,230 @@
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::{DatabaseConfig, DbType};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+pub const SCHEMA_FILE: &str = "schema.cql";
+
+fn option_str<'a>(cfg: &'a DatabaseConfig, key: &str) -> Option<&'a str> {
+    cfg.options
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+pub fn is_scylla(cfg: &DatabaseConfig) -> bool {
+    matches!(cfg.db_type, DbType::Scylladb)
+}
+
+pub fn cql_port(cfg: &DatabaseConfig) -> u16 {
+    cfg.options
+        .get("cql_port")
+        .and_then(|v| v.as_u64())
+        .and_then(|p| u16::try_from(p).ok())
+        .unwrap_or(9042)
+}
+
+/// Node data directory as seen by the agent (a shared mount of the node's
+/// data volume). Snapshots are read from, and refreshed SSTables written to, here.
+pub fn data_dir(cfg: &DatabaseConfig) -> PathBuf {
+    if !cfg.path.is_empty() {
+        return PathBuf::from(&cfg.path);
+    }
+    if is_scylla(cfg) {
+        PathBuf::from("/var/lib/scylla/data")
+    } else {
+        PathBuf::from("/var/lib/cassandra/data")
+    }
+}
+
+/// Optional `options.tables` restricting the snapshot to some tables of the keyspace.
+pub fn tables(cfg: &DatabaseConfig) -> Vec<String> {
+    cfg.options
+        .get("tables")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|t| t.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn use_refresh(cfg: &DatabaseConfig) -> bool {
+    option_str(cfg, "restore_mode") == Some("refresh")
+}
+
+/// Credential files handed to `nodetool -pwf` and `cqlsh --cqlshrc`, so the
+/// password never appears in the process list. Removed on drop.
+pub struct Credentials {
+    _dir: tempfile::TempDir,
+    pub nodetool_pwf: Option<PathBuf>,
+    pub cqlshrc: Option<PathBuf>,
+}
+
+impl Credentials {
+    pub fn write(cfg: &DatabaseConfig) -> Result<Self> {
+        let dir = tempfile::TempDir::new()?;
+        if cfg.username.is_empty() {
+            return Ok(Self { _dir: dir, nodetool_pwf: None, cqlshrc: None });
+        }
+
+        let pwf = dir.path().join("jmxremote.password");
+        write_private(&pwf, &format!("{} {}\n", cfg.username, cfg.password))?;
+
+        let rc = dir.path().join("cqlshrc");
+        write_private(
+            &rc,
+            &format!(
+                "[authentication]\nusername = {}\npassword = {}\n",
+                cfg.username, cfg.password
+            ),
+        )?;
+
+        Ok(Self { _dir: dir, nodetool_pwf: Some(pwf), cqlshrc: Some(rc) })
+    }
+}
+
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut f = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    f.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+pub fn nodetool_args(cfg: &DatabaseConfig, creds: &Credentials) -> Vec<String> {
+    let mut args = vec![
+        "-h".to_string(),
+        cfg.host.clone(),
+        "-p".to_string(),
+        cfg.port.to_string(),
+    ];
+    if let Some(pwf) = &creds.nodetool_pwf {
+        args.push("-u".to_string());
+        args.push(cfg.username.clone());
+        args.push("-pwf".to_string());
+        args.push(pwf.display().to_string());
+    }
+    args
+}
+
+pub fn cqlsh_args(cfg: &DatabaseConfig, creds: &Credentials) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(rc) = &creds.cqlshrc {
+        args.push(format!("--cqlshrc={}", rc.display()));
+    }
+    args.push(cfg.host.clone());
+    args.push(cql_port(cfg).to_string());
+    args
+}
+
+/// `sstableloader` for `table_dir`. The tool only takes the password as
+/// `-pw`, so it travels in `SSTABLELOADER_PASSWORD` to a shell that appends it
+/// at exec time, keeping it out of the agent's own command line and logs.
+pub fn sstableloader_command(cfg: &DatabaseConfig, table_dir: &Path) -> Command {
+    let mut cmd = if cfg.username.is_empty() {
+        Command::new("sstableloader")
+    } else {
+        let mut sh = Command::new("sh");
+        sh.arg("-c")
+            .arg(r#"exec sstableloader "$@" -pw "$SSTABLELOADER_PASSWORD""#)
+            .arg("sstableloader")
+            .env("SSTABLELOADER_PASSWORD", &cfg.password);
+        sh
+    };
+    cmd.arg("-d").arg(&cfg.host).arg("-p").arg(cql_port(cfg).to_string());
+    if !cfg.username.is_empty() {
+        cmd.arg("-u").arg(&cfg.username);
+    }
+    cmd.arg(table_dir);
+    cmd
+}
+
+/// Strips the `-<table id>` suffix from a table data directory name.
+pub fn table_name(dir_name: &str) -> Option<&str> {
+    let (name, id) = dir_name.rsplit_once('-')?;
+    (id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit())).then_some(name)
+}
+
+/// Table directories under `<data_dir>/<keyspace>` holding a snapshot `tag`,
+/// keyed by table name.
+pub fn snapshot_dirs(data_dir: &Path, keyspace: &str, tag: &str) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(data_dir.join(keyspace)) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<(String, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let table = table_name(&name)?.to_string();
+            let snap = e.path().join("snapshots").join(tag);
+            snap.is_dir().then_some((table, snap))
+        })
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Live data directory of `table`. After a drop/recreate several may exist;
+/// the most recently modified one belongs to the current table id.
+pub fn live_table_dir(data_dir: &Path, keyspace: &str, table: &str) -> Option<PathBuf> {
+    std::fs::read_dir(data_dir.join(keyspace))
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| table_name(&e.file_name().to_string_lossy()) == Some(table))
+        .max_by_key(|e| e.metadata().and_then(|m| m.modified()).ok())
+        .map(|e| e.path())
+}
+
+/// `DESCRIBE KEYSPACE` emits plain `CREATE` statements; make them
+/// idempotent so the schema can be replayed onto an existing keyspace.
+pub fn idempotent_schema(cql: &str) -> String {
+    let mut out = cql.to_string();
+    for kind in [
+        "KEYSPACE",
+        "TABLE",
+        "TYPE",
+        "FUNCTION",
+        "AGGREGATE",
+        "INDEX",
+        "CUSTOM INDEX",
+        "MATERIALIZED VIEW",
+    ] {
+        let from = format!("CREATE {kind} ");
+        let to = format!("CREATE {kind} IF NOT EXISTS ");
+        out = out.replace(&to, &from).replace(&from, &to);
+    }
+    out
+}
+
+/// Runs a Cassandra tool, logging it under `display` (never the raw argv,
+/// which may reference credential files) and returning stdout.
+pub fn run_tool(logger: &JobLogger, mut cmd: Command, display: &str) -> Result<String> {
+    let start = Instant::now();
+    let output = cmd
+        .output()
+        .with_context(|| format!("{display} failed to start"))?;
+    let duration_ms = start.elapsed().as_millis() as f64;
+    let exit_code = output.status.code().unwrap_or(-1);
+
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+
+    if !output.status.success() {
+        let combined = format!("{}{}", stdout, stderr).trim().to_string();
+        logger.log_command(display, Some(combined), Some(exit_code), Some(duration_ms));
+        anyhow::bail!("{} failed: {}", display, stderr.trim());
+    }
+
+    logger.log_command(display, None, Some(0), Some(duration_ms));
+    Ok(stdout)
+}
@@ -0,0 +1,28 @@
+use super::connection::{Credentials, nodetool_args};
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use tokio::process::Command;
+use tokio::time::{Duration, timeout};
+use tracing::{error, info};
+
+pub async fn run(cfg: DatabaseConfig) -> Result<bool> {
+    let creds = Credentials::write(&cfg)?;
+    let mut cmd = Command::new("nodetool");
+    cmd.args(nodetool_args(&cfg, &creds)).arg("version");
+
+    match timeout(Duration::from_secs(10), cmd.output()).await {
+        Ok(output) => {
+            let output = output.context("Failed to execute nodetool")?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                error!("nodetool version failed: {}", stderr.trim());
+                return Ok(false);
+            }
+            Ok(true)
+        }
+        Err(_) => {
+            info!("Timeout connecting to {}:{}", cfg.host, cfg.port);
+            Ok(false)
+        }
+    }
+}
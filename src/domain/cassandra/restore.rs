@@ -0,0 +1,103 @@
+use super::connection::{
+    Credentials, SCHEMA_FILE, cqlsh_args, data_dir, idempotent_schema, is_scylla, live_table_dir,
+    nodetool_args, run_tool, sstableloader_command, use_refresh,
+};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+/// Replays the schema, then streams the SSTables back with `sstableloader`
+/// (default) or, with `options.restore_mode = "refresh"`, copies them into
+/// the node's table directories and runs `nodetool refresh`.
+pub async fn run(cfg: DatabaseConfig, restore_file: PathBuf, logger: Arc<JobLogger>) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        logger.log("debug", format!("Starting snapshot restore for {}", cfg.name));
+
+        if !restore_file.exists() {
+            anyhow::bail!("Restore file not found: {}", restore_file.display());
+        }
+
+        let unpacked = tempfile::TempDir::new()?;
+        let tar = std::fs::File::open(&restore_file)?;
+        tar::Archive::new(tar)
+            .unpack(unpacked.path())
+            .with_context(|| format!("Failed to unpack {}", restore_file.display()))?;
+
+        let creds = Credentials::write(&cfg)?;
+
+        let schema_path = unpacked.path().join(SCHEMA_FILE);
+        let schema = std::fs::read_to_string(&schema_path)
+            .context("Invalid snapshot archive: schema.cql not found")?;
+        std::fs::write(&schema_path, idempotent_schema(&schema))?;
+
+        let mut apply = Command::new("cqlsh");
+        apply.args(cqlsh_args(&cfg, &creds)).arg("-f").arg(&schema_path);
+        run_tool(&logger, apply, "cqlsh -f schema.cql")?;
+
+        for keyspace_dir in subdirs(unpacked.path())? {
+            let keyspace = file_name(&keyspace_dir);
+            if keyspace != cfg.database {
+                logger.log(
+                    "warn",
+                    format!("Archive keyspace {} differs from configured {}; restoring into {}", keyspace, cfg.database, keyspace),
+                );
+            }
+            for table_dir in subdirs(&keyspace_dir)? {
+                let table = file_name(&table_dir);
+                if use_refresh(&cfg) {
+                    refresh_table(&cfg, &creds, &keyspace, &table, &table_dir, &logger)?;
+                } else {
+                    let load = sstableloader_command(&cfg, &table_dir);
+                    run_tool(&logger, load, &format!("sstableloader -d {} {keyspace}/{table}", cfg.host))?;
+                }
+            }
+        }
+
+        logger.log("info", format!("Snapshot restore completed for {}", cfg.name));
+        Ok(())
+    })
+    .await?
+}
+
+fn refresh_table(
+    cfg: &DatabaseConfig,
+    creds: &Credentials,
+    keyspace: &str,
+    table: &str,
+    source: &Path,
+    logger: &JobLogger,
+) -> Result<()> {
+    let live = live_table_dir(&data_dir(cfg), keyspace, table).ok_or_else(|| {
+        anyhow::anyhow!("Table directory for {keyspace}.{table} not found under {}", data_dir(cfg).display())
+    })?;
+    // ScyllaDB only picks up SSTables staged in the table's upload directory.
+    let target = if is_scylla(cfg) { live.join("upload") } else { live };
+    std::fs::create_dir_all(&target)?;
+
+    for entry in std::fs::read_dir(source)?.filter_map(|e| e.ok()) {
+        std::fs::copy(entry.path(), target.join(entry.file_name()))
+            .with_context(|| format!("Failed to copy {} into {}", entry.path().display(), target.display()))?;
+    }
+
+    let mut refresh = Command::new("nodetool");
+    refresh.args(nodetool_args(cfg, creds)).args(["refresh", "--", keyspace, table]);
+    run_tool(logger, refresh, &format!("nodetool refresh {keyspace} {table}"))?;
+    Ok(())
+}
+
+fn subdirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
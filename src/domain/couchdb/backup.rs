@@ -0,0 +1,147 @@
+use super::connection::{
+    MANIFEST_FILE, ManifestEntry, authed, backs_up_all, batch_size, client, db_url, list_databases,
+};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::io::AsyncWriteExt;
+
+pub async fn run(
+    cfg: DatabaseConfig,
+    backup_dir: PathBuf,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<PathBuf> {
+    logger.log("info", format!("Starting CouchDB backup for {}", cfg.name));
+
+    let client = client()?;
+    let databases = if backs_up_all(&cfg) {
+        list_databases(&client, &cfg).await?
+    } else {
+        vec![cfg.database.clone()]
+    };
+    logger.log("info", format!("CouchDB databases selected: {:?}", databases));
+
+    let export_dir = backup_dir.join(format!("{}_export", cfg.generated_id));
+    tokio::fs::create_dir_all(&export_dir).await?;
+
+    let mut manifest = Vec::with_capacity(databases.len());
+    for (i, db) in databases.iter().enumerate() {
+        let file = format!("{i}.ndjson");
+        let start = Instant::now();
+        let (doc_count, update_seq) =
+            match dump_database(&client, &cfg, db, &export_dir.join(&file)).await {
+                Ok(v) => v,
+                Err(e) => {
+                    let duration_ms = start.elapsed().as_millis() as f64;
+                    logger.log_command(format!("couchdb _all_docs {db}"), Some(e.to_string()), Some(1), Some(duration_ms));
+                    return Err(e);
+                }
+            };
+        let duration_ms = start.elapsed().as_millis() as f64;
+        logger.log_command(
+            format!("couchdb _all_docs {db}"),
+            Some(format!("{doc_count} documents exported")),
+            Some(0),
+            Some(duration_ms),
+        );
+        manifest.push(ManifestEntry { db: db.clone(), file, doc_count, update_seq });
+    }
+
+    tokio::fs::write(export_dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?).await?;
+
+    let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+    let archive_path = file_path.clone();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let out = std::fs::File::create(&archive_path)
+            .with_context(|| format!("Failed to create {}", archive_path.display()))?;
+        let mut tar = tar::Builder::new(out);
+        tar.append_dir_all(".", &export_dir)?;
+        tar.finish()?;
+        std::fs::remove_dir_all(&export_dir).ok();
+        Ok(())
+    })
+    .await??;
+
+    logger.log("info", format!("CouchDB backup completed for {}", cfg.name));
+    Ok(file_path)
+}
+
+/// Pages through `_all_docs?include_docs=true&attachments=true` and writes one
+/// document per line, so memory stays bounded by `batch_size` regardless of
+/// database size.
+async fn dump_database(
+    client: &Client,
+    cfg: &DatabaseConfig,
+    db: &str,
+    out_path: &Path,
+) -> Result<(u64, Option<Value>)> {
+    let url = db_url(cfg, db);
+
+    let info: Value = authed(cfg, client.get(&url))
+        .send()
+        .await?
+        .error_for_status()
+        .with_context(|| format!("CouchDB database {db} not accessible"))?
+        .json()
+        .await?;
+    let update_seq = info.get("update_seq").cloned();
+
+    let limit = batch_size(cfg);
+    let mut out = tokio::io::BufWriter::new(tokio::fs::File::create(out_path).await?);
+    let mut last_id: Option<String> = None;
+    let mut count: u64 = 0;
+
+    loop {
+        let mut query: Vec<(&str, String)> = vec![
+            ("include_docs", "true".into()),
+            ("attachments", "true".into()),
+            ("limit", limit.to_string()),
+        ];
+        if let Some(id) = &last_id {
+            query.push(("startkey", serde_json::to_string(id)?));
+            query.push(("skip", "1".into()));
+        }
+
+        let page: Value = authed(cfg, client.get(format!("{url}/_all_docs")).query(&query))
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("CouchDB _all_docs failed for {db}"))?
+            .json()
+            .await?;
+
+        let rows = page
+            .get("rows")
+            .and_then(|r| r.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Unexpected _all_docs response for {db}"))?;
+
+        for row in rows {
+            if let Some(doc) = row.get("doc").filter(|d| !d.is_null()) {
+                out.write_all(&serde_json::to_vec(doc)?).await?;
+                out.write_all(b"\n").await?;
+                count += 1;
+            }
+        }
+
+        if rows.len() < limit {
+            break;
+        }
+        last_id = rows
+            .last()
+            .and_then(|r| r.get("id"))
+            .and_then(|id| id.as_str())
+            .map(str::to_string);
+        if last_id.is_none() {
+            break;
+        }
+    }
+
+    out.flush().await?;
+    Ok((count, update_seq))
+}
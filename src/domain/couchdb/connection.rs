@@ -0,0 +1,103 @@
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub db: String,
+    pub file: String,
+    pub doc_count: u64,
+    #[serde(default)]
+    pub update_seq: Option<serde_json::Value>,
+}
+
+/// How `_bulk_docs` conflicts are resolved on restore (`options.conflict_mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictMode {
+    /// Replace the current revision of conflicting documents.
+    Overwrite,
+    /// Keep the document already present in the target.
+    Skip,
+    /// Abort the restore on the first conflict.
+    Fail,
+    /// Replay revisions as-is (`new_edits: false`), like replication does.
+    Preserve,
+}
+
+impl ConflictMode {
+    pub fn from_config(cfg: &DatabaseConfig) -> Result<Self> {
+        match cfg.options.get("conflict_mode").and_then(|v| v.as_str()) {
+            None | Some("overwrite") => Ok(Self::Overwrite),
+            Some("skip") => Ok(Self::Skip),
+            Some("fail") => Ok(Self::Fail),
+            Some("preserve") => Ok(Self::Preserve),
+            Some(other) => {
+                anyhow::bail!("Unknown conflict_mode '{other}', expected overwrite, skip, fail or preserve")
+            }
+        }
+    }
+}
+
+pub fn base_url(cfg: &DatabaseConfig) -> String {
+    let tls = cfg.options.get("tls").and_then(|v| v.as_bool()).unwrap_or(false);
+    let scheme = if tls { "https" } else { "http" };
+    format!("{}://{}:{}", scheme, cfg.host, cfg.port)
+}
+
+pub fn db_url(cfg: &DatabaseConfig, db: &str) -> String {
+    let encoded: String = url::form_urlencoded::byte_serialize(db.as_bytes()).collect();
+    format!("{}/{}", base_url(cfg), encoded)
+}
+
+pub fn batch_size(cfg: &DatabaseConfig) -> usize {
+    cfg.options
+        .get("batch_size")
+        .and_then(|v| v.as_u64())
+        .filter(|n| *n > 0)
+        .unwrap_or(500) as usize
+}
+
+/// `database` empty or `*` selects every database on the server.
+pub fn backs_up_all(cfg: &DatabaseConfig) -> bool {
+    cfg.database.is_empty() || cfg.database == "*"
+}
+
+pub fn filter_databases(cfg: &DatabaseConfig, all: Vec<String>) -> Vec<String> {
+    let include_system = cfg
+        .options
+        .get("include_system_dbs")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    all.into_iter()
+        .filter(|db| include_system || !db.starts_with('_'))
+        .collect()
+}
+
+pub fn client() -> Result<Client> {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(5))
+        .timeout(Duration::from_secs(300))
+        .build()
+        .context("Failed to build CouchDB http client")
+}
+
+pub fn authed(cfg: &DatabaseConfig, req: RequestBuilder) -> RequestBuilder {
+    if cfg.username.is_empty() {
+        req
+    } else {
+        req.basic_auth(&cfg.username, Some(&cfg.password))
+    }
+}
+
+pub async fn list_databases(client: &Client, cfg: &DatabaseConfig) -> Result<Vec<String>> {
+    let res = authed(cfg, client.get(format!("{}/_all_dbs", base_url(cfg))))
+        .send()
+        .await?
+        .error_for_status()
+        .context("CouchDB _all_dbs failed")?;
+    Ok(filter_databases(cfg, res.json().await?))
+}
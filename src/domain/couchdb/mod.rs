@@ -0,0 +1,5 @@
+pub mod backup;
+pub mod connection;
+pub mod database;
+mod ping;
+pub mod restore;
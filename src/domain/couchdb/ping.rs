@@ -0,0 +1,22 @@
+use super::connection::{authed, base_url, client};
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+use tracing::error;
+
+pub async fn run(cfg: DatabaseConfig) -> Result<bool> {
+    let client = client()?;
+    match authed(&cfg, client.get(base_url(&cfg))).send().await {
+        Ok(res) if res.status().is_success() => {
+            let body: serde_json::Value = res.json().await.unwrap_or_default();
+            Ok(body.get("couchdb").is_some())
+        }
+        Ok(res) => {
+            error!("CouchDB ping returned status {} for {}:{}", res.status(), cfg.host, cfg.port);
+            Ok(false)
+        }
+        Err(e) => {
+            error!("CouchDB ping failed for {}:{}: {}", cfg.host, cfg.port, e);
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use super::connection::{
+    ConflictMode, MANIFEST_FILE, ManifestEntry, authed, backs_up_all, batch_size, client, db_url,
+};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use reqwest::{Client, StatusCode};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::io::AsyncBufReadExt;
+
+#[derive(Default, Debug)]
+struct RestoreStats {
+    written: u64,
+    overwritten: u64,
+    skipped: u64,
+}
+
+pub async fn run(cfg: DatabaseConfig, restore_file: PathBuf, logger: Arc<JobLogger>) -> Result<()> {
+    logger.log("debug", format!("Starting CouchDB restore for {}", cfg.name));
+
+    let mode = ConflictMode::from_config(&cfg)?;
+
+    let unpacked = tempfile::TempDir::new()?;
+    let archive = restore_file.clone();
+    let dest = unpacked.path().to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let tar = std::fs::File::open(&archive)?;
+        tar::Archive::new(tar)
+            .unpack(&dest)
+            .with_context(|| format!("Failed to unpack {}", archive.display()))
+    })
+    .await??;
+
+    let manifest_path = unpacked.path().join(MANIFEST_FILE);
+    let manifest: Vec<ManifestEntry> = serde_json::from_slice(
+        &tokio::fs::read(&manifest_path)
+            .await
+            .context("Invalid CouchDB archive: manifest.json not found")?,
+    )?;
+
+    // A single-database archive restored into a configured database is
+    // renamed to it; otherwise databases keep their original names.
+    let rename_to = (!backs_up_all(&cfg) && manifest.len() == 1).then(|| cfg.database.clone());
+
+    let client = client()?;
+    for entry in &manifest {
+        let target = rename_to.clone().unwrap_or_else(|| entry.db.clone());
+        logger.log("info", format!("Restoring CouchDB database {} into {} ({:?})", entry.db, target, mode));
+
+        let start = Instant::now();
+        let res = restore_database(&client, &cfg, &target, &unpacked.path().join(&entry.file), mode).await;
+        let duration_ms = start.elapsed().as_millis() as f64;
+
+        match res {
+            Ok(stats) => logger.log_command(
+                format!("couchdb _bulk_docs {target}"),
+                Some(format!(
+                    "{} written, {} overwritten, {} skipped",
+                    stats.written, stats.overwritten, stats.skipped
+                )),
+                Some(0),
+                Some(duration_ms),
+            ),
+            Err(e) => {
+                logger.log_command(format!("couchdb _bulk_docs {target}"), Some(e.to_string()), Some(1), Some(duration_ms));
+                logger.log("error", format!("CouchDB restore failed for {}: {}", cfg.name, e));
+                return Err(e);
+            }
+        }
+    }
+
+    logger.log("info", format!("CouchDB restore completed for {}", cfg.name));
+    Ok(())
+}
+
+async fn restore_database(
+    client: &Client,
+    cfg: &DatabaseConfig,
+    db: &str,
+    file: &Path,
+    mode: ConflictMode,
+) -> Result<RestoreStats> {
+    let url = db_url(cfg, db);
+
+    let created = authed(cfg, client.put(&url)).send().await?;
+    if !created.status().is_success() && created.status() != StatusCode::PRECONDITION_FAILED {
+        anyhow::bail!("Failed to create CouchDB database {}: {}", db, created.status());
+    }
+
+    let limit = batch_size(cfg);
+    let mut stats = RestoreStats::default();
+    let mut lines = tokio::io::BufReader::new(tokio::fs::File::open(file).await?).lines();
+    let mut batch = Vec::with_capacity(limit);
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        batch.push(serde_json::from_str::<Value>(&line)?);
+        if batch.len() >= limit {
+            write_batch(client, cfg, &url, std::mem::take(&mut batch), mode, &mut stats).await?;
+        }
+    }
+    if !batch.is_empty() {
+        write_batch(client, cfg, &url, batch, mode, &mut stats).await?;
+    }
+
+    Ok(stats)
+}
+
+async fn bulk_docs(client: &Client, cfg: &DatabaseConfig, url: &str, body: Value) -> Result<Vec<Value>> {
+    authed(cfg, client.post(format!("{url}/_bulk_docs")).json(&body))
+        .send()
+        .await?
+        .error_for_status()
+        .context("CouchDB _bulk_docs failed")?
+        .json()
+        .await
+        .context("Unexpected _bulk_docs response")
+}
+
+fn conflicted_ids(results: &[Value]) -> Vec<String> {
+    results
+        .iter()
+        .filter(|r| r.get("error").and_then(|e| e.as_str()) == Some("conflict"))
+        .filter_map(|r| r.get("id").and_then(|i| i.as_str()).map(str::to_string))
+        .collect()
+}
+
+fn other_errors(results: &[Value]) -> Vec<String> {
+    results
+        .iter()
+        .filter(|r| matches!(r.get("error").and_then(|e| e.as_str()), Some(e) if e != "conflict"))
+        .map(|r| r.to_string())
+        .collect()
+}
+
+async fn write_batch(
+    client: &Client,
+    cfg: &DatabaseConfig,
+    url: &str,
+    mut docs: Vec<Value>,
+    mode: ConflictMode,
+    stats: &mut RestoreStats,
+) -> Result<()> {
+    match mode {
+        ConflictMode::Preserve => {
+            let results = bulk_docs(client, cfg, url, json!({ "docs": docs, "new_edits": false })).await?;
+            let errors = other_errors(&results);
+            if !errors.is_empty() {
+                anyhow::bail!("_bulk_docs rejected {} document(s): {}", errors.len(), errors.join(", "));
+            }
+            stats.written += docs.len() as u64;
+            Ok(())
+        }
+        ConflictMode::Skip => {
+            let conflicts = write_new_revisions(client, cfg, url, &mut docs, stats).await?;
+            stats.skipped += conflicts.len() as u64;
+            Ok(())
+        }
+        ConflictMode::Fail => {
+            let conflicts = write_new_revisions(client, cfg, url, &mut docs, stats).await?;
+            if !conflicts.is_empty() {
+                anyhow::bail!("{} document(s) already exist in target: {:?}", conflicts.len(), conflicts);
+            }
+            Ok(())
+        }
+        ConflictMode::Overwrite => {
+            let conflicts = write_new_revisions(client, cfg, url, &mut docs, stats).await?;
+            if conflicts.is_empty() {
+                return Ok(());
+            }
+            overwrite(client, cfg, url, docs, conflicts, stats).await
+        }
+    }
+}
+
+/// Writes `docs` without their source revisions and returns the ids that
+/// already exist in the target.
+async fn write_new_revisions(
+    client: &Client,
+    cfg: &DatabaseConfig,
+    url: &str,
+    docs: &mut [Value],
+    stats: &mut RestoreStats,
+) -> Result<Vec<String>> {
+    // Revisions from the source are meaningless on the target; let CouchDB
+    // assign new ones and deal with the documents that already exist.
+    for doc in docs.iter_mut() {
+        if let Some(obj) = doc.as_object_mut() {
+            obj.remove("_rev");
+        }
+    }
+
+    let results = bulk_docs(client, cfg, url, json!({ "docs": docs })).await?;
+    let errors = other_errors(&results);
+    if !errors.is_empty() {
+        anyhow::bail!("_bulk_docs rejected {} document(s): {}", errors.len(), errors.join(", "));
+    }
+
+    let conflicts = conflicted_ids(&results);
+    stats.written += (docs.len() - conflicts.len()) as u64;
+    Ok(conflicts)
+}
+
+/// Retries the conflicting documents on top of their current revision.
+async fn overwrite(
+    client: &Client,
+    cfg: &DatabaseConfig,
+    url: &str,
+    docs: Vec<Value>,
+    conflicts: Vec<String>,
+    stats: &mut RestoreStats,
+) -> Result<()> {
+    let revs: Value = authed(cfg, client.post(format!("{url}/_all_docs")).json(&json!({ "keys": conflicts })))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let current: HashMap<String, String> = revs
+        .get("rows")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|row| {
+            let id = row.get("id")?.as_str()?.to_string();
+            let rev = row.get("value")?.get("rev")?.as_str()?.to_string();
+            Some((id, rev))
+        })
+        .collect();
+
+    let retry: Vec<Value> = docs
+        .into_iter()
+        .filter_map(|mut doc| {
+            let id = doc.get("_id")?.as_str()?.to_string();
+            let rev = current.get(&id)?;
+            doc.as_object_mut()?.insert("_rev".into(), Value::String(rev.clone()));
+            Some(doc)
+        })
+        .collect();
+
+    let results = bulk_docs(client, cfg, url, json!({ "docs": retry })).await?;
+    let failed: Vec<String> = results
+        .iter()
+        .filter(|r| r.get("error").is_some())
+        .map(|r| r.to_string())
+        .collect();
+    if !failed.is_empty() {
+        anyhow::bail!("Failed to overwrite {} document(s): {}", failed.len(), failed.join(", "));
+    }
+    stats.overwritten += retry.len() as u64;
+    Ok(())
+}
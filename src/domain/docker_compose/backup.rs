@@ -0,0 +1,96 @@
+use super::project::{
+    MANIFEST_FILE, Manifest, VolumeEntry, list_containers, list_volumes, start_project, stop_project,
+};
+use crate::domain::docker_volume::docker::{download_volume, resolve_helper_image};
+use crate::domain::docker_volume::endpoint::connect;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Produces a single tar holding `manifest.json` and one `volumes/<name>.tar`
+/// per project volume, all taken while the project is stopped.
+pub async fn run(cfg: DatabaseConfig, backup_dir: PathBuf, logger: Arc<JobLogger>) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        futures::executor::block_on(async move {
+            let project = cfg.database.clone();
+            logger.log("info", format!("Starting docker-compose backup for {} (project {project})", cfg.name));
+
+            let docker = connect(&cfg).await?;
+            let image = resolve_helper_image(&docker, &cfg).await?;
+            logger.log("debug", format!("Helper image: {image}"));
+
+            let volumes = list_volumes(&docker, &project).await?;
+            if volumes.is_empty() {
+                anyhow::bail!("No volumes found for Compose project {project}");
+            }
+            logger.log(
+                "info",
+                format!(
+                    "Found {} volume(s): {}",
+                    volumes.len(),
+                    volumes.iter().map(|v| v.name.as_str()).collect::<Vec<_>>().join(", ")
+                ),
+            );
+
+            let containers = list_containers(&docker, &project).await?;
+            let stopped = stop_project(&docker, &containers, &logger).await?;
+
+            let result = async {
+                let work_dir = backup_dir.join(format!("{}_compose", cfg.generated_id));
+                std::fs::create_dir_all(&work_dir)?;
+
+                let file_path = backup_dir.join(format!("{}.tar", cfg.generated_id));
+                let out = std::fs::File::create(&file_path)
+                    .with_context(|| format!("Failed to create backup file {}", file_path.display()))?;
+                let mut builder = tar::Builder::new(out);
+
+                let mut entries = Vec::with_capacity(volumes.len());
+                for volume in &volumes {
+                    let entry = VolumeEntry::from_volume(volume);
+                    let tmp = work_dir.join(format!("{}.tar", volume.name));
+                    let _ = std::fs::remove_file(&tmp);
+
+                    let start = Instant::now();
+                    let bytes = download_volume(&docker, &image, &volume.name, &cfg.generated_id, &tmp).await?;
+                    let duration_ms = start.elapsed().as_millis() as f64;
+                    logger.log_command(
+                        format!("docker download_from_container ({})", volume.name),
+                        None,
+                        Some(0),
+                        Some(duration_ms),
+                    );
+                    logger.log("info", format!("Volume {} archived ({bytes} bytes)", volume.name));
+
+                    builder
+                        .append_path_with_name(&tmp, &entry.file)
+                        .with_context(|| format!("Failed to add volume {} to archive", volume.name))?;
+                    std::fs::remove_file(&tmp)?;
+                    entries.push(entry);
+                }
+
+                let manifest = serde_json::to_vec_pretty(&Manifest { project: project.clone(), volumes: entries })?;
+                let mut header = tar::Header::new_gnu();
+                header.set_size(manifest.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder.append_data(&mut header, MANIFEST_FILE, manifest.as_slice())?;
+                builder.into_inner()?.sync_all()?;
+
+                let _ = std::fs::remove_dir_all(&work_dir);
+                anyhow::Ok(file_path)
+            }
+            .await;
+
+            start_project(&docker, &stopped, &logger).await;
+
+            if result.is_ok() {
+                logger.log("info", format!("Docker-compose backup completed for {}", cfg.name));
+            }
+            result
+        })
+    })
+    .await?
+}
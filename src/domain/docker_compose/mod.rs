@@ -0,0 +1,5 @@
+mod backup;
+pub mod database;
+mod ping;
+pub mod project;
+mod restore;
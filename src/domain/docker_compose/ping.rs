@@ -0,0 +1,9 @@
+use super::project::list_volumes;
+use crate::domain::docker_volume::endpoint::connect;
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+
+pub async fn run(cfg: DatabaseConfig) -> Result<bool> {
+    let docker = connect(&cfg).await?;
+    Ok(!list_volumes(&docker, &cfg.database).await?.is_empty())
+}
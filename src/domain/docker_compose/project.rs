@@ -0,0 +1,209 @@
+use crate::domain::docker_volume::docker::{self_container_id, start_container, stop_container};
+use crate::services::backup::logger::JobLogger;
+use anyhow::{Context, Result};
+use bollard::Docker;
+use bollard::models::{ContainerSummary, ContainerSummaryStateEnum, Volume};
+use bollard::query_parameters::{ListContainersOptions, ListVolumesOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+pub const PROJECT_LABEL: &str = "com.docker.compose.project";
+pub const SERVICE_LABEL: &str = "com.docker.compose.service";
+pub const DEPENDS_ON_LABEL: &str = "com.docker.compose.depends_on";
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub project: String,
+    pub volumes: Vec<VolumeEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeEntry {
+    pub name: String,
+    /// Path of the volume's tar inside the artifact.
+    pub file: String,
+    pub driver: String,
+    #[serde(default)]
+    pub driver_opts: HashMap<String, String>,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+impl VolumeEntry {
+    pub fn from_volume(volume: &Volume) -> Self {
+        Self {
+            name: volume.name.clone(),
+            file: format!("volumes/{}.tar", volume.name),
+            driver: volume.driver.clone(),
+            driver_opts: volume.options.clone(),
+            labels: volume.labels.clone(),
+        }
+    }
+
+    /// The entry as it should exist in `project`. Compose names volumes
+    /// `<project>_<key>`, so restoring into another project renames them and
+    /// moves the project label along.
+    pub fn for_project(&self, from: &str, project: &str) -> Self {
+        let mut entry = self.clone();
+        if from == project {
+            return entry;
+        }
+        if let Some(key) = self.name.strip_prefix(&format!("{from}_")) {
+            entry.name = format!("{project}_{key}");
+        }
+        entry.labels.insert(PROJECT_LABEL.to_string(), project.to_string());
+        entry
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectContainer {
+    pub id: String,
+    pub name: String,
+    pub service: String,
+    pub depends_on: Vec<String>,
+    pub running: bool,
+}
+
+impl ProjectContainer {
+    pub fn from_summary(summary: &ContainerSummary) -> Option<Self> {
+        let labels = summary.labels.clone().unwrap_or_default();
+        let id = summary.id.clone()?;
+        let name = summary
+            .names
+            .as_ref()
+            .and_then(|n| n.first())
+            .map(|n| n.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| id.clone());
+        Some(Self {
+            name,
+            service: labels.get(SERVICE_LABEL).cloned().unwrap_or_default(),
+            depends_on: labels.get(DEPENDS_ON_LABEL).map(|v| parse_depends_on(v)).unwrap_or_default(),
+            running: summary.state == Some(ContainerSummaryStateEnum::RUNNING),
+            id,
+        })
+    }
+}
+
+/// Parses the `com.docker.compose.depends_on` label, a comma separated list of
+/// `service:condition:restart` entries.
+pub fn parse_depends_on(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter_map(|dep| dep.split(':').next())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Containers ordered so that every service comes after the services it
+/// depends on. Stopping walks this order in reverse. Services caught in a
+/// dependency cycle are appended in name order.
+pub fn start_order(containers: &[ProjectContainer]) -> Vec<ProjectContainer> {
+    let mut by_service: BTreeMap<&str, Vec<&ProjectContainer>> = BTreeMap::new();
+    for c in containers {
+        by_service.entry(c.service.as_str()).or_default().push(c);
+    }
+
+    let deps: BTreeMap<&str, BTreeSet<&str>> = by_service
+        .iter()
+        .map(|(service, members)| {
+            let deps = members
+                .iter()
+                .flat_map(|c| c.depends_on.iter().map(String::as_str))
+                .filter(|d| d != service && by_service.contains_key(d))
+                .collect();
+            (*service, deps)
+        })
+        .collect();
+
+    let mut done: BTreeSet<&str> = BTreeSet::new();
+    let mut ordered = Vec::with_capacity(containers.len());
+    while done.len() < by_service.len() {
+        let mut ready: Vec<&str> = deps
+            .iter()
+            .filter(|(s, d)| !done.contains(*s) && d.iter().all(|dep| done.contains(dep)))
+            .map(|(s, _)| *s)
+            .collect();
+        if ready.is_empty() {
+            ready = deps.keys().filter(|s| !done.contains(*s)).copied().collect();
+        }
+        for service in ready {
+            let mut members = by_service[service].clone();
+            members.sort_by(|a, b| a.name.cmp(&b.name));
+            ordered.extend(members.into_iter().cloned());
+            done.insert(service);
+        }
+    }
+    ordered
+}
+
+fn project_filter(project: &str) -> HashMap<String, Vec<String>> {
+    let mut filters = HashMap::new();
+    filters.insert("label".to_string(), vec![format!("{PROJECT_LABEL}={project}")]);
+    filters
+}
+
+pub async fn list_volumes(docker: &Docker, project: &str) -> Result<Vec<Volume>> {
+    let opts = ListVolumesOptions { filters: Some(project_filter(project)) };
+    let mut volumes = docker
+        .list_volumes(Some(opts))
+        .await
+        .with_context(|| format!("Failed to list volumes of project {project}"))?
+        .volumes
+        .unwrap_or_default();
+    volumes.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(volumes)
+}
+
+pub async fn list_containers(docker: &Docker, project: &str) -> Result<Vec<ProjectContainer>> {
+    let opts = ListContainersOptions {
+        all: true,
+        filters: Some(project_filter(project)),
+        ..Default::default()
+    };
+    let list = docker
+        .list_containers(Some(opts))
+        .await
+        .with_context(|| format!("Failed to list containers of project {project}"))?;
+    // The agent may be deployed as a service of the project it backs up; it
+    // must not stop itself.
+    let own_id = self_container_id();
+    Ok(list
+        .iter()
+        .filter_map(ProjectContainer::from_summary)
+        .filter(|c| own_id.as_deref() != Some(c.id.as_str()))
+        .collect())
+}
+
+/// Stops the running containers of the project, dependents first. Returns the
+/// stopped containers in stop order; on failure the ones already stopped are
+/// started again before the error is returned.
+pub async fn stop_project(
+    docker: &Docker,
+    containers: &[ProjectContainer],
+    logger: &JobLogger,
+) -> Result<Vec<ProjectContainer>> {
+    let mut stopped = Vec::new();
+    for c in start_order(containers).into_iter().rev().filter(|c| c.running) {
+        logger.log("info", format!("Stopping container {} (service {})", c.name, c.service));
+        if let Err(e) = stop_container(docker, &c.id).await {
+            start_project(docker, &stopped, logger).await;
+            return Err(e);
+        }
+        stopped.push(c);
+    }
+    Ok(stopped)
+}
+
+/// Starts containers returned by [`stop_project`] again, dependencies first.
+/// Failures are logged so every container still gets its chance to start.
+pub async fn start_project(docker: &Docker, stopped: &[ProjectContainer], logger: &JobLogger) {
+    for c in stopped.iter().rev() {
+        if let Err(e) = start_container(docker, &c.id).await {
+            logger.log("error", format!("Failed to restart container {}: {e}", c.name));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use super::project::{MANIFEST_FILE, Manifest, list_containers, start_project, stop_project};
+use crate::domain::docker_volume::docker::{
+    create_volume, replace_volume_contents, resolve_helper_image, volume_exists,
+};
+use crate::domain::docker_volume::endpoint::connect;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Recreates every volume listed in the manifest: missing volumes are created
+/// with their original driver, options and labels, existing ones are wiped.
+/// Volumes are renamed when restoring into a different project.
+pub async fn run(cfg: DatabaseConfig, archive: PathBuf, logger: Arc<JobLogger>) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        futures::executor::block_on(async move {
+            let project = cfg.database.clone();
+            logger.log("info", format!("Starting docker-compose restore for {} (project {project})", cfg.name));
+
+            let unpacked = tempfile::TempDir::new()?;
+            tar::Archive::new(std::fs::File::open(&archive)?)
+                .unpack(unpacked.path())
+                .with_context(|| format!("Failed to unpack {}", archive.display()))?;
+
+            let manifest: Manifest = serde_json::from_slice(
+                &std::fs::read(unpacked.path().join(MANIFEST_FILE))
+                    .context("Invalid docker-compose archive: manifest.json not found")?,
+            )
+            .context("Invalid docker-compose manifest")?;
+            if manifest.project != project {
+                logger.log(
+                    "info",
+                    format!("Archive was taken from project {}, restoring into {project}", manifest.project),
+                );
+            }
+
+            let docker = connect(&cfg).await?;
+            let image = resolve_helper_image(&docker, &cfg).await?;
+
+            let containers = list_containers(&docker, &project).await?;
+            let stopped = stop_project(&docker, &containers, &logger).await?;
+
+            let result = async {
+                for source in &manifest.volumes {
+                    let entry = source.for_project(&manifest.project, &project);
+
+                    if volume_exists(&docker, &entry.name).await? {
+                        logger.log("info", format!("Replacing content of volume {}", entry.name));
+                    } else {
+                        logger.log("info", format!("Creating volume {} (driver {})", entry.name, entry.driver));
+                        create_volume(
+                            &docker,
+                            &entry.name,
+                            &entry.driver,
+                            entry.driver_opts.clone(),
+                            entry.labels.clone(),
+                        )
+                        .await?;
+                    }
+
+                    let start = Instant::now();
+                    let volume_tar = unpacked.path().join(&source.file);
+                    replace_volume_contents(&docker, &image, &entry.name, &cfg.generated_id, &volume_tar).await?;
+                    let duration_ms = start.elapsed().as_millis() as f64;
+                    logger.log_command(
+                        format!("docker upload_to_container ({})", entry.name),
+                        None,
+                        Some(0),
+                        Some(duration_ms),
+                    );
+                }
+                anyhow::Ok(())
+            }
+            .await;
+
+            start_project(&docker, &stopped, &logger).await;
+
+            if result.is_ok() {
+                logger.log("info", format!("Docker-compose restore completed for {}", cfg.name));
+            }
+            result
+        })
+    })
+    .await?
+}
@@ -0,0 +1,138 @@
+use crate::domain::docker_volume::docker::{ExecOutput, container_running, exec_streaming};
+use crate::domain::docker_volume::endpoint::connect;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+use std::path::Path;
+use std::time::Instant;
+
+/// Where dump and restore tools run (`options.execution`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Execution {
+    /// The agent's bundled clients, connecting over the network.
+    Local,
+    /// The clients shipped in the database's own container (`container_name`),
+    /// run through the Docker exec API with data streamed over the API.
+    Container(String),
+}
+
+impl Execution {
+    pub fn from_config(cfg: &DatabaseConfig) -> Result<Self> {
+        match cfg.options.get("execution").and_then(|v| v.as_str()) {
+            None | Some("local") => Ok(Self::Local),
+            Some("docker_exec") => match cfg.container_name.as_deref().map(str::trim) {
+                Some(name) if !name.is_empty() => Ok(Self::Container(name.to_string())),
+                _ => anyhow::bail!("execution 'docker_exec' requires container_name"),
+            },
+            Some(other) => anyhow::bail!("Unknown execution '{other}', expected local or docker_exec"),
+        }
+    }
+}
+
+/// A command to run in the database container. Passwords go in `env` when the
+/// client can read them from there, keeping them out of the process list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecCommand {
+    pub args: Vec<String>,
+    pub env: Vec<String>,
+}
+
+impl ExecCommand {
+    pub fn new<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { args: args.into_iter().map(Into::into).collect(), env: Vec::new() }
+    }
+
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.env.push(format!("{key}={value}"));
+        self
+    }
+
+    /// `sh -c script` with `params` as `$1`, `$2`, …
+    pub fn shell<S: Into<String>>(script: &str, params: impl IntoIterator<Item = S>) -> Self {
+        let mut args = vec!["sh".to_string(), "-c".to_string(), script.to_string(), "sh".to_string()];
+        args.extend(params.into_iter().map(Into::into));
+        Self { args, env: Vec::new() }
+    }
+}
+
+/// Runs `command` in `container` on the database's Docker endpoint, streaming
+/// `stdin` in and stdout to `stdout`, and logs it under `label`. Fails on a
+/// non-zero exit.
+pub async fn run(
+    cfg: &DatabaseConfig,
+    container: &str,
+    label: &str,
+    command: ExecCommand,
+    stdin: Option<&Path>,
+    stdout: Option<&Path>,
+    logger: &JobLogger,
+) -> Result<ExecOutput> {
+    let docker = connect(cfg).await?;
+    if !container_running(&docker, container).await {
+        anyhow::bail!("Container {container} is not running");
+    }
+
+    let label = format!("docker exec {container} {label}");
+    let start = Instant::now();
+    let res = exec_streaming(&docker, container, command.args, command.env, stdin, stdout).await;
+    let duration_ms = start.elapsed().as_millis() as f64;
+
+    match res {
+        Ok(out) => {
+            let payload = if out.output.is_empty() { None } else { Some(out.output.clone()) };
+            logger.log_command(label.clone(), payload, Some(out.exit_code as i32), Some(duration_ms));
+            if out.exit_code != 0 {
+                anyhow::bail!("{label} failed (exit {}): {}", out.exit_code, out.output);
+            }
+            Ok(out)
+        }
+        Err(e) => {
+            logger.log_command(label, Some(e.to_string()), Some(-1), Some(duration_ms));
+            Err(e)
+        }
+    }
+}
+
+/// Whether `command` exits 0 in `container`; used for pings, so failures to
+/// reach Docker count as down rather than errors.
+pub async fn probe(cfg: &DatabaseConfig, container: &str, command: ExecCommand) -> Result<bool> {
+    let Ok(docker) = connect(cfg).await else {
+        return Ok(false);
+    };
+    if !container_running(&docker, container).await {
+        return Ok(false);
+    }
+    let fut = exec_streaming(&docker, container, command.args, command.env, None, None);
+    match tokio::time::timeout(std::time::Duration::from_secs(10), fut).await {
+        Ok(Ok(out)) => Ok(out.exit_code == 0),
+        _ => Ok(false),
+    }
+}
+
+/// First line of `program --version`, run where the database's tools run;
+/// `None` when it cannot be run.
+pub async fn tool_version(cfg: &DatabaseConfig, program: &str) -> Option<String> {
+    let output = match Execution::from_config(cfg).ok()? {
+        Execution::Local => {
+            let out = tokio::process::Command::new(program).arg("--version").output().await.ok()?;
+            if !out.status.success() {
+                return None;
+            }
+            String::from_utf8_lossy(&out.stdout).into_owned()
+        }
+        Execution::Container(container) => {
+            let docker = connect(cfg).await.ok()?;
+            let args = vec![program.to_string(), "--version".to_string()];
+            let out = exec_streaming(&docker, &container, args, Vec::new(), None, None).await.ok()?;
+            if out.exit_code != 0 {
+                return None;
+            }
+            out.output
+        }
+    };
+    output.lines().next().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string)
+}
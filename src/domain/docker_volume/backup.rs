@@ -1,68 +1,122 @@
+use crate::domain::docker_volume::consistency::{
+    Consistency, STAGE_MOUNT, first_pass_script, second_pass_script,
+};
 use crate::domain::docker_volume::docker::{
-    client, create_helper, remove_helper, resolve_helper_image, start_container, stop_container,
+    EPHEMERAL_LABEL, create_volume, download_volume, remove_volume, resolve_helper_image,
+    run_helper_script,
 };
+use crate::domain::docker_volume::endpoint::connect;
+use crate::domain::docker_volume::metadata::{VolumeMeta, write_entry};
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
 use anyhow::{Context, Result};
-use bollard::query_parameters::DownloadFromContainerOptions;
-use futures_util::StreamExt;
+use bollard::Docker;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
 
 pub async fn run(cfg: DatabaseConfig, backup_dir: PathBuf, logger: Arc<JobLogger>) -> Result<PathBuf> {
     tokio::task::spawn_blocking(move || -> Result<PathBuf> {
         futures::executor::block_on(async move {
             logger.log("info", format!("Starting docker-volume backup for {}", cfg.name));
 
-            let docker = client()?;
-            let image = resolve_helper_image(&docker).await?;
+            let docker = connect(&cfg).await?;
+            let image = resolve_helper_image(&docker, &cfg).await?;
             logger.log("debug", format!("Helper image: {image}"));
 
-            if let Some(name) = &cfg.container_name {
-                logger.log("info", format!("Stopping container {name} for consistent backup"));
-                stop_container(&docker, name).await?;
-            }
-
-            let result = async {
-                let helper = create_helper(&docker, &image, &cfg.volume_name, &cfg.generated_id, true, None).await?;
+            let consistency = Consistency::from_config(&cfg)?;
 
-                let file_path = backup_dir.join(format!("{}.tar", cfg.generated_id));
-                let start = Instant::now();
+            let file_path = backup_dir.join(format!("{}.tar", cfg.generated_id));
+            let volume = docker
+                .inspect_volume(&cfg.volume_name)
+                .await
+                .with_context(|| format!("Failed to inspect volume {}", cfg.volume_name))?;
+            let mut out = std::fs::File::create(&file_path)
+                .with_context(|| format!("Failed to create backup file {}", file_path.display()))?;
+            write_entry(&mut out, &VolumeMeta::from_volume(&volume))?;
+            drop(out);
 
-                let dl_opts = DownloadFromContainerOptions { path: "/vol".to_string() };
-                let mut stream = docker.download_from_container(&helper.id, Some(dl_opts));
-
-                let mut out = File::create(&file_path)
-                    .await
-                    .with_context(|| format!("Failed to create backup file {}", file_path.display()))?;
-                let mut bytes_written: u64 = 0;
-                while let Some(chunk) = stream.next().await {
-                    let chunk = chunk.context("Error streaming volume archive from Docker")?;
-                    bytes_written += chunk.len() as u64;
-                    out.write_all(&chunk).await?;
+            let start = Instant::now();
+            let bytes_written = match (&cfg.container_name, &consistency) {
+                (Some(name), Consistency::TwoPass) => {
+                    two_pass(&docker, &image, &cfg, name, &file_path, &logger).await?
                 }
-                out.flush().await?;
-
-                let duration_ms = start.elapsed().as_millis() as f64;
-                logger.log_command("docker download_from_container", None, Some(0), Some(duration_ms));
-                logger.log("info", format!("Volume backup wrote {bytes_written} bytes to {}", file_path.display()));
-
-                remove_helper(&docker, &helper.id).await;
-                anyhow::Ok(file_path)
-            }
-            .await;
-
-            if let Some(name) = &cfg.container_name {
-                if let Err(e) = start_container(&docker, name).await {
-                    logger.log("error", format!("Failed to restart container {name}: {e}"));
+                (Some(name), _) => {
+                    consistency.freeze(&docker, name, &logger).await?;
+                    let frozen = Instant::now();
+                    let res = download_volume(&docker, &image, &cfg.volume_name, &cfg.generated_id, &file_path).await;
+                    consistency.thaw(&docker, name, &logger).await;
+                    logger.log(
+                        "info",
+                        format!("Container {name} was frozen for {} ms ({consistency:?})", frozen.elapsed().as_millis()),
+                    );
+                    res?
                 }
-            }
+                (None, _) => {
+                    download_volume(&docker, &image, &cfg.volume_name, &cfg.generated_id, &file_path).await?
+                }
+            };
+
+            let duration_ms = start.elapsed().as_millis() as f64;
+            logger.log_command("docker download_from_container", None, Some(0), Some(duration_ms));
+            logger.log("info", format!("Volume backup wrote {bytes_written} bytes to {}", file_path.display()));
 
-            result
+            Ok(file_path)
         })
     })
     .await?
 }
+
+/// Copies the live volume into an ephemeral staging volume, stops `container`
+/// only to bring the copy up to date, then archives the staging volume.
+async fn two_pass(
+    docker: &Docker,
+    image: &str,
+    cfg: &DatabaseConfig,
+    container: &str,
+    file_path: &std::path::Path,
+    logger: &JobLogger,
+) -> Result<u64> {
+    let staging = format!("portabase-stage-{}-{}", cfg.generated_id, &uuid::Uuid::new_v4().to_string()[..8]);
+    let labels = HashMap::from([(EPHEMERAL_LABEL.to_string(), "true".to_string())]);
+    create_volume(docker, &staging, "", HashMap::new(), labels).await?;
+    let binds = vec![
+        format!("{}:/vol:ro", cfg.volume_name),
+        format!("{staging}:{STAGE_MOUNT}"),
+    ];
+
+    let result = async {
+        logger.log("info", format!("Two-pass backup: copying {} while {container} runs", cfg.volume_name));
+        let start = Instant::now();
+        let (code, output) =
+            run_helper_script(docker, image, binds.clone(), &cfg.generated_id, first_pass_script()).await?;
+        logger.log_command(
+            "two-pass copy (live)",
+            Some(output.clone()),
+            Some(code as i32),
+            Some(start.elapsed().as_millis() as f64),
+        );
+        if code != 0 {
+            anyhow::bail!("Live copy of {} failed (exit {code}): {output}", cfg.volume_name);
+        }
+
+        Consistency::TwoPass.freeze(docker, container, logger).await?;
+        let frozen = Instant::now();
+        let delta = run_helper_script(docker, image, binds, &cfg.generated_id, second_pass_script()).await;
+        Consistency::TwoPass.thaw(docker, container, logger).await;
+        logger.log("info", format!("Container {container} was stopped for {} ms", frozen.elapsed().as_millis()));
+
+        let (code, output) = delta?;
+        logger.log_command("two-pass copy (delta)", Some(output.clone()), Some(code as i32), None);
+        if code != 0 {
+            anyhow::bail!("Delta copy of {} failed (exit {code}): {output}", cfg.volume_name);
+        }
+
+        download_volume(docker, image, &staging, &cfg.generated_id, file_path).await
+    }
+    .await;
+
+    remove_volume(docker, &staging).await;
+    result
+}
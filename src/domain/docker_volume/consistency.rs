@@ -0,0 +1,127 @@
+use super::docker::{
+    exec_in_container, pause_container, start_container, stop_container, unpause_container,
+};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+use bollard::Docker;
+
+/// How `container_name` is kept from writing to the volume while it is copied
+/// (`options.consistency`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Consistency {
+    /// Stop the container for the whole copy.
+    Stop,
+    /// Freeze the container's processes with `docker pause` for the copy.
+    Pause,
+    /// Run `options.quiesce_command` inside the container before the copy and
+    /// `options.unquiesce_command` after it, e.g. `fsfreeze` or a flush-and-lock.
+    Exec { quiesce: String, unquiesce: Option<String> },
+    /// Copy the live volume to a staging volume, then stop the container only
+    /// while the changes made meanwhile are applied to the copy.
+    TwoPass,
+}
+
+impl Consistency {
+    pub fn from_config(cfg: &DatabaseConfig) -> Result<Self> {
+        let command = |key: &str| {
+            cfg.options
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+        };
+        match cfg.options.get("consistency").and_then(|v| v.as_str()) {
+            None | Some("stop") => Ok(Self::Stop),
+            Some("pause") => Ok(Self::Pause),
+            Some("exec") => match command("quiesce_command") {
+                Some(quiesce) => Ok(Self::Exec { quiesce, unquiesce: command("unquiesce_command") }),
+                None => anyhow::bail!("consistency 'exec' requires options.quiesce_command"),
+            },
+            Some("two_pass") => Ok(Self::TwoPass),
+            Some(other) => {
+                anyhow::bail!("Unknown consistency '{other}', expected stop, pause, exec or two_pass")
+            }
+        }
+    }
+
+    /// Makes `container` stop writing. Two-pass freezes by stopping, like
+    /// [`Consistency::Stop`], but only around its second pass.
+    pub async fn freeze(&self, docker: &Docker, container: &str, logger: &JobLogger) -> Result<()> {
+        match self {
+            Self::Stop | Self::TwoPass => {
+                logger.log("info", format!("Stopping container {container} for consistent backup"));
+                stop_container(docker, container).await
+            }
+            Self::Pause => {
+                logger.log("info", format!("Pausing container {container} for consistent backup"));
+                pause_container(docker, container).await
+            }
+            Self::Exec { quiesce, .. } => {
+                logger.log("info", format!("Running quiesce command in {container}"));
+                let (code, output) = exec_in_container(docker, container, quiesce).await?;
+                logger.log_command("quiesce_command", Some(output.clone()), Some(code as i32), None);
+                if code != 0 {
+                    anyhow::bail!("Quiesce command failed in {container} (exit {code}): {output}");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Undoes [`Consistency::freeze`]. Failures are logged rather than returned
+    /// so they never mask the backup result.
+    pub async fn thaw(&self, docker: &Docker, container: &str, logger: &JobLogger) {
+        let res = match self {
+            Self::Stop | Self::TwoPass => start_container(docker, container).await,
+            Self::Pause => unpause_container(docker, container).await,
+            Self::Exec { unquiesce: None, .. } => Ok(()),
+            Self::Exec { unquiesce: Some(cmd), .. } => match exec_in_container(docker, container, cmd).await {
+                Ok((0, _)) => Ok(()),
+                Ok((code, output)) => Err(anyhow::anyhow!("exit {code}: {output}")),
+                Err(e) => Err(e),
+            },
+        };
+        if let Err(e) = res {
+            logger.log("error", format!("Failed to resume container {container}: {e}"));
+        }
+    }
+}
+
+/// Mount point of the staging volume in two-pass helpers; the source volume
+/// is mounted read-only at `/vol`.
+pub const STAGE_MOUNT: &str = "/stage";
+const MARKER: &str = ".portabase-pass1";
+
+/// First pass, against the running container: stamp a marker, then copy
+/// everything.
+pub fn first_pass_script() -> String {
+    format!("set -e; touch {STAGE_MOUNT}/{MARKER}; cp -a /vol/. {STAGE_MOUNT}/")
+}
+
+/// Second pass, with the container stopped: drop what was deleted since the
+/// first pass, recopy entries modified after the marker, and pick up entries
+/// moved into modified directories.
+pub fn second_pass_script() -> String {
+    format!(
+        r#"set -e
+cd {STAGE_MOUNT}
+find . -depth ! -path . ! -path ./{MARKER} | while IFS= read -r f; do
+  if [ ! -e "/vol/$f" ] && [ ! -L "/vol/$f" ]; then rm -rf "{STAGE_MOUNT}/$f"; fi
+done
+cd /vol
+find . -newer {STAGE_MOUNT}/{MARKER} | while IFS= read -r f; do
+  if [ -d "$f" ] && [ ! -L "$f" ]; then
+    mkdir -p "{STAGE_MOUNT}/$f"
+    find "$f" -mindepth 1 -maxdepth 1 | while IFS= read -r c; do
+      if [ ! -e "{STAGE_MOUNT}/$c" ] && [ ! -L "{STAGE_MOUNT}/$c" ]; then cp -a "$c" "{STAGE_MOUNT}/$c"; fi
+    done
+  else
+    rm -rf "{STAGE_MOUNT}/$f"
+    cp -a "$f" "{STAGE_MOUNT}/$f"
+  fi
+done
+rm -f {STAGE_MOUNT}/{MARKER}"#
+    )
+}
@@ -3,6 +3,8 @@ use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use super::docker::{resolve_helper_image, volume_usage};
+use super::endpoint::connect;
 use super::{backup, ping, restore};
 use crate::domain::factory::Database;
 use crate::services::backup::logger::JobLogger;
@@ -36,6 +38,12 @@ impl Database for DockerVolumeDatabase {
         res
     }
 
+    async fn estimated_size(&self) -> Option<u64> {
+        let docker = connect(&self.cfg).await.ok()?;
+        let image = resolve_helper_image(&docker, &self.cfg).await.ok()?;
+        volume_usage(&docker, &image, &self.cfg.volume_name, &self.cfg.generated_id).await.ok()
+    }
+
     async fn restore(&self, file: &Path, logger: Arc<JobLogger>) -> Result<()> {
         FileLock::acquire(&self.cfg.generated_id, DbOpLock::Restore.as_str()).await?;
         let res = restore::run(self.cfg.clone(), file.to_path_buf(), logger).await;
@@ -1,13 +1,22 @@
 #![allow(dead_code)]
 
+use super::metadata::read_entry;
+use crate::services::config::DatabaseConfig;
 use anyhow::{Context, Result};
 use bollard::Docker;
-use bollard::models::{ContainerCreateBody, HostConfig};
+use bollard::container::LogOutput;
+use bollard::exec::StartExecResults;
+use bollard::models::{ContainerCreateBody, ExecConfig, HostConfig, VolumeCreateRequest};
 use bollard::query_parameters::{
-    CreateContainerOptions, InspectContainerOptions, ListContainersOptions,
-    RemoveContainerOptions, StartContainerOptions, StopContainerOptions,
+    CreateContainerOptions, CreateImageOptions, DownloadFromContainerOptions, InspectContainerOptions,
+    ListContainersOptions, ListVolumesOptions, LogsOptions, RemoveContainerOptions,
+    RemoveVolumeOptions, StartContainerOptions, StopContainerOptions, UploadToContainerOptions,
+    WaitContainerOptions,
 };
+use futures_util::StreamExt;
 use std::collections::HashMap;
+use std::path::Path;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
 use tracing::{info, warn};
 use uuid::Uuid;
 
@@ -35,23 +44,71 @@ pub fn parse_container_id(mountinfo: &str, cgroup: &str) -> Option<String> {
     None
 }
 
-pub async fn resolve_helper_image(docker: &Docker) -> Result<String> {
-    if let Ok(img) = std::env::var("PORTABASE_HELPER_IMAGE") {
-        if !img.trim().is_empty() {
-            return Ok(img);
-        }
-    }
+/// Id of the container the agent runs in, if any.
+pub fn self_container_id() -> Option<String> {
     let mountinfo = std::fs::read_to_string("/proc/self/mountinfo").unwrap_or_default();
     let cgroup = std::fs::read_to_string("/proc/self/cgroup").unwrap_or_default();
-    let id = parse_container_id(&mountinfo, &cgroup).context(
-        "Could not determine own container id; set PORTABASE_HELPER_IMAGE to a locally-present image",
-    )?;
-    let info = docker
-        .inspect_container(&id, None::<InspectContainerOptions>)
-        .await
-        .with_context(|| format!("Failed to inspect self container {id}"))?;
-    info.image
-        .context("Self container inspection returned no image reference")
+    parse_container_id(&mountinfo, &cgroup)
+}
+
+/// Fallback helper when the agent's own image is not available on the target
+/// daemon (remote hosts, Podman, agent not containerized).
+pub const DEFAULT_HELPER_IMAGE: &str = "busybox:stable";
+
+/// Picks the image helper containers run from, in order: `options.helper_image`,
+/// `PORTABASE_HELPER_IMAGE`, the agent's own image when the target daemon runs
+/// the agent, then [`DEFAULT_HELPER_IMAGE`]. Images missing on the daemon are
+/// pulled.
+pub async fn resolve_helper_image(docker: &Docker, cfg: &DatabaseConfig) -> Result<String> {
+    let configured = cfg
+        .options
+        .get("helper_image")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| std::env::var("PORTABASE_HELPER_IMAGE").ok())
+        .filter(|img| !img.trim().is_empty());
+
+    let image = match configured {
+        Some(image) => image,
+        None => match self_image(docker).await {
+            Some(image) => return Ok(image),
+            None => DEFAULT_HELPER_IMAGE.to_string(),
+        },
+    };
+    ensure_image(docker, &image).await?;
+    Ok(image)
+}
+
+async fn self_image(docker: &Docker) -> Option<String> {
+    let id = self_container_id()?;
+    match docker.inspect_container(&id, None::<InspectContainerOptions>).await {
+        Ok(info) => info.image,
+        Err(e) => {
+            info!("Agent container {id} not found on target daemon ({e}), using fallback helper image");
+            None
+        }
+    }
+}
+
+pub async fn ensure_image(docker: &Docker, image: &str) -> Result<()> {
+    if docker.inspect_image(image).await.is_ok() {
+        return Ok(());
+    }
+    info!("Pulling helper image {image}");
+    let (name, tag) = match image.rsplit_once(':') {
+        Some((name, tag)) if !tag.contains('/') => (name, tag),
+        _ => (image, "latest"),
+    };
+    let opts = CreateImageOptions {
+        from_image: Some(name.to_string()),
+        tag: Some(tag.to_string()),
+        ..Default::default()
+    };
+    let mut stream = docker.create_image(Some(opts), None, None);
+    while let Some(item) = stream.next().await {
+        item.with_context(|| format!("Failed to pull helper image {image}"))?;
+    }
+    Ok(())
 }
 
 pub struct Helper {
@@ -70,12 +127,51 @@ pub async fn create_helper(
         "{volume_name}:{HELPER_MOUNT}{}",
         if read_only { ":ro" } else { "" }
     );
-    let mut labels = HashMap::new();
-    labels.insert(EPHEMERAL_LABEL.to_string(), "true".to_string());
-    labels.insert("com.docker.compose.project".to_string(), String::new());
-    labels.insert("com.docker.compose.service".to_string(), String::new());
-    labels.insert("com.docker.compose.oneoff".to_string(), String::new());
+    create_helper_with_binds(docker, image, vec![bind], generated_id, cmd)
+        .await
+        .with_context(|| format!("Failed to create helper container for volume {volume_name}"))
+}
 
+/// Like [`create_helper`], with arbitrary `volume:path[:ro]` binds.
+pub async fn create_helper_with_binds(
+    docker: &Docker,
+    image: &str,
+    binds: Vec<String>,
+    generated_id: &str,
+    cmd: Option<Vec<String>>,
+) -> Result<Helper> {
+    let host_config = HostConfig {
+        binds: Some(binds),
+        auto_remove: Some(false),
+        ..Default::default()
+    };
+    create_helper_container(docker, image, host_config, generated_id, cmd).await
+}
+
+/// A helper with no volumes. It joins the network namespace of `container`
+/// when given, so whatever that container reaches, the helper reaches too.
+pub async fn create_network_helper(
+    docker: &Docker,
+    image: &str,
+    container: Option<&str>,
+    generated_id: &str,
+    cmd: Option<Vec<String>>,
+) -> Result<Helper> {
+    let host_config = HostConfig {
+        network_mode: container.map(|name| format!("container:{name}")),
+        auto_remove: Some(false),
+        ..Default::default()
+    };
+    create_helper_container(docker, image, host_config, generated_id, cmd).await
+}
+
+async fn create_helper_container(
+    docker: &Docker,
+    image: &str,
+    host_config: HostConfig,
+    generated_id: &str,
+    cmd: Option<Vec<String>>,
+) -> Result<Helper> {
     let name = format!(
         "portabase-vol-{generated_id}-{}",
         &Uuid::new_v4().to_string()[..8]
@@ -84,9 +180,49 @@ pub async fn create_helper(
     let body = ContainerCreateBody {
         image: Some(image.to_string()),
         cmd,
-        labels: Some(labels),
+        labels: Some(ephemeral_labels()),
+        host_config: Some(host_config),
+        ..Default::default()
+    };
+
+    let opts = CreateContainerOptions {
+        name: Some(name),
+        ..Default::default()
+    };
+
+    let res = docker
+        .create_container(Some(opts), body)
+        .await
+        .context("Failed to create helper container")?;
+
+    Ok(Helper { id: res.id })
+}
+
+/// Labels marking a container as ours to sweep, and keeping Compose from
+/// adopting it.
+fn ephemeral_labels() -> HashMap<String, String> {
+    HashMap::from([
+        (EPHEMERAL_LABEL.to_string(), "true".to_string()),
+        ("com.docker.compose.project".to_string(), String::new()),
+        ("com.docker.compose.service".to_string(), String::new()),
+        ("com.docker.compose.oneoff".to_string(), String::new()),
+    ])
+}
+
+/// Creates and starts a throwaway container running `image`'s own entrypoint
+/// with `env`. Remove it with [`remove_helper`]; [`sweep_ephemeral`] reclaims
+/// it otherwise.
+pub async fn run_ephemeral(docker: &Docker, image: &str, env: Vec<String>, generated_id: &str) -> Result<Helper> {
+    let name = format!(
+        "portabase-verify-{generated_id}-{}",
+        &Uuid::new_v4().to_string()[..8]
+    );
+
+    let body = ContainerCreateBody {
+        image: Some(image.to_string()),
+        env: Some(env),
+        labels: Some(ephemeral_labels()),
         host_config: Some(HostConfig {
-            binds: Some(vec![bind]),
             auto_remove: Some(false),
             ..Default::default()
         }),
@@ -101,11 +237,215 @@ pub async fn create_helper(
     let res = docker
         .create_container(Some(opts), body)
         .await
-        .with_context(|| format!("Failed to create helper container for volume {volume_name}"))?;
+        .with_context(|| format!("Failed to create container from {image}"))?;
+    let helper = Helper { id: res.id };
 
-    Ok(Helper { id: res.id })
+    if let Err(e) = start_container(docker, &helper.id).await {
+        remove_helper(docker, &helper.id).await;
+        return Err(e);
+    }
+    Ok(helper)
+}
+
+/// Runs `script` with `sh -c` in a helper holding `binds`, waits for it and
+/// removes it. Returns the exit code and the combined output.
+pub async fn run_helper_script(
+    docker: &Docker,
+    image: &str,
+    binds: Vec<String>,
+    generated_id: &str,
+    script: String,
+) -> Result<(i64, String)> {
+    let cmd = vec!["sh".to_string(), "-c".to_string(), script];
+    let helper = create_helper_with_binds(docker, image, binds, generated_id, Some(cmd)).await?;
+
+    let result = async {
+        start_container(docker, &helper.id).await?;
+
+        let mut wait = docker.wait_container(&helper.id, None::<WaitContainerOptions>);
+        let exit_code = match wait.next().await {
+            Some(Ok(res)) => res.status_code,
+            Some(Err(bollard::errors::Error::DockerContainerWaitError { code, .. })) => code,
+            Some(Err(e)) => return Err(e).context("Failed waiting for helper container"),
+            None => -1,
+        };
+
+        let mut logs = docker.logs(
+            &helper.id,
+            Some(LogsOptions { stdout: true, stderr: true, ..Default::default() }),
+        );
+        let mut output = String::new();
+        while let Some(Ok(line)) = logs.next().await {
+            output.push_str(&line.to_string());
+        }
+        anyhow::Ok((exit_code, output.trim().to_string()))
+    }
+    .await;
+
+    remove_helper(docker, &helper.id).await;
+    result
+}
+
+/// Bytes used by `volume`, measured with `du` in a helper.
+pub async fn volume_usage(docker: &Docker, image: &str, volume: &str, generated_id: &str) -> Result<u64> {
+    let binds = vec![format!("{volume}:/vol:ro")];
+    let (code, output) =
+        run_helper_script(docker, image, binds, generated_id, "du -sk /vol | cut -f1".to_string()).await?;
+    if code != 0 {
+        anyhow::bail!("du failed (exit {code}): {output}");
+    }
+    let kib: u64 = output.lines().last().unwrap_or_default().trim().parse()?;
+    Ok(kib.saturating_mul(1024))
+}
+
+/// Runs `command` with `sh -c` inside a running container. Returns the exit
+/// code and the combined output.
+pub async fn exec_in_container(docker: &Docker, container: &str, command: &str) -> Result<(i64, String)> {
+    let exec = docker
+        .create_exec(
+            container,
+            ExecConfig {
+                cmd: Some(vec!["sh".to_string(), "-c".to_string(), command.to_string()]),
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .with_context(|| format!("Failed to create exec in container {container}"))?;
+
+    let mut output = String::new();
+    if let StartExecResults::Attached { output: mut stream, .. } = docker
+        .start_exec(&exec.id, None)
+        .await
+        .with_context(|| format!("Failed to run exec in container {container}"))?
+    {
+        while let Some(Ok(chunk)) = stream.next().await {
+            output.push_str(&chunk.to_string());
+        }
+    }
+
+    let exit_code = docker
+        .inspect_exec(&exec.id)
+        .await
+        .context("Failed to inspect exec")?
+        .exit_code
+        .unwrap_or(-1);
+    Ok((exit_code, output.trim().to_string()))
+}
+
+/// Result of [`exec_streaming`]: stdout is counted when redirected to a file,
+/// otherwise it is kept in `output` after stderr.
+#[derive(Debug, Default)]
+pub struct ExecOutput {
+    pub exit_code: i64,
+    pub output: String,
+    pub stdout_bytes: u64,
+}
+
+/// Runs `cmd` inside a running container without a shell. `stdin`, when
+/// given, is streamed into the process and closed at end of file; stdout is
+/// streamed into the `stdout` file when given. `env` entries are `KEY=value`.
+pub async fn exec_streaming(
+    docker: &Docker,
+    container: &str,
+    cmd: Vec<String>,
+    env: Vec<String>,
+    stdin: Option<&Path>,
+    stdout: Option<&Path>,
+) -> Result<ExecOutput> {
+    let exec = docker
+        .create_exec(
+            container,
+            ExecConfig {
+                cmd: Some(cmd),
+                env: Some(env),
+                attach_stdin: Some(stdin.is_some()),
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .with_context(|| format!("Failed to create exec in container {container}"))?;
+
+    let mut source = match stdin {
+        Some(path) => Some(
+            tokio::fs::File::open(path)
+                .await
+                .with_context(|| format!("Failed to open {}", path.display()))?,
+        ),
+        None => None,
+    };
+    let mut sink = match stdout {
+        Some(path) => Some(
+            tokio::fs::File::create(path)
+                .await
+                .with_context(|| format!("Failed to create {}", path.display()))?,
+        ),
+        None => None,
+    };
+
+    let mut result = ExecOutput::default();
+    let mut stdout_text = String::new();
+    if let StartExecResults::Attached { output: mut stream, mut input } = docker
+        .start_exec(&exec.id, None)
+        .await
+        .with_context(|| format!("Failed to run exec in container {container}"))?
+    {
+        // Feeding stdin and draining the output must overlap, or a process
+        // that writes while reading fills its pipe and both sides stall.
+        let feed = async {
+            if let Some(file) = source.as_mut() {
+                tokio::io::copy(file, &mut input).await.context("Failed to stream exec stdin")?;
+            }
+            input.shutdown().await.context("Failed to close exec stdin")?;
+            anyhow::Ok(())
+        };
+        let drain = async {
+            while let Some(chunk) = stream.next().await {
+                match chunk.context("Exec output stream failed")? {
+                    LogOutput::StdOut { message } => match sink.as_mut() {
+                        Some(file) => {
+                            file.write_all(&message).await.context("Failed to write exec output")?;
+                            result.stdout_bytes += message.len() as u64;
+                        }
+                        None => stdout_text.push_str(&String::from_utf8_lossy(&message)),
+                    },
+                    other => result.output.push_str(&other.to_string()),
+                }
+            }
+            if let Some(file) = sink.as_mut() {
+                file.flush().await?;
+            }
+            anyhow::Ok(())
+        };
+        let (fed, drained) = futures::join!(feed, drain);
+        drained?;
+        fed?;
+    }
+    result.output.push_str(&stdout_text);
+    result.output = result.output.trim().to_string();
+
+    result.exit_code = docker
+        .inspect_exec(&exec.id)
+        .await
+        .context("Failed to inspect exec")?
+        .exit_code
+        .unwrap_or(-1);
+    Ok(result)
 }
 
+/// Whether `container` exists and is running.
+pub async fn container_running(docker: &Docker, container: &str) -> bool {
+    docker
+        .inspect_container(container, None::<InspectContainerOptions>)
+        .await
+        .ok()
+        .and_then(|info| info.state)
+        .and_then(|state| state.running)
+        .unwrap_or(false)
+}
 
 pub async fn remove_helper(docker: &Docker, id: &str) {
     let stop_opts = StopContainerOptions {
@@ -133,6 +473,147 @@ pub async fn remove_helper(docker: &Docker, id: &str) {
     }
 }
 
+pub async fn volume_exists(docker: &Docker, volume_name: &str) -> Result<bool> {
+    match docker.inspect_volume(volume_name).await {
+        Ok(_) => Ok(true),
+        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn create_volume(
+    docker: &Docker,
+    volume_name: &str,
+    driver: &str,
+    driver_opts: HashMap<String, String>,
+    labels: HashMap<String, String>,
+) -> Result<()> {
+    let req = VolumeCreateRequest {
+        name: Some(volume_name.to_string()),
+        driver: (!driver.is_empty()).then(|| driver.to_string()),
+        driver_opts: Some(driver_opts),
+        labels: Some(labels),
+        ..Default::default()
+    };
+    docker
+        .create_volume(req)
+        .await
+        .with_context(|| format!("Failed to create volume {volume_name}"))?;
+    Ok(())
+}
+
+pub async fn remove_volume(docker: &Docker, volume_name: &str) {
+    if let Err(e) = docker.remove_volume(volume_name, None::<RemoveVolumeOptions>).await {
+        warn!("Failed to remove volume {volume_name}: {e}");
+    }
+}
+
+/// Streams the content of `volume_name` as a tar (entries under `vol/`) through
+/// a read-only helper, appending it to `dest`. Returns the number of bytes
+/// written.
+pub async fn download_volume(
+    docker: &Docker,
+    image: &str,
+    volume_name: &str,
+    generated_id: &str,
+    dest: &Path,
+) -> Result<u64> {
+    let helper = create_helper(docker, image, volume_name, generated_id, true, None).await?;
+
+    let result = async {
+        let dl_opts = DownloadFromContainerOptions { path: HELPER_MOUNT.to_string() };
+        let mut stream = docker.download_from_container(&helper.id, Some(dl_opts));
+
+        let mut out = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dest)
+            .await
+            .with_context(|| format!("Failed to create backup file {}", dest.display()))?;
+        let mut bytes_written: u64 = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.context("Error streaming volume archive from Docker")?;
+            bytes_written += chunk.len() as u64;
+            out.write_all(&chunk).await?;
+        }
+        out.flush().await?;
+        anyhow::Ok(bytes_written)
+    }
+    .await;
+
+    remove_helper(docker, &helper.id).await;
+    result
+}
+
+/// Wipes `volume_name` and unpacks `archive` (as produced by
+/// [`download_volume`]) into it, skipping a leading metadata entry.
+pub async fn replace_volume_contents(
+    docker: &Docker,
+    image: &str,
+    volume_name: &str,
+    generated_id: &str,
+    archive: &Path,
+) -> Result<()> {
+    let helper = create_helper(
+        docker,
+        image,
+        volume_name,
+        generated_id,
+        false,
+        Some(vec![
+            "sh".into(),
+            "-c".into(),
+            "trap 'exit 0' TERM; sleep 2147483647 & wait".into(),
+        ]),
+    )
+    .await?;
+
+    let result = async {
+        start_container(docker, &helper.id).await?;
+
+        let exec = docker
+            .create_exec(
+                &helper.id,
+                ExecConfig {
+                    cmd: Some(vec![
+                        "sh".to_string(),
+                        "-c".to_string(),
+                        "rm -rf /vol/* /vol/.[!.]* 2>/dev/null || true".to_string(),
+                    ]),
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .context("Failed to create wipe exec")?;
+
+        if let StartExecResults::Attached { mut output, .. } =
+            docker.start_exec(&exec.id, None).await.context("Failed to run wipe exec")?
+        {
+            while output.next().await.is_some() {}
+        }
+
+        let offset = read_entry(archive)?.map(|(_, offset)| offset).unwrap_or(0);
+        let mut file = tokio::fs::File::open(archive)
+            .await
+            .with_context(|| format!("Failed to open {}", archive.display()))?;
+        file.seek(std::io::SeekFrom::Start(offset)).await?;
+        let stream = tokio_util::io::ReaderStream::new(file);
+
+        let up_opts = UploadToContainerOptions { path: "/".to_string(), ..Default::default() };
+        docker
+            .upload_to_container(&helper.id, Some(up_opts), bollard::body_try_stream(stream))
+            .await
+            .context("Failed to upload volume archive")?;
+        anyhow::Ok(())
+    }
+    .await;
+
+    remove_helper(docker, &helper.id).await;
+    result
+}
+
 pub async fn stop_container(docker: &Docker, name: &str) -> Result<()> {
     docker
         .stop_container(name, None::<StopContainerOptions>)
@@ -140,6 +621,20 @@ pub async fn stop_container(docker: &Docker, name: &str) -> Result<()> {
         .with_context(|| format!("Failed to stop container {name}"))
 }
 
+pub async fn pause_container(docker: &Docker, name: &str) -> Result<()> {
+    docker
+        .pause_container(name)
+        .await
+        .with_context(|| format!("Failed to pause container {name}"))
+}
+
+pub async fn unpause_container(docker: &Docker, name: &str) -> Result<()> {
+    docker
+        .unpause_container(name)
+        .await
+        .with_context(|| format!("Failed to unpause container {name}"))
+}
+
 pub async fn start_container(docker: &Docker, name: &str) -> Result<()> {
     docker
         .start_container(name, None::<StartContainerOptions>)
@@ -165,5 +660,19 @@ pub async fn sweep_ephemeral(docker: &Docker) -> Result<usize> {
             removed += 1;
         }
     }
+
+    // Staging volumes are only removable once their helpers are gone.
+    let volume_filters = HashMap::from([(
+        "label".to_string(),
+        vec![format!("{EPHEMERAL_LABEL}=true")],
+    )]);
+    let volumes = docker
+        .list_volumes(Some(ListVolumesOptions { filters: Some(volume_filters) }))
+        .await?
+        .volumes
+        .unwrap_or_default();
+    for volume in volumes {
+        remove_volume(docker, &volume.name).await;
+    }
     Ok(removed)
 }
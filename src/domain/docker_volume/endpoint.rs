@@ -0,0 +1,237 @@
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use bollard::{API_DEFAULT_VERSION, Docker};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+const TIMEOUT_SECS: u64 = 120;
+const DEFAULT_REMOTE_SOCKET: &str = "/var/run/docker.sock";
+const TUNNEL_WAIT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsFiles {
+    pub ca: PathBuf,
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+/// Daemon a database's volumes live on, from `options.docker_host`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// The agent's own daemon (`DOCKER_HOST` or the default socket).
+    Local,
+    /// A Docker or Podman API socket, e.g. `/run/podman/podman.sock`.
+    Unix(PathBuf),
+    /// `tcp://host:port`, over TLS when certificates are configured.
+    Tcp { addr: String, tls: Option<TlsFiles> },
+    /// `ssh://[user@]host[:port][/socket]`, reached through an `ssh` socket
+    /// forward. The socket defaults to `/var/run/docker.sock`.
+    Ssh {
+        destination: String,
+        port: Option<u16>,
+        socket: String,
+    },
+}
+
+impl Endpoint {
+    pub fn from_config(cfg: &DatabaseConfig) -> Result<Self> {
+        let Some(host) = option_str(cfg, "docker_host") else {
+            return Ok(Self::Local);
+        };
+
+        if let Some(path) = host.strip_prefix("unix://") {
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+        if host.starts_with('/') {
+            return Ok(Self::Unix(PathBuf::from(host)));
+        }
+        if host.starts_with("tcp://") || host.starts_with("https://") || host.starts_with("http://") {
+            return Ok(Self::Tcp { addr: host.to_string(), tls: tls_files(cfg)? });
+        }
+        if host.starts_with("ssh://") {
+            let url = url::Url::parse(host).with_context(|| format!("Invalid docker_host {host}"))?;
+            let hostname = url.host_str().context("docker_host ssh:// URL has no host")?;
+            let destination = if url.username().is_empty() {
+                hostname.to_string()
+            } else {
+                format!("{}@{hostname}", url.username())
+            };
+            let socket = match url.path() {
+                "" | "/" => DEFAULT_REMOTE_SOCKET.to_string(),
+                path => path.to_string(),
+            };
+            return Ok(Self::Ssh { destination, port: url.port(), socket });
+        }
+        anyhow::bail!("Unsupported docker_host '{host}', expected unix://, tcp:// or ssh://")
+    }
+}
+
+fn option_str<'a>(cfg: &'a DatabaseConfig, key: &str) -> Option<&'a str> {
+    cfg.options
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// TLS material for tcp endpoints, either from `docker_cert_path` (a directory
+/// holding `ca.pem`, `cert.pem` and `key.pem`, like `DOCKER_CERT_PATH`) or from
+/// the individual `docker_tls_ca`, `docker_tls_cert` and `docker_tls_key` paths.
+fn tls_files(cfg: &DatabaseConfig) -> Result<Option<TlsFiles>> {
+    let dir = option_str(cfg, "docker_cert_path").map(Path::new);
+    let pick = |key: &str, file: &str| {
+        option_str(cfg, key)
+            .map(PathBuf::from)
+            .or_else(|| dir.map(|d| d.join(file)))
+    };
+    match (
+        pick("docker_tls_ca", "ca.pem"),
+        pick("docker_tls_cert", "cert.pem"),
+        pick("docker_tls_key", "key.pem"),
+    ) {
+        (None, None, None) => Ok(None),
+        (Some(ca), Some(cert), Some(key)) => Ok(Some(TlsFiles { ca, cert, key })),
+        _ => anyhow::bail!("docker_tls_ca, docker_tls_cert and docker_tls_key must be set together"),
+    }
+}
+
+pub fn ssh_args(
+    destination: &str,
+    port: Option<u16>,
+    identity: Option<&str>,
+    local: &Path,
+    remote: &str,
+) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "-nNT",
+        "-o",
+        "BatchMode=yes",
+        "-o",
+        "ExitOnForwardFailure=yes",
+        "-o",
+        "StreamLocalBindUnlink=yes",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    if let Some(port) = port {
+        args.push("-p".into());
+        args.push(port.to_string());
+    }
+    if let Some(identity) = identity {
+        args.push("-i".into());
+        args.push(identity.to_string());
+    }
+    args.push("-L".into());
+    args.push(format!("{}:{remote}", local.display()));
+    args.push(destination.to_string());
+    args
+}
+
+/// `ssh -L` forward of the remote API socket to a local one, closed on drop.
+struct SshTunnel {
+    child: Child,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl SshTunnel {
+    async fn open(cfg: &DatabaseConfig, destination: &str, port: Option<u16>, remote: &str) -> Result<(Self, PathBuf)> {
+        let dir = tempfile::TempDir::new()?;
+        let local = dir.path().join("docker.sock");
+        let args = ssh_args(destination, port, option_str(cfg, "ssh_key"), &local, remote);
+
+        let child = Command::new("ssh")
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to start ssh for docker_host")?;
+        let mut tunnel = Self { child, _dir: dir };
+
+        let start = Instant::now();
+        while !local.exists() {
+            if let Some(status) = tunnel.child.try_wait()? {
+                let mut stderr = String::new();
+                if let Some(mut err) = tunnel.child.stderr.take() {
+                    let _ = std::io::Read::read_to_string(&mut err, &mut stderr);
+                }
+                anyhow::bail!("ssh to {destination} exited with {status}: {}", stderr.trim());
+            }
+            if start.elapsed() > TUNNEL_WAIT {
+                anyhow::bail!("Timed out waiting for ssh forward to {destination}:{remote}");
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Ok((tunnel, local))
+    }
+}
+
+/// A Docker client bound to a database's endpoint. Keeps the ssh forward, if
+/// any, alive for as long as the client is used.
+pub struct DockerConnection {
+    docker: Docker,
+    _tunnel: Option<SshTunnel>,
+}
+
+impl Deref for DockerConnection {
+    type Target = Docker;
+
+    fn deref(&self) -> &Docker {
+        &self.docker
+    }
+}
+
+pub async fn connect(cfg: &DatabaseConfig) -> Result<DockerConnection> {
+    let endpoint = Endpoint::from_config(cfg)?;
+    let (docker, tunnel) = match &endpoint {
+        Endpoint::Local => {
+            let docker = super::docker::client()?;
+            return Ok(DockerConnection { docker, _tunnel: None });
+        }
+        Endpoint::Unix(path) => (
+            Docker::connect_with_unix(&path.to_string_lossy(), TIMEOUT_SECS, API_DEFAULT_VERSION)
+                .with_context(|| format!("Failed to connect to socket {}", path.display()))?,
+            None,
+        ),
+        Endpoint::Tcp { addr, tls: None } => (
+            Docker::connect_with_http(addr, TIMEOUT_SECS, API_DEFAULT_VERSION)
+                .with_context(|| format!("Failed to connect to {addr}"))?,
+            None,
+        ),
+        Endpoint::Tcp { addr, tls: Some(tls) } => {
+            // reqwest and bollard pull in different rustls backends, so the
+            // provider has to be chosen explicitly.
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            (
+                Docker::connect_with_ssl(addr, &tls.key, &tls.cert, &tls.ca, TIMEOUT_SECS, API_DEFAULT_VERSION)
+                    .with_context(|| format!("Failed to connect to {addr} over TLS"))?,
+                None,
+            )
+        }
+        Endpoint::Ssh { destination, port, socket } => {
+            let (tunnel, local) = SshTunnel::open(cfg, destination, *port, socket).await?;
+            (
+                Docker::connect_with_unix(&local.to_string_lossy(), TIMEOUT_SECS, API_DEFAULT_VERSION)
+                    .context("Failed to connect to forwarded docker socket")?,
+                Some(tunnel),
+            )
+        }
+    };
+
+    // Remote daemons and Podman may speak an older API than bollard defaults to.
+    let docker = docker
+        .negotiate_version()
+        .await
+        .with_context(|| format!("Failed to reach Docker endpoint {endpoint:?}"))?;
+    Ok(DockerConnection { docker, _tunnel: tunnel })
+}
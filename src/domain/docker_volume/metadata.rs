@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use bollard::models::Volume;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// First entry of a docker-volume archive, ahead of the `vol/` tree returned by
+/// Docker. It is skipped when the archive is uploaded back into a volume.
+pub const METADATA_ENTRY: &str = "portabase-volume.json";
+
+/// What is needed to recreate the source volume elsewhere.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VolumeMeta {
+    pub name: String,
+    pub driver: String,
+    #[serde(default)]
+    pub driver_opts: HashMap<String, String>,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+impl VolumeMeta {
+    pub fn from_volume(volume: &Volume) -> Self {
+        Self {
+            name: volume.name.clone(),
+            driver: volume.driver.clone(),
+            driver_opts: volume.options.clone(),
+            labels: volume.labels.clone(),
+        }
+    }
+}
+
+/// Writes the metadata entry without an end-of-archive marker, so the tar
+/// streamed by Docker can be appended right after it.
+pub fn write_entry<W: Write>(out: &mut W, meta: &VolumeMeta) -> Result<()> {
+    let data = serde_json::to_vec_pretty(meta)?;
+    let mut header = tar::Header::new_ustar();
+    header.set_path(METADATA_ENTRY)?;
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_cksum();
+
+    out.write_all(header.as_bytes())?;
+    out.write_all(&data)?;
+    out.write_all(&vec![0u8; padding(data.len() as u64) as usize])?;
+    Ok(())
+}
+
+/// Reads the metadata entry of `archive`, returning it together with the
+/// offset at which the volume content starts. Archives made before the entry
+/// existed yield `None`.
+pub fn read_entry(archive: &Path) -> Result<Option<(VolumeMeta, u64)>> {
+    let file = std::fs::File::open(archive)
+        .with_context(|| format!("Failed to open {}", archive.display()))?;
+    let mut tar = tar::Archive::new(file);
+    let Some(entry) = tar.entries()?.next() else {
+        return Ok(None);
+    };
+    let mut entry = entry?;
+    if entry.path()?.as_os_str() != METADATA_ENTRY {
+        return Ok(None);
+    }
+
+    let size = entry.size();
+    let offset = entry.raw_file_position() + size + padding(size);
+    let mut data = Vec::with_capacity(size as usize);
+    entry.read_to_end(&mut data)?;
+    let meta = serde_json::from_slice(&data).context("Invalid volume metadata in archive")?;
+    Ok(Some((meta, offset)))
+}
+
+fn padding(len: u64) -> u64 {
+    (512 - len % 512) % 512
+}
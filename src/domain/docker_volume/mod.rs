@@ -1,5 +1,8 @@
 pub mod backup;
+pub mod consistency;
 pub mod database;
 pub mod docker;
+pub mod endpoint;
+pub mod metadata;
 pub mod ping;
 pub mod restore;
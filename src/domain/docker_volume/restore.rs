@@ -1,95 +1,97 @@
 use crate::domain::docker_volume::docker::{
-    client, create_helper, remove_helper, resolve_helper_image, start_container, stop_container,
+    create_volume, replace_volume_contents, resolve_helper_image, start_container,
+    stop_container, volume_exists,
 };
+use crate::domain::docker_volume::metadata::{VolumeMeta, read_entry};
+use crate::domain::docker_volume::endpoint::connect;
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
-use anyhow::{Context, Result};
-use bollard::exec::StartExecResults;
-use bollard::models::ExecConfig;
-use bollard::query_parameters::UploadToContainerOptions;
-use futures_util::StreamExt;
+use anyhow::Result;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
 
+/// Volume the archive is restored into: `options.restore_volume_name`, or the
+/// configured volume itself.
+pub fn target_volume(cfg: &DatabaseConfig) -> &str {
+    cfg.options
+        .get("restore_volume_name")
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or(&cfg.volume_name)
+}
+
+/// Labels that tie a volume to its compose project or to Portabase discovery.
+const OWNER_LABEL_PREFIXES: [&str; 2] = ["com.docker.compose.", "io.portabase."];
+
+/// Settings for creating the restore target from the archive's metadata. A
+/// copy of a bind-backed local volume (`device` option) would point at the
+/// live data, so those options are dropped for renamed targets. Renamed
+/// targets also lose the owner labels, so the copy is not mistaken for the
+/// compose project's volume.
+pub fn creation_meta(meta: Option<VolumeMeta>, in_place: bool) -> (VolumeMeta, bool) {
+    let mut meta = meta.unwrap_or_default();
+    let drop_opts = !in_place && meta.driver_opts.contains_key("device");
+    if drop_opts {
+        meta.driver_opts.clear();
+    }
+    if !in_place {
+        meta.labels.retain(|key, _| !OWNER_LABEL_PREFIXES.iter().any(|p| key.starts_with(p)));
+    }
+    (meta, drop_opts)
+}
+
 pub async fn run(cfg: DatabaseConfig, archive: PathBuf, logger: Arc<JobLogger>) -> Result<()> {
     tokio::task::spawn_blocking(move || -> Result<()> {
         futures::executor::block_on(async move {
             logger.log("info", format!("Starting docker-volume restore for {}", cfg.name));
 
-            let docker = client()?;
-            let image = resolve_helper_image(&docker).await?;
+            let docker = connect(&cfg).await?;
+            let image = resolve_helper_image(&docker, &cfg).await?;
 
             logger.log("debug", format!("Restore archive: {}", archive.display()));
 
-            if let Some(name) = &cfg.container_name {
+            let target = target_volume(&cfg).to_string();
+            // Only the live volume's consumer needs stopping; a side-by-side copy
+            // leaves it running.
+            let in_place = target == cfg.volume_name;
+            let container = cfg.container_name.as_ref().filter(|_| in_place);
+            if !in_place {
+                logger.log("info", format!("Restoring into volume {target} instead of {}", cfg.volume_name));
+            }
+
+            if !volume_exists(&docker, &target).await? {
+                let (meta, dropped_opts) = creation_meta(read_entry(&archive)?.map(|(m, _)| m), in_place);
+                if dropped_opts {
+                    logger.log(
+                        "warn",
+                        format!("Volume {} is bind-backed, creating {target} without its driver options", meta.name),
+                    );
+                }
+                let driver = if meta.driver.is_empty() { "local" } else { meta.driver.as_str() };
+                logger.log("info", format!("Creating volume {target} (driver {driver})"));
+                create_volume(&docker, &target, &meta.driver, meta.driver_opts, meta.labels).await?;
+            }
+
+            if let Some(name) = container {
                 logger.log("info", format!("Stopping container {name} for restore"));
                 stop_container(&docker, name).await?;
             }
 
             let result = async {
-                let helper = create_helper(
-                    &docker,
-                    &image,
-                    &cfg.volume_name,
-                    &cfg.generated_id,
-                    false,
-                    Some(vec![
-                        "sh".into(),
-                        "-c".into(),
-                        "trap 'exit 0' TERM; sleep 2147483647 & wait".into(),
-                    ]),
-                )
-                .await?;
-                start_container(&docker, &helper.id).await?;
-
-
-                let exec = docker
-                    .create_exec(
-                        &helper.id,
-                        ExecConfig {
-                            cmd: Some(vec![
-                                "sh".to_string(),
-                                "-c".to_string(),
-                                "rm -rf /vol/* /vol/.[!.]* 2>/dev/null || true".to_string(),
-                            ]),
-                            attach_stdout: Some(true),
-                            attach_stderr: Some(true),
-                            ..Default::default()
-                        },
-                    )
-                    .await
-                    .context("Failed to create wipe exec")?;
-
-                if let StartExecResults::Attached { mut output, .. } =
-                    docker.start_exec(&exec.id, None).await.context("Failed to run wipe exec")?
-                {
-                    while output.next().await.is_some() {}
-                }
-
                 let start = Instant::now();
 
-                let file = tokio::fs::File::open(&archive)
-                    .await
-                    .with_context(|| format!("Failed to open {}", archive.display()))?;
-                let stream = tokio_util::io::ReaderStream::new(file);
-
-                let up_opts = UploadToContainerOptions { path: "/".to_string(), ..Default::default() };
-                docker
-                    .upload_to_container(&helper.id, Some(up_opts), bollard::body_try_stream(stream))
-                    .await
-                    .context("Failed to upload volume archive")?;
+                replace_volume_contents(&docker, &image, &target, &cfg.generated_id, &archive).await?;
 
                 let duration_ms = start.elapsed().as_millis() as f64;
                 logger.log_command("docker upload_to_container", None, Some(0), Some(duration_ms));
 
-                remove_helper(&docker, &helper.id).await;
                 logger.log("info", format!("Volume restore completed for {}", cfg.name));
                 anyhow::Ok(())
             }
             .await;
 
-            if let Some(name) = &cfg.container_name {
+            if let Some(name) = container {
                 if let Err(e) = start_container(&docker, name).await {
                     logger.log("error", format!("Failed to restart container {name}: {e}"));
                 }
@@ -0,0 +1,118 @@
+use super::snapshot::{Strategy, snapshot_files, uses_df_format};
+use crate::domain::redis::connection::{auth_env, connection_args};
+use crate::domain::redis::info::engine_version;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+
+pub async fn run(
+    cfg: DatabaseConfig,
+    backup_dir: PathBuf,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        logger.log("info", format!("Starting Dragonfly backup for database {}", cfg.name));
+        if let Some(version) = engine_version(&cfg, "redis-cli", &cfg.host, cfg.port) {
+            logger.log("info", format!("Dragonfly {} at {}:{}", version, cfg.host, cfg.port));
+        }
+
+        let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+
+        match Strategy::from_config(&cfg) {
+            Strategy::Snapshot => save_snapshot(&cfg, &file_path, &logger)?,
+            Strategy::Replica => {
+                let mut cmd = Command::new("redis-cli");
+                cmd.args(connection_args(&cfg, &cfg.host, cfg.port))
+                    .envs(auth_env(&cfg))
+                    .arg("--rdb")
+                    .arg(&file_path);
+                run_cli(cmd, "redis-cli --rdb", &cfg, &logger)?;
+            }
+        }
+
+        logger.log("info", format!("Dragonfly backup completed for {}", cfg.name));
+        Ok(file_path)
+    })
+    .await?
+}
+
+/// Asks Dragonfly to `SAVE` under a unique basename, then moves the files
+/// out of the shared snapshot directory into the backup artifact.
+fn save_snapshot(cfg: &DatabaseConfig, file_path: &Path, logger: &JobLogger) -> Result<()> {
+    if cfg.path.is_empty() {
+        anyhow::bail!("Dragonfly snapshot mode requires path (the server's --dir as mounted on the agent)");
+    }
+    let snapshot_dir = PathBuf::from(&cfg.path);
+    let basename = format!("portabase-{}-{}", cfg.generated_id, chrono::Utc::now().format("%Y%m%d%H%M%S"));
+    let format = if uses_df_format(cfg) { "DF" } else { "RDB" };
+
+    let mut cmd = Command::new("redis-cli");
+    cmd.args(connection_args(cfg, &cfg.host, cfg.port))
+        .envs(auth_env(cfg))
+        .args(["SAVE", format, &basename]);
+    run_cli(cmd, &format!("redis-cli SAVE {format} {basename}"), cfg, logger)?;
+
+    let files = snapshot_files(&snapshot_dir, &basename);
+    if files.is_empty() {
+        anyhow::bail!(
+            "Snapshot {} not found in {}; is the Dragonfly --dir mounted on the agent?",
+            basename,
+            snapshot_dir.display()
+        );
+    }
+
+    let result = if uses_df_format(cfg) {
+        (|| -> Result<()> {
+            let out = std::fs::File::create(file_path)
+                .with_context(|| format!("Failed to create {}", file_path.display()))?;
+            let mut tar = tar::Builder::new(out);
+            for file in &files {
+                tar.append_path_with_name(file, file.file_name().unwrap())?;
+            }
+            tar.finish()?;
+            Ok(())
+        })()
+    } else {
+        std::fs::copy(&files[0], file_path)
+            .map(|_| ())
+            .with_context(|| format!("Failed to copy {}", files[0].display()))
+    };
+
+    for file in &files {
+        if let Err(e) = std::fs::remove_file(file) {
+            logger.log("warn", format!("Failed to remove snapshot file {}: {}", file.display(), e));
+        }
+    }
+    result
+}
+
+fn run_cli(mut cmd: Command, display: &str, cfg: &DatabaseConfig, logger: &JobLogger) -> Result<()> {
+    let start = Instant::now();
+    let output = cmd.output().context("Dragonfly backup command failed")?;
+    let duration_ms = start.elapsed().as_millis() as f64;
+    let exit_code = output.status.code().unwrap_or(-1);
+
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // redis-cli exits 0 on server-side errors when not in --rdb mode.
+    if !output.status.success() || stdout.starts_with("ERR") || stdout.starts_with("NOAUTH") {
+        let message = if stderr.trim().is_empty() { stdout.clone() } else { stderr };
+        logger.log("error", format!("Dragonfly backup failed for {}: {}", cfg.name, message));
+        logger.log_command(display, Some(message.clone()), Some(if exit_code == 0 { 1 } else { exit_code }), Some(duration_ms));
+        anyhow::bail!("Dragonfly backup failed for {}: {}", cfg.name, message);
+    }
+
+    logger.log_command(
+        display,
+        if stdout.is_empty() { None } else { Some(stdout) },
+        Some(0),
+        Some(duration_ms),
+    );
+    Ok(())
+}
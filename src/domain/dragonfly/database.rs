@@ -0,0 +1,47 @@
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::backup;
+use super::snapshot::{Strategy, uses_df_format};
+use crate::domain::factory::Database;
+use crate::domain::redis::ping;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use crate::utils::locks::{DbOpLock, FileLock};
+
+pub struct DragonflyDatabase {
+    cfg: DatabaseConfig,
+}
+
+impl DragonflyDatabase {
+    pub fn new(cfg: DatabaseConfig) -> Self {
+        Self { cfg }
+    }
+}
+
+#[async_trait]
+impl Database for DragonflyDatabase {
+    fn file_extension(&self) -> &'static str {
+        match Strategy::from_config(&self.cfg) {
+            Strategy::Snapshot if uses_df_format(&self.cfg) => ".tar",
+            _ => ".rdb",
+        }
+    }
+
+    async fn ping(&self) -> Result<bool> {
+        ping::run(self.cfg.clone()).await
+    }
+
+    async fn backup(&self, dir: &Path, logger: Arc<JobLogger>) -> Result<PathBuf> {
+        FileLock::acquire(&self.cfg.generated_id, DbOpLock::Backup.as_str()).await?;
+        let res = backup::run(self.cfg.clone(), dir.to_path_buf(), self.file_extension(), logger).await;
+        FileLock::release(&self.cfg.generated_id).await?;
+        res
+    }
+
+    async fn restore(&self, _file: &Path, _logger: Arc<JobLogger>) -> Result<()> {
+        bail!("Restore not supported for Dragonfly databases")
+    }
+}
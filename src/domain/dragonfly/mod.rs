@@ -0,0 +1,3 @@
+mod backup;
+pub mod database;
+pub mod snapshot;
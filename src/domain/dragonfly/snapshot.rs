@@ -0,0 +1,42 @@
+use crate::services::config::DatabaseConfig;
+use std::path::{Path, PathBuf};
+
+/// How Dragonfly is backed up, selected with `options.dragonfly_mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// `SAVE` into Dragonfly's `--dir`, which must be mounted on the agent at `path`.
+    Snapshot,
+    /// Full sync through `redis-cli --rdb`; only for setups where the
+    /// snapshot directory cannot be shared.
+    Replica,
+}
+
+impl Strategy {
+    pub fn from_config(cfg: &DatabaseConfig) -> Self {
+        match cfg.options.get("dragonfly_mode").and_then(|v| v.as_str()) {
+            Some("replica") => Self::Replica,
+            _ => Self::Snapshot,
+        }
+    }
+}
+
+/// `options.snapshot_format = "df"` keeps Dragonfly's native multi-file
+/// format (faster to save and load); the default is a single RDB file.
+pub fn uses_df_format(cfg: &DatabaseConfig) -> bool {
+    cfg.options.get("snapshot_format").and_then(|v| v.as_str()) == Some("df")
+}
+
+/// Files written by `SAVE <format> <basename>` in the snapshot directory.
+pub fn snapshot_files(dir: &Path, basename: &str) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(basename))
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+    files.sort();
+    files
+}
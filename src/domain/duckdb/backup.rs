@@ -0,0 +1,76 @@
+use super::connection::{export_format, quote_literal};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+
+pub async fn run(
+    cfg: DatabaseConfig,
+    backup_dir: PathBuf,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        logger.log("info", format!("Starting DuckDB backup for database {}", cfg.name));
+
+        if cfg.path.is_empty() {
+            anyhow::bail!("Database path not configured");
+        }
+
+        let db_path = PathBuf::from(&cfg.path);
+        logger.log("info", format!("Database path: {}", db_path.display()));
+
+        if !db_path.exists() {
+            logger.log("error", format!("DuckDB database file not found: {}", db_path.display()));
+            anyhow::bail!("DuckDB database file not found: {}", db_path.display());
+        }
+
+        let export_dir = backup_dir.join(format!("{}_export", cfg.generated_id));
+        let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+        let format = export_format(&cfg);
+
+        // Opening read-only takes a shared lock on the file, so the export is a
+        // consistent snapshot and fails fast if another process holds it for writing.
+        logger.log("info", format!("Running duckdb EXPORT DATABASE ({}) for {}", format, cfg.name));
+
+        let start = Instant::now();
+        let output = Command::new("duckdb")
+            .arg("-readonly")
+            .arg(db_path.as_os_str())
+            .arg("-c")
+            .arg(format!(
+                "EXPORT DATABASE {} (FORMAT {});",
+                quote_literal(&export_dir.to_string_lossy()),
+                format
+            ))
+            .output()
+            .context("DuckDB backup command failed to start")?;
+        let duration_ms = start.elapsed().as_millis() as f64;
+        let exit_code = output.status.code().unwrap_or(-1);
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            logger.log("error", format!("DuckDB backup failed for {}: {}", cfg.name, stderr));
+            logger.log_command("duckdb EXPORT DATABASE", Some(stderr.clone()), Some(exit_code), Some(duration_ms));
+            anyhow::bail!("DuckDB backup failed for {}: {}", cfg.name, stderr);
+        }
+
+        logger.log_command("duckdb EXPORT DATABASE", None, Some(0), Some(duration_ms));
+
+        let out = std::fs::File::create(&file_path)
+            .with_context(|| format!("Failed to create {}", file_path.display()))?;
+        let mut tar = tar::Builder::new(out);
+        tar.append_dir_all(".", &export_dir)
+            .with_context(|| format!("Failed to archive export dir for {}", cfg.name))?;
+        tar.finish()?;
+
+        let _ = std::fs::remove_dir_all(&export_dir);
+
+        logger.log("info", format!("DuckDB backup completed for {}", cfg.name));
+        Ok(file_path)
+    })
+    .await?
+}
@@ -0,0 +1,33 @@
+use crate::services::config::DatabaseConfig;
+use std::path::{Path, PathBuf};
+
+pub fn quote_literal(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Format used by `EXPORT DATABASE`; parquet keeps types intact and is
+/// compact, csv is offered for interoperability with other tools.
+pub fn export_format(cfg: &DatabaseConfig) -> &'static str {
+    match cfg.options.get("export_format").and_then(|v| v.as_str()) {
+        Some("csv") => "CSV",
+        _ => "PARQUET",
+    }
+}
+
+/// Locates the directory produced by `EXPORT DATABASE` (the one holding
+/// `load.sql`) inside an unpacked backup archive.
+pub fn find_export_dir(root: &Path) -> Option<PathBuf> {
+    if root.join("load.sql").is_file() {
+        return Some(root.to_path_buf());
+    }
+    std::fs::read_dir(root)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .find_map(|p| find_export_dir(&p))
+}
+
+pub fn wal_path(db_path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.wal", db_path.display()))
+}
@@ -0,0 +1,82 @@
+use super::connection::{find_export_dir, quote_literal, wal_path};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Imports the exported database into a staging file next to `cfg.path` and
+/// renames it over the live file once the import succeeded, so readers never
+/// observe a half-imported database.
+pub async fn run(cfg: DatabaseConfig, restore_file: PathBuf, logger: Arc<JobLogger>) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        logger.log("debug", format!("Starting DuckDB restore for database {}", cfg.name));
+
+        if cfg.path.is_empty() {
+            anyhow::bail!("Database path not configured");
+        }
+        if !restore_file.exists() {
+            anyhow::bail!("Restore file not found: {}", restore_file.display());
+        }
+
+        let db_path = PathBuf::from(&cfg.path);
+
+        let unpacked = tempfile::TempDir::new()?;
+        let tar = std::fs::File::open(&restore_file)?;
+        tar::Archive::new(tar)
+            .unpack(unpacked.path())
+            .with_context(|| format!("Failed to unpack {}", restore_file.display()))?;
+
+        let export_dir = find_export_dir(unpacked.path())
+            .ok_or_else(|| anyhow::anyhow!("Invalid DuckDB archive: load.sql not found"))?;
+
+        let staging = PathBuf::from(format!("{}.portabase-restore", cfg.path));
+        for stale in [&staging, &wal_path(&staging)] {
+            if stale.exists() {
+                std::fs::remove_file(stale)
+                    .with_context(|| format!("Failed to remove stale {}", stale.display()))?;
+            }
+        }
+
+        let start = Instant::now();
+        let output = Command::new("duckdb")
+            .arg(staging.as_os_str())
+            .arg("-c")
+            .arg(format!(
+                "IMPORT DATABASE {}; CHECKPOINT;",
+                quote_literal(&export_dir.to_string_lossy())
+            ))
+            .output()
+            .with_context(|| format!("Failed to run duckdb import for {}", cfg.name))?;
+
+        let duration_ms = start.elapsed().as_millis() as f64;
+        let exit_code = output.status.code().unwrap_or(-1);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if !output.status.success() {
+            logger.log_command("duckdb IMPORT DATABASE", Some(stderr.clone()), Some(exit_code), Some(duration_ms));
+            logger.log("error", format!("DuckDB restore failed for {}: {}", cfg.name, stderr));
+            let _ = std::fs::remove_file(&staging);
+            anyhow::bail!("DuckDB restore failed for {}", cfg.name);
+        }
+
+        logger.log_command("duckdb IMPORT DATABASE", if stderr.is_empty() { None } else { Some(stderr) }, Some(0), Some(duration_ms));
+
+        // A WAL left behind by the previous file would be replayed on top of the
+        // restored database, so drop it before swapping.
+        let live_wal = wal_path(&db_path);
+        if live_wal.exists() {
+            std::fs::remove_file(&live_wal)
+                .with_context(|| format!("Failed to remove {}", live_wal.display()))?;
+        }
+
+        std::fs::rename(&staging, &db_path)
+            .with_context(|| format!("Failed to swap restored DB into {}", db_path.display()))?;
+
+        logger.log("info", format!("DuckDB restore completed for {}", cfg.name));
+        Ok(())
+    })
+    .await?
+}
@@ -0,0 +1,55 @@
+use super::connection::{auth_env, etcdctl_args};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+
+pub async fn run(
+    cfg: DatabaseConfig,
+    backup_dir: PathBuf,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        logger.log("info", format!("Starting etcd snapshot for {}", cfg.name));
+
+        let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+
+        logger.log("info", format!("Running etcdctl snapshot save for {}", cfg.name));
+
+        let start = Instant::now();
+        let output = Command::new("etcdctl")
+            .args(etcdctl_args(&cfg))
+            .arg("snapshot")
+            .arg("save")
+            .arg(&file_path)
+            .envs(auth_env(&cfg))
+            .output()
+            .context("etcd snapshot command failed to start")?;
+        let duration_ms = start.elapsed().as_millis() as f64;
+        let exit_code = output.status.code().unwrap_or(-1);
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let combined = format!("{}{}", stdout, stderr).trim().to_string();
+
+        if !output.status.success() {
+            logger.log("error", format!("etcd snapshot failed for {}: {}", cfg.name, stderr));
+            logger.log_command("etcdctl snapshot save", Some(combined), Some(exit_code), Some(duration_ms));
+            anyhow::bail!("etcd snapshot failed for {}: {}", cfg.name, stderr);
+        }
+
+        logger.log_command(
+            "etcdctl snapshot save",
+            if combined.is_empty() { None } else { Some(combined) },
+            Some(0),
+            Some(duration_ms),
+        );
+        logger.log("info", format!("etcd snapshot completed for {}", cfg.name));
+        Ok(file_path)
+    })
+    .await?
+}
@@ -0,0 +1,71 @@
+use crate::services::config::DatabaseConfig;
+
+fn option_str<'a>(cfg: &'a DatabaseConfig, key: &str) -> Option<&'a str> {
+    cfg.options
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn option_bool(cfg: &DatabaseConfig, key: &str) -> bool {
+    cfg.options.get(key).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+pub fn uses_tls(cfg: &DatabaseConfig) -> bool {
+    option_bool(cfg, "tls")
+        || option_str(cfg, "ca_cert").is_some()
+        || option_str(cfg, "client_cert").is_some()
+}
+
+pub fn endpoint(cfg: &DatabaseConfig) -> String {
+    let scheme = if uses_tls(cfg) { "https" } else { "http" };
+    format!("{}://{}:{}", scheme, cfg.host, cfg.port)
+}
+
+/// Connection flags shared by every `etcdctl` invocation. Credentials are
+/// passed through [`auth_env`] so they never show up in the process list.
+pub fn etcdctl_args(cfg: &DatabaseConfig) -> Vec<String> {
+    let mut args = vec![format!("--endpoints={}", endpoint(cfg))];
+
+    if let Some(ca) = option_str(cfg, "ca_cert") {
+        args.push(format!("--cacert={}", ca));
+    }
+    if let Some(cert) = option_str(cfg, "client_cert") {
+        args.push(format!("--cert={}", cert));
+    }
+    if let Some(key) = option_str(cfg, "client_key") {
+        args.push(format!("--key={}", key));
+    }
+    if option_bool(cfg, "insecure_skip_tls_verify") {
+        args.push("--insecure-skip-tls-verify".to_string());
+    }
+
+    args
+}
+
+pub fn auth_env(cfg: &DatabaseConfig) -> Vec<(String, String)> {
+    let mut envs = vec![("ETCDCTL_API".to_string(), "3".to_string())];
+    if !cfg.username.is_empty() {
+        envs.push(("ETCDCTL_USER".to_string(), cfg.username.clone()));
+        envs.push(("ETCDCTL_PASSWORD".to_string(), cfg.password.clone()));
+    }
+    envs
+}
+
+/// Flags forwarded to `etcdutl snapshot restore` so the restored member
+/// can rejoin (or bootstrap) a cluster with the expected identity.
+pub fn restore_args(cfg: &DatabaseConfig) -> Vec<String> {
+    let mut args = Vec::new();
+    for (key, flag) in [
+        ("name", "--name"),
+        ("initial_cluster", "--initial-cluster"),
+        ("initial_cluster_token", "--initial-cluster-token"),
+        ("initial_advertise_peer_urls", "--initial-advertise-peer-urls"),
+    ] {
+        if let Some(v) = option_str(cfg, key) {
+            args.push(format!("{}={}", flag, v));
+        }
+    }
+    args
+}
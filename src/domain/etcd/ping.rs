@@ -0,0 +1,35 @@
+use super::connection::{auth_env, etcdctl_args};
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use tokio::process::Command;
+use tokio::time::{Duration, timeout};
+use tracing::{debug, error, info};
+
+pub async fn run(cfg: DatabaseConfig) -> Result<bool> {
+    let mut cmd = Command::new("etcdctl");
+    cmd.args(etcdctl_args(&cfg))
+        .arg("--command-timeout=5s")
+        .args(["endpoint", "status", "--write-out=json"])
+        .envs(auth_env(&cfg));
+
+    debug!("Command Ping etcd: {:?}", cmd.as_std().get_args().collect::<Vec<_>>());
+
+    match timeout(Duration::from_secs(10), cmd.output()).await {
+        Ok(output) => {
+            let output = output.context("Failed to execute etcdctl")?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                error!("etcd endpoint status failed: {}", stderr.trim());
+                return Ok(false);
+            }
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Ok(stdout.contains("\"Status\""))
+        }
+        Err(_) => {
+            info!("Timeout connecting to etcd at {}:{}", cfg.host, cfg.port);
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use super::connection::restore_args;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Materialises the snapshot as an etcd data directory at `cfg.path`.
+///
+/// `etcdutl` refuses to write into an existing directory, so the snapshot is
+/// restored next to the target first and swapped in once it succeeded. The
+/// previous data directory is kept aside rather than deleted. The etcd member
+/// has to be (re)started on the new directory by the operator.
+pub async fn run(cfg: DatabaseConfig, restore_file: PathBuf, logger: Arc<JobLogger>) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        logger.log("debug", format!("Starting etcd restore for {}", cfg.name));
+
+        if cfg.path.is_empty() {
+            anyhow::bail!("etcd restore requires 'path' (target data directory) for {}", cfg.name);
+        }
+        if !restore_file.exists() {
+            anyhow::bail!("Restore file not found: {}", restore_file.display());
+        }
+
+        let data_dir = PathBuf::from(&cfg.path);
+        let staging = PathBuf::from(format!("{}.portabase-restore", cfg.path));
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)
+                .with_context(|| format!("Failed to clear staging dir {}", staging.display()))?;
+        }
+
+        logger.log("info", format!("Restoring etcd snapshot into {}", staging.display()));
+
+        let start = Instant::now();
+        let output = Command::new("etcdutl")
+            .arg("snapshot")
+            .arg("restore")
+            .arg(&restore_file)
+            .arg(format!("--data-dir={}", staging.display()))
+            .args(restore_args(&cfg))
+            .output()
+            .with_context(|| format!("Failed to run etcdutl for {}", cfg.name))?;
+        let duration_ms = start.elapsed().as_millis() as f64;
+        let exit_code = output.status.code().unwrap_or(-1);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if !output.status.success() {
+            logger.log_command("etcdutl snapshot restore", Some(stderr.clone()), Some(exit_code), Some(duration_ms));
+            logger.log("error", format!("etcd restore failed for {}: {}", cfg.name, stderr));
+            let _ = std::fs::remove_dir_all(&staging);
+            anyhow::bail!("etcd restore failed for {}", cfg.name);
+        }
+
+        logger.log_command(
+            "etcdutl snapshot restore",
+            if stderr.is_empty() { None } else { Some(stderr) },
+            Some(0),
+            Some(duration_ms),
+        );
+
+        if data_dir.exists() {
+            let previous = PathBuf::from(format!(
+                "{}.pre-restore-{}",
+                cfg.path,
+                chrono::Utc::now().format("%Y%m%d%H%M%S")
+            ));
+            std::fs::rename(&data_dir, &previous).with_context(|| {
+                format!("Failed to move existing data dir {} aside", data_dir.display())
+            })?;
+            logger.log("warn", format!("Previous etcd data dir moved to {}", previous.display()));
+        }
+
+        std::fs::rename(&staging, &data_dir)
+            .with_context(|| format!("Failed to move restored data into {}", data_dir.display()))?;
+
+        logger.log(
+            "info",
+            format!("etcd restore completed for {}; restart the member on {}", cfg.name, data_dir.display()),
+        );
+        Ok(())
+    })
+    .await?
+}
@@ -1,8 +1,10 @@
+use crate::domain::docker_compose::database::DockerComposeDatabase;
 use crate::domain::docker_volume::database::DockerVolumeDatabase;
 use crate::domain::mongodb::database::MongoDatabase;
 use crate::domain::mysql::database::MySQLDatabase;
 use crate::domain::postgres::cluster::database::PostgresClusterDatabase;
 use crate::domain::postgres::database::PostgresDatabase;
+use crate::domain::postgres::format::PostgresDumpFormat;
 use crate::domain::postgres::{detect_format_from_file, detect_format_from_size};
 use crate::domain::redis::database::RedisDatabase;
 use crate::domain::sqlite::database::SqliteDatabase;
@@ -10,18 +12,66 @@ use crate::domain::valkey::database::ValkeyDatabase;
 use crate::domain::firebird::database::FirebirdDatabase;
 use crate::domain::mariadb::database::MariaDBDatabase;
 use crate::domain::mssql::database::MssqlDatabase;
+use crate::domain::etcd::database::EtcdDatabase;
+use crate::domain::duckdb::database::DuckdbDatabase;
+use crate::domain::couchdb::database::CouchdbDatabase;
+use crate::domain::influxdb::database::InfluxdbDatabase;
+use crate::domain::neo4j::database::Neo4jDatabase;
+use crate::domain::cassandra::database::CassandraDatabase;
+use crate::domain::dragonfly::database::DragonflyDatabase;
+use crate::domain::filesystem::database::FilesystemDatabase;
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::{DatabaseConfig, DbType};
+use crate::utils::stream::DumpStream;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// What produced a dump, as recorded in the backup manifest. Fields an engine
+/// cannot tell are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Time spent dumping to a file; unset for streamed dumps, which run for
+    /// as long as their upload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
 #[async_trait::async_trait]
 pub trait Database: Send + Sync {
     fn file_extension(&self) -> &'static str;
     async fn ping(&self) -> Result<bool>;
     async fn backup(&self, backup_dir: &Path, logger: Arc<JobLogger>) -> Result<PathBuf>;
     async fn restore(&self, restore_file: &Path, logger: Arc<JobLogger>) -> Result<()>;
+
+    /// Starts a dump written to stdout, for streaming backups. `None` when the
+    /// engine can only dump to files. Takes the backup lock; the caller
+    /// releases it once the stream has been consumed.
+    async fn backup_stream(&self, _logger: Arc<JobLogger>) -> Result<Option<DumpStream>> {
+        Ok(None)
+    }
+
+    /// Server and dump tool behind this engine's backups; `streamed` when the
+    /// dump came from [`Database::backup_stream`].
+    async fn describe(&self, _streamed: bool) -> DumpInfo {
+        DumpInfo::default()
+    }
+
+    /// Bytes of data a dump will hold, leaving out indexes, to size a
+    /// backup's scratch space. `None` when the engine cannot tell.
+    async fn estimated_size(&self) -> Option<u64> {
+        None
+    }
 }
 
 pub struct DatabaseFactory;
@@ -38,18 +88,39 @@ impl DatabaseFactory {
             DbType::Mariadb => Arc::new(MariaDBDatabase::new(cfg)),
             DbType::MongoDB => Arc::new(MongoDatabase::new(cfg)),
             DbType::Sqlite => Arc::new(SqliteDatabase::new(cfg)),
-            DbType::Redis => Arc::new(RedisDatabase::new(cfg)),
+            DbType::Redis | DbType::Keydb => Arc::new(RedisDatabase::new(cfg)),
+            DbType::Dragonfly => Arc::new(DragonflyDatabase::new(cfg)),
             DbType::Valkey => Arc::new(ValkeyDatabase::new(cfg)),
             DbType::Firebird => Arc::new(FirebirdDatabase::new(cfg)),
             DbType::Mssql => Arc::new(MssqlDatabase::new(cfg)),
             DbType::DockerVolume => Arc::new(DockerVolumeDatabase::new(cfg)),
+            DbType::DockerCompose => Arc::new(DockerComposeDatabase::new(cfg)),
+            DbType::Etcd => Arc::new(EtcdDatabase::new(cfg)),
+            DbType::Duckdb => Arc::new(DuckdbDatabase::new(cfg)),
+            DbType::Couchdb => Arc::new(CouchdbDatabase::new(cfg)),
+            DbType::Influxdb => Arc::new(InfluxdbDatabase::new(cfg)),
+            DbType::Neo4j => Arc::new(Neo4jDatabase::new(cfg)),
+            DbType::Cassandra | DbType::Scylladb => Arc::new(CassandraDatabase::new(cfg)),
+            DbType::Filesystem => Arc::new(FilesystemDatabase::new(cfg)),
         }
     }
 
     pub async fn create_for_restore(cfg: DatabaseConfig, restore_file: &Path) -> Arc<dyn Database> {
+        Self::create_for_restore_with_format(cfg, restore_file, None).await
+    }
+
+    /// Like [`Self::create_for_restore`], with the dump format recorded in the
+    /// backup manifest taking precedence over the file name.
+    pub async fn create_for_restore_with_format(
+        cfg: DatabaseConfig,
+        restore_file: &Path,
+        format: Option<&str>,
+    ) -> Arc<dyn Database> {
         match cfg.db_type {
             DbType::Postgresql => {
-                let format = detect_format_from_file(restore_file);
+                let format = format
+                    .and_then(PostgresDumpFormat::parse)
+                    .unwrap_or_else(|| detect_format_from_file(restore_file));
                 Arc::new(PostgresDatabase::new(cfg, format))
             }
             DbType::PostgresqlCluster => Arc::new(PostgresClusterDatabase::new(cfg)),
@@ -57,11 +128,20 @@ impl DatabaseFactory {
             DbType::Mariadb => Arc::new(MariaDBDatabase::new(cfg)),
             DbType::MongoDB => Arc::new(MongoDatabase::new(cfg)),
             DbType::Sqlite => Arc::new(SqliteDatabase::new(cfg)),
-            DbType::Redis => Arc::new(RedisDatabase::new(cfg)),
+            DbType::Redis | DbType::Keydb => Arc::new(RedisDatabase::new(cfg)),
+            DbType::Dragonfly => Arc::new(DragonflyDatabase::new(cfg)),
             DbType::Valkey => Arc::new(ValkeyDatabase::new(cfg)),
             DbType::Firebird => Arc::new(FirebirdDatabase::new(cfg)),
             DbType::Mssql => Arc::new(MssqlDatabase::new(cfg)),
             DbType::DockerVolume => Arc::new(DockerVolumeDatabase::new(cfg)),
+            DbType::DockerCompose => Arc::new(DockerComposeDatabase::new(cfg)),
+            DbType::Etcd => Arc::new(EtcdDatabase::new(cfg)),
+            DbType::Duckdb => Arc::new(DuckdbDatabase::new(cfg)),
+            DbType::Couchdb => Arc::new(CouchdbDatabase::new(cfg)),
+            DbType::Influxdb => Arc::new(InfluxdbDatabase::new(cfg)),
+            DbType::Neo4j => Arc::new(Neo4jDatabase::new(cfg)),
+            DbType::Cassandra | DbType::Scylladb => Arc::new(CassandraDatabase::new(cfg)),
+            DbType::Filesystem => Arc::new(FilesystemDatabase::new(cfg)),
         }
     }
 }
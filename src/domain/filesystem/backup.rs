@@ -0,0 +1,210 @@
+use super::connection::{SymlinkPolicy, xattrs_enabled};
+use super::filter::Filter;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs::File;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub async fn run(
+    cfg: DatabaseConfig,
+    backup_dir: PathBuf,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        logger.log("info", format!("Starting filesystem backup for {}", cfg.name));
+
+        if cfg.path.is_empty() {
+            anyhow::bail!("Path not configured");
+        }
+        let root = PathBuf::from(&cfg.path);
+        if !root.is_dir() {
+            logger.log("error", format!("Directory not found: {}", root.display()));
+            anyhow::bail!("Directory not found: {}", root.display());
+        }
+
+        let symlinks = SymlinkPolicy::from_config(&cfg)?;
+        let xattrs = xattrs_enabled(&cfg);
+        logger.log(
+            "info",
+            format!("Archiving {} (symlinks: {:?}, xattrs: {})", root.display(), symlinks, xattrs),
+        );
+
+        let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+        let out = File::create(&file_path)
+            .with_context(|| format!("Failed to create backup file {}", file_path.display()))?;
+
+        let mut builder = tar::Builder::new(out);
+        builder.follow_symlinks(symlinks == SymlinkPolicy::Follow);
+
+        let start = Instant::now();
+        let mut archiver = Archiver {
+            root: &root,
+            filter: Filter::from_config(&cfg),
+            symlinks,
+            xattrs,
+            builder,
+            dirs: HashSet::new(),
+            visited: HashSet::new(),
+            entries: 0,
+            skipped: 0,
+            logger: &logger,
+        };
+        if let Ok(canonical) = root.canonicalize() {
+            archiver.visited.insert(canonical);
+        }
+
+        archiver.walk(&root, "", false)?;
+        let (entries, skipped) = (archiver.entries, archiver.skipped);
+        archiver.builder.into_inner()?.sync_all()?;
+
+        let duration_ms = start.elapsed().as_millis() as f64;
+        logger.log_command("tar (filesystem)", None, Some(0), Some(duration_ms));
+        logger.log(
+            "info",
+            format!("Filesystem backup archived {entries} entries ({skipped} skipped) to {}", file_path.display()),
+        );
+        Ok(file_path)
+    })
+    .await?
+}
+
+struct Archiver<'a> {
+    root: &'a Path,
+    filter: Filter,
+    symlinks: SymlinkPolicy,
+    xattrs: bool,
+    builder: tar::Builder<File>,
+    /// Directories already written, so parents of included files are added once.
+    dirs: HashSet<String>,
+    /// Canonical directories entered, guarding against symlink loops in follow mode.
+    visited: HashSet<PathBuf>,
+    entries: u64,
+    skipped: u64,
+    logger: &'a JobLogger,
+}
+
+impl Archiver<'_> {
+    /// `included` is set once an ancestor directory matched an include
+    /// pattern, which pulls in its whole subtree.
+    fn walk(&mut self, dir: &Path, rel_dir: &str, included: bool) -> Result<()> {
+        let mut children: Vec<_> = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?
+            .collect::<std::io::Result<_>>()?;
+        children.sort_by_key(|e| e.file_name());
+
+        for child in children {
+            let path = child.path();
+            let name = child.file_name().to_string_lossy().to_string();
+            let rel = if rel_dir.is_empty() { name } else { format!("{rel_dir}/{name}") };
+
+            if self.filter.is_excluded(&rel) {
+                self.logger.log("debug", format!("Excluded {rel}"));
+                self.skipped += 1;
+                continue;
+            }
+
+            let mut meta = std::fs::symlink_metadata(&path)?;
+            if meta.file_type().is_symlink() {
+                match self.symlinks {
+                    SymlinkPolicy::Skip => {
+                        self.skipped += 1;
+                        continue;
+                    }
+                    SymlinkPolicy::Preserve => {
+                        if included || self.filter.is_included(&rel) {
+                            self.append(&path, &rel)?;
+                        }
+                        continue;
+                    }
+                    SymlinkPolicy::Follow => match std::fs::metadata(&path) {
+                        Ok(target) => meta = target,
+                        Err(e) => {
+                            self.logger.log("warn", format!("Skipping broken symlink {rel}: {e}"));
+                            self.skipped += 1;
+                            continue;
+                        }
+                    },
+                }
+            }
+
+            let file_type = meta.file_type();
+            if file_type.is_dir() {
+                if self.symlinks == SymlinkPolicy::Follow {
+                    let canonical = path.canonicalize()?;
+                    if !self.visited.insert(canonical) {
+                        self.logger.log("warn", format!("Skipping {rel}: symlink loop"));
+                        self.skipped += 1;
+                        continue;
+                    }
+                }
+                let included = included || self.filter.is_included(&rel);
+                if included {
+                    self.append_dir(&rel)?;
+                }
+                self.walk(&path, &rel, included)?;
+            } else if file_type.is_socket() {
+                self.logger.log("debug", format!("Skipping socket {rel}"));
+                self.skipped += 1;
+            } else if included || self.filter.is_included(&rel) {
+                self.append(&path, &rel)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes `rel` and any of its ancestors not yet in the archive.
+    fn append_dir(&mut self, rel: &str) -> Result<()> {
+        if self.dirs.contains(rel) {
+            return Ok(());
+        }
+        if let Some((parent, _)) = rel.rsplit_once('/') {
+            self.append_dir(parent)?;
+        }
+        self.append(&self.root.join(rel), rel)?;
+        self.dirs.insert(rel.to_string());
+        Ok(())
+    }
+
+    fn append(&mut self, path: &Path, rel: &str) -> Result<()> {
+        if let Some((parent, _)) = rel.rsplit_once('/')
+            && !self.dirs.contains(parent)
+        {
+            self.append_dir(parent)?;
+        }
+
+        if self.xattrs {
+            let records = read_xattrs(path, self.symlinks == SymlinkPolicy::Follow);
+            self.builder.append_pax_extensions(
+                records.iter().map(|(k, v)| (k.as_str(), v.as_slice())),
+            )?;
+        }
+
+        self.builder
+            .append_path_with_name(path, rel)
+            .with_context(|| format!("Failed to archive {}", path.display()))?;
+        self.entries += 1;
+        Ok(())
+    }
+}
+
+/// Extended attributes of `path` as PAX records. Attributes that cannot be
+/// read (unsupported filesystem, permissions) are left out.
+fn read_xattrs(path: &Path, follow: bool) -> Vec<(String, Vec<u8>)> {
+    let names = if follow { xattr::list_deref(path) } else { xattr::list(path) };
+    let Ok(names) = names else {
+        return Vec::new();
+    };
+    names
+        .filter_map(|name| {
+            let key = name.to_str()?.to_string();
+            let value = if follow { xattr::get_deref(path, &name) } else { xattr::get(path, &name) };
+            Some((format!("SCHILY.xattr.{key}"), value.ok()??))
+        })
+        .collect()
+}
@@ -0,0 +1,89 @@
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+use std::path::Path;
+
+/// How symbolic links under the backup root are archived (`options.symlinks`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Store the link itself, pointing at the same target.
+    Preserve,
+    /// Store whatever the link points to, as if it were a regular entry.
+    Follow,
+    /// Leave links out of the archive.
+    Skip,
+}
+
+impl SymlinkPolicy {
+    pub fn from_config(cfg: &DatabaseConfig) -> Result<Self> {
+        match option_str(cfg, "symlinks") {
+            None | Some("preserve") => Ok(Self::Preserve),
+            Some("follow") => Ok(Self::Follow),
+            Some("skip") => Ok(Self::Skip),
+            Some(other) => anyhow::bail!("Unknown symlinks policy '{other}', expected preserve, follow or skip"),
+        }
+    }
+}
+
+/// What happens to the existing directory content on restore
+/// (`options.restore_mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreMode {
+    /// Remove everything under the path, then put the archive in place.
+    Wipe,
+    /// Extract over the existing tree, overwriting files present in the archive
+    /// and leaving the others alone.
+    Merge,
+}
+
+impl RestoreMode {
+    pub fn from_config(cfg: &DatabaseConfig) -> Result<Self> {
+        match option_str(cfg, "restore_mode") {
+            None | Some("wipe") => Ok(Self::Wipe),
+            Some("merge") => Ok(Self::Merge),
+            Some(other) => anyhow::bail!("Unknown restore_mode '{other}', expected wipe or merge"),
+        }
+    }
+}
+
+fn option_str<'a>(cfg: &'a DatabaseConfig, key: &str) -> Option<&'a str> {
+    cfg.options.get(key).and_then(|v| v.as_str())
+}
+
+/// Extended attributes are stored as PAX `SCHILY.xattr.*` records and applied
+/// back on restore when enabled (`options.xattrs`, off by default).
+pub fn xattrs_enabled(cfg: &DatabaseConfig) -> bool {
+    cfg.options.get("xattrs").and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// Restoring uid/gid needs the agent to run as root; set
+/// `options.preserve_ownership` to false to extract as the agent user.
+pub fn preserve_ownership(cfg: &DatabaseConfig) -> bool {
+    cfg.options
+        .get("preserve_ownership")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+}
+
+/// Name used for the staging directory a wipe restore extracts into. It lives
+/// inside the target so the final moves never cross a filesystem boundary.
+pub fn staging_name(cfg: &DatabaseConfig) -> String {
+    format!(".portabase-restore-{}", cfg.generated_id)
+}
+
+/// Removes everything under `dir` except the entry named `keep`, leaving `dir`
+/// itself in place since it is often a mount point.
+pub fn clear_dir(dir: &Path, keep: &str) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name() == keep {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use crate::services::config::DatabaseConfig;
+
+/// Include/exclude globs applied to paths relative to the backup root.
+///
+/// A pattern without `/` is matched against the entry's file name at any
+/// depth (`*.log`); otherwise it is matched against the whole relative path
+/// (`cache/**`). `*` and `?` never cross a `/`, `**` does.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl Filter {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        Self { include, exclude }
+    }
+
+    pub fn from_config(cfg: &DatabaseConfig) -> Self {
+        Self::new(patterns(cfg, "include"), patterns(cfg, "exclude"))
+    }
+
+    /// Excluded entries are skipped, and so is everything below an excluded
+    /// directory.
+    pub fn is_excluded(&self, rel: &str) -> bool {
+        self.exclude.iter().any(|p| matches(p, rel))
+    }
+
+    /// Whether a file (or a directory, together with its whole subtree)
+    /// should be archived. Always true when no include pattern is set.
+    pub fn is_included(&self, rel: &str) -> bool {
+        self.include.is_empty() || self.include.iter().any(|p| matches(p, rel))
+    }
+}
+
+fn patterns(cfg: &DatabaseConfig, key: &str) -> Vec<String> {
+    cfg.options
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|p| p.as_str())
+                .map(|p| p.trim_start_matches("./").trim_end_matches('/').to_string())
+                .filter(|p| !p.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn matches(pattern: &str, rel: &str) -> bool {
+    let target = if pattern.contains('/') {
+        rel
+    } else {
+        rel.rsplit('/').next().unwrap_or(rel)
+    };
+    let pat: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = target.chars().collect();
+    glob_match(&pat, &text)
+}
+
+fn glob_match(pat: &[char], text: &[char]) -> bool {
+    match pat {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // `a/**/b` also matches `a/b`.
+            if let ['/', after @ ..] = rest
+                && glob_match(after, text)
+            {
+                return true;
+            }
+            (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+        }
+        ['*', rest @ ..] => {
+            let segment = text.iter().position(|c| *c == '/').unwrap_or(text.len());
+            (0..=segment).any(|i| glob_match(rest, &text[i..]))
+        }
+        ['?', rest @ ..] => matches!(text, [c, tail @ ..] if *c != '/' && glob_match(rest, tail)),
+        [p, rest @ ..] => matches!(text, [c, tail @ ..] if c == p && glob_match(rest, tail)),
+    }
+}
@@ -0,0 +1,6 @@
+mod backup;
+pub mod connection;
+pub mod database;
+pub mod filter;
+mod ping;
+mod restore;
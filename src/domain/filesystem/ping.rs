@@ -0,0 +1,6 @@
+use crate::services::config::DatabaseConfig;
+use std::path::Path;
+
+pub async fn run(cfg: DatabaseConfig) -> anyhow::Result<bool> {
+    Ok(!cfg.path.is_empty() && Path::new(&cfg.path).is_dir())
+}
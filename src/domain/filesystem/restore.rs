@@ -0,0 +1,77 @@
+use super::connection::{RestoreMode, clear_dir, preserve_ownership, staging_name, xattrs_enabled};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// In wipe mode the archive is first extracted into a staging directory inside
+/// the target, so a corrupt archive fails before anything is deleted. Merge
+/// mode extracts straight over the existing tree.
+pub async fn run(cfg: DatabaseConfig, restore_file: PathBuf, logger: Arc<JobLogger>) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        logger.log("info", format!("Starting filesystem restore for {}", cfg.name));
+
+        if cfg.path.is_empty() {
+            anyhow::bail!("Path not configured");
+        }
+        if !restore_file.exists() {
+            anyhow::bail!("Restore file not found: {}", restore_file.display());
+        }
+
+        let mode = RestoreMode::from_config(&cfg)?;
+        let target = PathBuf::from(&cfg.path);
+        std::fs::create_dir_all(&target)
+            .with_context(|| format!("Failed to create {}", target.display()))?;
+
+        logger.log("info", format!("Restoring into {} ({:?} mode)", target.display(), mode));
+
+        let start = Instant::now();
+        match mode {
+            RestoreMode::Merge => unpack(&cfg, &restore_file, &target)?,
+            RestoreMode::Wipe => {
+                let keep = staging_name(&cfg);
+                let staging = target.join(&keep);
+                if staging.exists() {
+                    std::fs::remove_dir_all(&staging)?;
+                }
+                std::fs::create_dir(&staging)?;
+
+                if let Err(e) = unpack(&cfg, &restore_file, &staging) {
+                    let _ = std::fs::remove_dir_all(&staging);
+                    logger.log("error", format!("Extraction failed, {} left untouched: {e}", target.display()));
+                    return Err(e);
+                }
+
+                clear_dir(&target, &keep)
+                    .with_context(|| format!("Failed to clear {}", target.display()))?;
+                for entry in std::fs::read_dir(&staging)? {
+                    let entry = entry?;
+                    std::fs::rename(entry.path(), target.join(entry.file_name()))
+                        .with_context(|| format!("Failed to move {} into place", entry.path().display()))?;
+                }
+                std::fs::remove_dir(&staging)?;
+            }
+        }
+
+        let duration_ms = start.elapsed().as_millis() as f64;
+        logger.log_command("tar -x (filesystem)", None, Some(0), Some(duration_ms));
+        logger.log("info", format!("Filesystem restore completed for {}", cfg.name));
+        Ok(())
+    })
+    .await?
+}
+
+fn unpack(cfg: &DatabaseConfig, restore_file: &Path, dest: &Path) -> Result<()> {
+    let file = std::fs::File::open(restore_file)?;
+    let mut archive = tar::Archive::new(file);
+    archive.set_preserve_permissions(true);
+    archive.set_preserve_mtime(true);
+    archive.set_preserve_ownerships(preserve_ownership(cfg));
+    archive.set_unpack_xattrs(xattrs_enabled(cfg));
+    archive.set_overwrite(true);
+    archive
+        .unpack(dest)
+        .with_context(|| format!("Failed to extract {} into {}", restore_file.display(), dest.display()))
+}
@@ -0,0 +1,65 @@
+use super::connection::{backup_args, influx_env};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+
+pub async fn run(
+    cfg: DatabaseConfig,
+    backup_dir: PathBuf,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        let scope = if cfg.database.is_empty() { "all buckets".to_string() } else { format!("bucket {}", cfg.database) };
+        logger.log("info", format!("Starting InfluxDB backup for {} ({})", cfg.name, scope));
+
+        let export_dir = backup_dir.join(format!("{}_export", cfg.generated_id));
+        std::fs::create_dir_all(&export_dir)?;
+
+        let args = backup_args(&cfg);
+        let start = Instant::now();
+        let output = Command::new("influx")
+            .arg("backup")
+            .arg(&export_dir)
+            .args(&args)
+            .envs(influx_env(&cfg))
+            .output()
+            .context("influx backup command failed to start")?;
+        let duration_ms = start.elapsed().as_millis() as f64;
+        let exit_code = output.status.code().unwrap_or(-1);
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let combined = format!("{}{}", stdout, stderr).trim().to_string();
+        let command = format!("influx backup {}", args.join(" ")).trim_end().to_string();
+
+        if !output.status.success() {
+            logger.log("error", format!("InfluxDB backup failed for {}: {}", cfg.name, stderr));
+            logger.log_command(command, Some(combined), Some(exit_code), Some(duration_ms));
+            anyhow::bail!("InfluxDB backup failed for {}: {}", cfg.name, stderr);
+        }
+
+        logger.log_command(
+            command,
+            if combined.is_empty() { None } else { Some(combined) },
+            Some(0),
+            Some(duration_ms),
+        );
+
+        let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+        let out = std::fs::File::create(&file_path)
+            .with_context(|| format!("Failed to create {}", file_path.display()))?;
+        let mut tar = tar::Builder::new(out);
+        tar.append_dir_all(".", &export_dir)?;
+        tar.finish()?;
+        std::fs::remove_dir_all(&export_dir).ok();
+
+        logger.log("info", format!("InfluxDB backup completed for {}", cfg.name));
+        Ok(file_path)
+    })
+    .await?
+}
@@ -0,0 +1,152 @@
+use crate::services::config::DatabaseConfig;
+use std::path::{Path, PathBuf};
+
+fn option_str<'a>(cfg: &'a DatabaseConfig, key: &str) -> Option<&'a str> {
+    cfg.options
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn option_bool(cfg: &DatabaseConfig, key: &str) -> bool {
+    cfg.options.get(key).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+pub fn host_url(cfg: &DatabaseConfig) -> String {
+    let scheme = if option_bool(cfg, "tls") { "https" } else { "http" };
+    format!("{}://{}:{}", scheme, cfg.host, cfg.port)
+}
+
+/// Operator token from `options.token`, falling back to the password field.
+/// Backups of all buckets require an operator (all-access) token.
+pub fn token(cfg: &DatabaseConfig) -> Option<&str> {
+    option_str(cfg, "token").or_else(|| Some(cfg.password.as_str()).filter(|p| !p.is_empty()))
+}
+
+/// Environment understood by the `influx` CLI. The token is passed here
+/// rather than with `--token` so it never shows up in the process list.
+pub fn influx_env(cfg: &DatabaseConfig) -> Vec<(String, String)> {
+    let mut envs = vec![("INFLUX_HOST".to_string(), host_url(cfg))];
+    if let Some(token) = token(cfg) {
+        envs.push(("INFLUX_TOKEN".to_string(), token.to_string()));
+    }
+    if let Some(org) = option_str(cfg, "org") {
+        envs.push(("INFLUX_ORG".to_string(), org.to_string()));
+    }
+    envs
+}
+
+pub fn common_args(cfg: &DatabaseConfig) -> Vec<String> {
+    let mut args = Vec::new();
+    if option_bool(cfg, "skip_verify") {
+        args.push("--skip-verify".to_string());
+    }
+    args
+}
+
+/// `database` selects a single bucket; empty backs up every bucket.
+pub fn backup_args(cfg: &DatabaseConfig) -> Vec<String> {
+    let mut args = common_args(cfg);
+    if !cfg.database.is_empty() {
+        args.push(format!("--bucket={}", cfg.database));
+    }
+    args
+}
+
+/// `options.full_restore` replaces all server data, including tokens and users.
+pub fn full_restore(cfg: &DatabaseConfig) -> bool {
+    option_bool(cfg, "full_restore")
+}
+
+/// `options.restore_bucket`: restore the configured bucket under this name.
+pub fn restore_bucket(cfg: &DatabaseConfig) -> Option<&str> {
+    option_str(cfg, "restore_bucket")
+}
+
+/// Restores the configured bucket, under `new_bucket` when given.
+pub fn restore_args(cfg: &DatabaseConfig, new_bucket: Option<&str>) -> Vec<String> {
+    let mut args = common_args(cfg);
+    if full_restore(cfg) {
+        args.push("--full".to_string());
+        return args;
+    }
+    if !cfg.database.is_empty() {
+        args.push(format!("--bucket={}", cfg.database));
+        if let Some(new_bucket) = new_bucket {
+            args.push(format!("--new-bucket={}", new_bucket));
+        }
+    }
+    args
+}
+
+/// Bucket an in-place restore replaces. `influx restore` refuses to write
+/// into an existing bucket, so the backup is restored under a staging name
+/// and swapped in once that succeeded. `None` when restoring under
+/// `restore_bucket` or in full.
+pub fn replaced_bucket(cfg: &DatabaseConfig) -> Option<&str> {
+    if full_restore(cfg) || restore_bucket(cfg).is_some() {
+        return None;
+    }
+    Some(cfg.database.as_str()).filter(|db| !db.is_empty())
+}
+
+/// Unique name to restore `bucket` under before it replaces the live one.
+pub fn staging_bucket(bucket: &str) -> String {
+    format!("{bucket}_restore_{}", uuid::Uuid::new_v4().simple())
+}
+
+pub fn list_bucket_args(cfg: &DatabaseConfig, bucket: &str) -> Vec<String> {
+    let mut args = vec!["bucket".to_string(), "list".to_string(), format!("--name={bucket}"), "--json".to_string()];
+    args.extend(common_args(cfg));
+    args
+}
+
+pub fn delete_bucket_args(cfg: &DatabaseConfig, bucket: &str) -> Vec<String> {
+    let mut args = vec!["bucket".to_string(), "delete".to_string(), format!("--name={bucket}")];
+    args.extend(common_args(cfg));
+    args
+}
+
+pub fn rename_bucket_args(cfg: &DatabaseConfig, id: &str, name: &str) -> Vec<String> {
+    let mut args = vec!["bucket".to_string(), "update".to_string(), format!("--id={id}"), format!("--name={name}")];
+    args.extend(common_args(cfg));
+    args
+}
+
+/// Whether a failed `influx bucket list --name` only reported that `bucket`
+/// does not exist, which the server answers with a 404.
+pub fn bucket_missing(stderr: &str, bucket: &str) -> bool {
+    stderr.contains(&format!("bucket \"{bucket}\" not found"))
+}
+
+/// ID of `bucket` in the output of `influx bucket list --json`.
+pub fn bucket_id(list_json: &str, bucket: &str) -> anyhow::Result<Option<String>> {
+    let buckets: Vec<serde_json::Value> = serde_json::from_str(list_json.trim())
+        .map_err(|e| anyhow::anyhow!("Unexpected influx bucket list output: {e}"))?;
+    Ok(buckets
+        .iter()
+        .find(|b| b.get("name").and_then(|n| n.as_str()) == Some(bucket))
+        .and_then(|b| b.get("id").and_then(|id| id.as_str()))
+        .map(str::to_string))
+}
+
+/// Locates the directory produced by `influx backup` (the one holding the
+/// `*.manifest` file) inside an unpacked backup archive.
+pub fn find_backup_dir(root: &Path) -> Option<PathBuf> {
+    let entries: Vec<PathBuf> = std::fs::read_dir(root)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .collect();
+    if entries
+        .iter()
+        .any(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "manifest"))
+    {
+        return Some(root.to_path_buf());
+    }
+    entries
+        .into_iter()
+        .filter(|p| p.is_dir())
+        .find_map(|p| find_backup_dir(&p))
+}
@@ -0,0 +1,5 @@
+mod backup;
+pub mod connection;
+pub mod database;
+mod ping;
+mod restore;
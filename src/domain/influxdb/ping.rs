@@ -0,0 +1,31 @@
+use super::connection::{host_url, token};
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+use std::time::Duration;
+use tracing::{error, info};
+
+pub async fn run(cfg: DatabaseConfig) -> Result<bool> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    let mut req = client.get(format!("{}/health", host_url(&cfg)));
+    if let Some(token) = token(&cfg) {
+        req = req.header("Authorization", format!("Token {}", token));
+    }
+
+    match req.send().await {
+        Ok(res) if res.status().is_success() => {
+            let body: serde_json::Value = res.json().await.unwrap_or_default();
+            Ok(body.get("status").and_then(|s| s.as_str()) == Some("pass"))
+        }
+        Ok(res) => {
+            error!("InfluxDB health check returned {} for {}:{}", res.status(), cfg.host, cfg.port);
+            Ok(false)
+        }
+        Err(e) => {
+            info!("Failed to reach InfluxDB at {}:{}: {}", cfg.host, cfg.port, e);
+            Ok(false)
+        }
+    }
+}
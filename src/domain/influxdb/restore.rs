@@ -0,0 +1,136 @@
+use super::connection::{
+    bucket_id, bucket_missing, delete_bucket_args, find_backup_dir, full_restore, influx_env, list_bucket_args,
+    rename_bucket_args, replaced_bucket, restore_args, restore_bucket, staging_bucket,
+};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+
+pub async fn run(cfg: DatabaseConfig, restore_file: PathBuf, logger: Arc<JobLogger>) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        logger.log("debug", format!("Starting InfluxDB restore for {}", cfg.name));
+
+        if !restore_file.exists() {
+            anyhow::bail!("Restore file not found: {}", restore_file.display());
+        }
+
+        // Without --full, every bucket in the backup has to be new to the server.
+        if cfg.database.is_empty() && !full_restore(&cfg) {
+            anyhow::bail!(
+                "InfluxDB restore of every bucket needs options.full_restore; set database to restore a single bucket"
+            );
+        }
+
+        let unpacked = tempfile::TempDir::new()?;
+        let tar = std::fs::File::open(&restore_file)?;
+        tar::Archive::new(tar)
+            .unpack(unpacked.path())
+            .with_context(|| format!("Failed to unpack {}", restore_file.display()))?;
+
+        let backup_dir = find_backup_dir(unpacked.path())
+            .ok_or_else(|| anyhow::anyhow!("Invalid InfluxDB archive: manifest not found"))?;
+
+        // In-place restores go to a staging bucket first so the live one is
+        // only dropped once the backup restored cleanly.
+        let replaced = replaced_bucket(&cfg);
+        let staging = replaced.map(staging_bucket);
+        let new_bucket = staging.as_deref().or(restore_bucket(&cfg));
+
+        let args = restore_args(&cfg, new_bucket);
+        logger.log("info", format!("Running influx restore for {} ({})", cfg.name, args.join(" ")));
+
+        let start = Instant::now();
+        let output = Command::new("influx")
+            .arg("restore")
+            .arg(&backup_dir)
+            .args(&args)
+            .envs(influx_env(&cfg))
+            .output()
+            .context("influx restore command failed to start")?;
+        let duration_ms = start.elapsed().as_millis() as f64;
+        let exit_code = output.status.code().unwrap_or(-1);
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let combined = format!("{}{}", stdout, stderr).trim().to_string();
+        let command = format!("influx restore {}", args.join(" ")).trim_end().to_string();
+
+        if !output.status.success() {
+            logger.log_command(command, Some(combined), Some(exit_code), Some(duration_ms));
+            logger.log("error", format!("InfluxDB restore failed for {}: {}", cfg.name, stderr));
+            if let Some(staging) = &staging
+                && let Err(e) = drop_bucket(&cfg, staging, &logger)
+            {
+                logger.log("warn", format!("Could not remove staging bucket {staging}: {e}"));
+            }
+            anyhow::bail!("InfluxDB restore failed for {}: {}", cfg.name, stderr);
+        }
+
+        logger.log_command(
+            command,
+            if combined.is_empty() { None } else { Some(combined) },
+            Some(0),
+            Some(duration_ms),
+        );
+
+        if let (Some(bucket), Some(staging)) = (replaced, &staging) {
+            logger.log("info", format!("Replacing bucket {bucket} with restored bucket {staging}"));
+            swap_bucket(&cfg, bucket, staging, &logger).with_context(|| {
+                format!("InfluxDB restore of {} is kept in bucket {staging}", cfg.name)
+            })?;
+        }
+
+        logger.log("info", format!("InfluxDB restore completed for {}", cfg.name));
+        Ok(())
+    })
+    .await?
+}
+
+/// Runs an `influx` subcommand, returning its stdout.
+fn influx(cfg: &DatabaseConfig, args: &[String], logger: &JobLogger) -> Result<String> {
+    let output = Command::new("influx")
+        .args(args)
+        .envs(influx_env(cfg))
+        .output()
+        .with_context(|| format!("influx {} failed to start", args[..2].join(" ")))?;
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    logger.log_command(
+        format!("influx {}", args.join(" ")),
+        Some(stderr.clone()).filter(|s| !s.is_empty()),
+        output.status.code(),
+        None,
+    );
+    if !output.status.success() {
+        anyhow::bail!("influx {} failed: {stderr}", args[..2].join(" "));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// ID of `bucket`, or `None` when the server has no bucket of that name.
+fn find_bucket(cfg: &DatabaseConfig, bucket: &str, logger: &JobLogger) -> Result<Option<String>> {
+    match influx(cfg, &list_bucket_args(cfg, bucket), logger) {
+        Ok(stdout) => bucket_id(&stdout, bucket),
+        Err(e) if bucket_missing(&e.to_string(), bucket) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn drop_bucket(cfg: &DatabaseConfig, bucket: &str, logger: &JobLogger) -> Result<()> {
+    if find_bucket(cfg, bucket, logger)?.is_some() {
+        influx(cfg, &delete_bucket_args(cfg, bucket), logger)?;
+    }
+    Ok(())
+}
+
+/// Deletes the live `bucket`, if any, and renames `staging` to take its place.
+fn swap_bucket(cfg: &DatabaseConfig, bucket: &str, staging: &str, logger: &JobLogger) -> Result<()> {
+    let id = find_bucket(cfg, staging, logger)?
+        .ok_or_else(|| anyhow::anyhow!("Restored bucket {staging} not found"))?;
+    drop_bucket(cfg, bucket, logger)?;
+    influx(cfg, &rename_bucket_args(cfg, &id, bucket), logger)?;
+    Ok(())
+}
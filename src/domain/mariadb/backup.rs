@@ -1,6 +1,7 @@
 use crate::domain::mariadb::connection::{select_mariadb_path, server_version};
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
+use crate::utils::stream::{DumpStream, command_stream};
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -8,6 +9,44 @@ use std::process::Command;
 use std::sync::Arc;
 use std::time::Instant;
 
+fn dump_command(cfg: &DatabaseConfig, env: HashMap<String, String>) -> Command {
+    let mut cmd = Command::new("mariadb-dump");
+    cmd.arg("--host").arg(&cfg.host)
+        .arg("--port").arg(cfg.port.to_string())
+        .arg("--user").arg(&cfg.username)
+        .arg("--routines")
+        .arg("--events")
+        .arg("--triggers")
+        .arg("--single-transaction")
+        .arg("--quick")
+        .arg("--skip-lock-tables")
+        .arg("--no-create-db")
+        .arg("--skip-add-drop-table")
+        .arg("--compress")
+        .arg("--verbose")
+        .arg(format!("--max-allowed-packet={}", cfg.max_packet_size))
+        .arg("--net-buffer-length=16K")
+        .arg("--default-character-set=utf8mb4")
+        .arg(&cfg.database)
+        .envs(env);
+    cmd
+}
+
+/// `mariadb-dump` writing to stdout, for streaming backups.
+pub async fn stream(
+    cfg: &DatabaseConfig,
+    env: HashMap<String, String>,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<DumpStream> {
+    logger.log("info", format!("Running mariadb-dump for {} (streaming)", cfg.name));
+    let cmd = tokio::process::Command::from(dump_command(cfg, env));
+    Ok(DumpStream {
+        file_name: format!("{}{}", cfg.generated_id, file_extension),
+        stream: command_stream(cmd, "mariadb-dump".to_string(), logger)?,
+    })
+}
+
 pub async fn run(
     cfg: DatabaseConfig,
     backup_dir: PathBuf,
@@ -41,26 +80,8 @@ pub async fn run(
         logger.log("info", format!("Running mariadb-dump for {}", cfg.name));
 
         let start = Instant::now();
-        let output = Command::new("mariadb-dump")
-            .arg("--host").arg(&cfg.host)
-            .arg("--port").arg(cfg.port.to_string())
-            .arg("--user").arg(&cfg.username)
-            .arg("--routines")
-            .arg("--events")
-            .arg("--triggers")
-            .arg("--single-transaction")
-            .arg("--quick")
-            .arg("--skip-lock-tables")
-            .arg("--no-create-db")
-            .arg("--skip-add-drop-table")
-            .arg("--compress")
-            .arg("--verbose")
-            .arg(format!("--max-allowed-packet={}", cfg.max_packet_size))
-            .arg("--net-buffer-length=16K")
-            .arg("--default-character-set=utf8mb4")
-            .arg(&cfg.database)
+        let output = dump_command(&cfg, env)
             .arg("-r").arg(&file_path)
-            .envs(env)
             .output()
             .with_context(|| format!("Failed to run mariadb-dump for {}", cfg.name))?;
         let duration_ms = start.elapsed().as_millis() as f64;
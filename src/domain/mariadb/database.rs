@@ -1,8 +1,11 @@
-use super::{backup, ping, restore};
-use crate::domain::factory::Database;
+use super::{backup, connection, ping, restore};
+use crate::domain::docker_exec::{self, Execution};
+use crate::domain::mysql::exec::{self, Flavor};
+use crate::domain::factory::{Database, DumpInfo};
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
 use crate::utils::locks::{DbOpLock, FileLock};
+use crate::utils::stream::DumpStream;
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -32,26 +35,71 @@ impl Database for MariaDBDatabase {
     }
 
     async fn ping(&self) -> Result<bool> {
-        ping::run(self.cfg.clone(), self.build_env().clone()).await
+        match Execution::from_config(&self.cfg)? {
+            Execution::Local => ping::run(self.cfg.clone(), self.build_env().clone()).await,
+            Execution::Container(container) => exec::ping(&self.cfg, &container, Flavor::Mariadb).await,
+        }
     }
 
     async fn backup(&self, dir: &Path, logger: Arc<JobLogger>) -> Result<PathBuf> {
         FileLock::acquire(&self.cfg.generated_id, DbOpLock::Backup.as_str()).await?;
-        let res = backup::run(
-            self.cfg.clone(),
-            dir.to_path_buf(),
-            self.build_env().clone(),
-            self.file_extension(),
-            logger,
-        )
-        .await;
+        let res = match Execution::from_config(&self.cfg) {
+            Ok(Execution::Local) => {
+                backup::run(
+                    self.cfg.clone(),
+                    dir.to_path_buf(),
+                    self.build_env().clone(),
+                    self.file_extension(),
+                    logger,
+                )
+                .await
+            }
+            Ok(Execution::Container(container)) => {
+                exec::backup(&self.cfg, &container, Flavor::Mariadb, dir, self.file_extension(), &logger).await
+            }
+            Err(e) => Err(e),
+        };
         FileLock::release(&self.cfg.generated_id).await?;
         res
     }
 
+    async fn backup_stream(&self, logger: Arc<JobLogger>) -> Result<Option<DumpStream>> {
+        if Execution::from_config(&self.cfg)? != Execution::Local {
+            return Ok(None);
+        }
+        FileLock::acquire(&self.cfg.generated_id, DbOpLock::Backup.as_str()).await?;
+        match backup::stream(&self.cfg, self.build_env(), self.file_extension(), logger).await {
+            Ok(dump) => Ok(Some(dump)),
+            Err(e) => {
+                FileLock::release(&self.cfg.generated_id).await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn describe(&self, _streamed: bool) -> DumpInfo {
+        DumpInfo {
+            server_version: connection::server_version(&self.cfg).await.ok(),
+            tool: Some("mariadb-dump".to_string()),
+            tool_version: docker_exec::tool_version(&self.cfg, "mariadb-dump").await,
+            format: Some("sql".to_string()),
+            ..Default::default()
+        }
+    }
+
+    async fn estimated_size(&self) -> Option<u64> {
+        connection::data_size(&self.cfg).await.ok()
+    }
+
     async fn restore(&self, file: &Path, logger: Arc<JobLogger>) -> Result<()> {
         FileLock::acquire(&self.cfg.generated_id, DbOpLock::Restore.as_str()).await?;
-        let res = restore::run(self.cfg.clone(), file.to_path_buf(), logger).await;
+        let res = match Execution::from_config(&self.cfg) {
+            Ok(Execution::Local) => restore::run(self.cfg.clone(), file.to_path_buf(), logger).await,
+            Ok(Execution::Container(container)) => {
+                exec::restore(&self.cfg, &container, Flavor::Mariadb, file, &logger).await
+            }
+            Err(e) => Err(e),
+        };
         FileLock::release(&self.cfg.generated_id).await?;
         res
     }
@@ -1,11 +1,21 @@
+pub mod docker_compose;
+pub mod docker_exec;
 pub mod docker_volume;
 pub mod factory;
-mod mongodb;
+pub mod mongodb;
 pub mod mysql;
 pub mod postgres;
-mod redis;
+pub mod redis;
 mod sqlite;
 mod valkey;
-mod mariadb;
+pub mod mariadb;
 mod firebird;
 pub mod mssql;
+pub mod etcd;
+pub mod duckdb;
+pub mod couchdb;
+pub mod influxdb;
+pub mod neo4j;
+pub mod cassandra;
+pub mod dragonfly;
+pub mod filesystem;
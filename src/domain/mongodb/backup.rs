@@ -1,12 +1,33 @@
 use crate::domain::mongodb::connection::{get_mongo_uri, select_mongo_path};
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
+use crate::utils::stream::{DumpStream, command_stream};
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::Arc;
 use std::time::Instant;
 
+fn dump_command(uri: &str) -> Command {
+    let mut cmd = Command::new(select_mongo_path().join("mongodump"));
+    cmd.arg(format!("--uri={}", uri))
+        .arg("--gzip")
+        .arg("--verbose");
+    cmd
+}
+
+/// `mongodump --archive` without a path writes the archive to stdout.
+pub async fn stream(cfg: &DatabaseConfig, file_extension: &'static str, logger: Arc<JobLogger>) -> Result<DumpStream> {
+    logger.log("info", format!("Running mongodump for {} (streaming)", cfg.name));
+    let uri = get_mongo_uri(cfg.clone())?;
+    let mut cmd = tokio::process::Command::from(dump_command(&uri));
+    cmd.arg("--archive");
+    Ok(DumpStream {
+        file_name: format!("{}{}", cfg.generated_id, file_extension),
+        stream: command_stream(cmd, "mongodump".to_string(), logger)?,
+    })
+}
+
 pub async fn run(
     cfg: DatabaseConfig,
     backup_dir: PathBuf,
@@ -17,17 +38,13 @@ pub async fn run(
         logger.log("info", format!("Starting MongoDB backup for database {}", cfg.name));
 
         let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
-        let mongodump = select_mongo_path().join("mongodump");
         let uri = get_mongo_uri(cfg.clone())?;
 
         logger.log("info", format!("Running mongodump for {}", cfg.name));
 
         let start = Instant::now();
-        let output = Command::new(mongodump)
-            .arg(format!("--uri={}", uri))
+        let output = dump_command(&uri)
             .arg(format!("--archive={}", file_path.display()))
-            .arg("--gzip")
-            .arg("--verbose")
             .output()
             .context("MongoDB backup failed")?;
         let duration_ms = start.elapsed().as_millis() as f64;
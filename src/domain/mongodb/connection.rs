@@ -1,6 +1,7 @@
 use crate::services::config::DatabaseConfig;
 use anyhow::Result;
 use mongodb::Client;
+use mongodb::bson::{Bson, doc};
 
 pub async fn connect(cfg: DatabaseConfig) -> Result<Client> {
     let uri = get_mongo_uri(cfg)?;
@@ -43,3 +44,17 @@ pub fn extract_db_name(dry_output: &str) -> Option<String> {
     }
     dbs.into_iter().next()
 }
+
+/// Uncompressed document bytes of the database, without indexes, from
+/// `dbStats`.
+pub async fn data_size(cfg: &DatabaseConfig) -> Result<u64> {
+    let client = connect(cfg.clone()).await?;
+    let stats = client.database(&cfg.database).run_command(doc! {"dbStats": 1}).await?;
+    let size = match stats.get("dataSize") {
+        Some(Bson::Int32(n)) => *n as f64,
+        Some(Bson::Int64(n)) => *n as f64,
+        Some(Bson::Double(n)) => *n,
+        _ => anyhow::bail!("dbStats returned no dataSize"),
+    };
+    Ok(size.max(0.0) as u64)
+}
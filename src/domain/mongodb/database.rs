@@ -3,11 +3,13 @@ use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use super::{backup, ping, restore};
+use super::{backup, connection, exec, ping, restore};
+use crate::domain::docker_exec::Execution;
 use crate::domain::factory::Database;
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
 use crate::utils::locks::{DbOpLock, FileLock};
+use crate::utils::stream::DumpStream;
 
 pub struct MongoDatabase {
     cfg: DatabaseConfig,
@@ -26,19 +28,55 @@ impl Database for MongoDatabase {
     }
 
     async fn ping(&self) -> Result<bool> {
-        ping::run(self.cfg.clone()).await
+        match Execution::from_config(&self.cfg)? {
+            Execution::Local => ping::run(self.cfg.clone()).await,
+            Execution::Container(container) => exec::ping(&self.cfg, &container).await,
+        }
     }
 
     async fn backup(&self, dir: &Path, logger: Arc<JobLogger>) -> Result<PathBuf> {
         FileLock::acquire(&self.cfg.generated_id, DbOpLock::Backup.as_str()).await?;
-        let res = backup::run(self.cfg.clone(), dir.to_path_buf(), self.file_extension(), logger).await;
+        let res = match Execution::from_config(&self.cfg) {
+            Ok(Execution::Local) => {
+                backup::run(self.cfg.clone(), dir.to_path_buf(), self.file_extension(), logger).await
+            }
+            Ok(Execution::Container(container)) => {
+                exec::backup(&self.cfg, &container, dir, self.file_extension(), &logger).await
+            }
+            Err(e) => Err(e),
+        };
         FileLock::release(&self.cfg.generated_id).await?;
         res
     }
 
+    async fn backup_stream(&self, logger: Arc<JobLogger>) -> Result<Option<DumpStream>> {
+        if Execution::from_config(&self.cfg)? != Execution::Local {
+            return Ok(None);
+        }
+        FileLock::acquire(&self.cfg.generated_id, DbOpLock::Backup.as_str()).await?;
+        match backup::stream(&self.cfg, self.file_extension(), logger).await {
+            Ok(dump) => Ok(Some(dump)),
+            Err(e) => {
+                FileLock::release(&self.cfg.generated_id).await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn estimated_size(&self) -> Option<u64> {
+        if self.cfg.database.is_empty() {
+            return None;
+        }
+        connection::data_size(&self.cfg).await.ok()
+    }
+
     async fn restore(&self, file: &Path, logger: Arc<JobLogger>) -> Result<()> {
         FileLock::acquire(&self.cfg.generated_id, DbOpLock::Restore.as_str()).await?;
-        let res = restore::run(self.cfg.clone(), file.to_path_buf(), logger).await;
+        let res = match Execution::from_config(&self.cfg) {
+            Ok(Execution::Local) => restore::run(self.cfg.clone(), file.to_path_buf(), logger).await,
+            Ok(Execution::Container(container)) => exec::restore(&self.cfg, &container, file, &logger).await,
+            Err(e) => Err(e),
+        };
         FileLock::release(&self.cfg.generated_id).await?;
         res
     }
@@ -0,0 +1,89 @@
+use crate::domain::docker_exec::{self, ExecCommand};
+use crate::domain::mongodb::connection::{extract_db_name, get_mongo_uri};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Older images only ship the legacy `mongo` shell.
+const PING_SCRIPT: &str = r#"if command -v mongosh >/dev/null 2>&1; then shell=mongosh; else shell=mongo; fi
+exec "$shell" "$1" --quiet --eval 'db.adminCommand({ ping: 1 })'"#;
+
+/// URI for the server as seen from inside its own container, on the
+/// configured port or mongod's default when none is set.
+pub fn local_uri(cfg: &DatabaseConfig) -> Result<String> {
+    let mut local = cfg.clone();
+    local.host = "localhost".to_string();
+    if local.port == 0 {
+        local.port = 27017;
+    }
+    get_mongo_uri(local)
+}
+
+pub fn ping_command(cfg: &DatabaseConfig) -> Result<ExecCommand> {
+    Ok(ExecCommand::shell(PING_SCRIPT, [local_uri(cfg)?]))
+}
+
+pub fn dump_command(cfg: &DatabaseConfig) -> Result<ExecCommand> {
+    Ok(ExecCommand::new([
+        "mongodump".to_string(),
+        format!("--uri={}", local_uri(cfg)?),
+        "--archive".to_string(),
+        "--gzip".to_string(),
+        "--verbose".to_string(),
+    ]))
+}
+
+pub fn restore_command(cfg: &DatabaseConfig, source_db: Option<&str>) -> Result<ExecCommand> {
+    let mut args = vec![
+        "mongorestore".to_string(),
+        format!("--uri={}", local_uri(cfg)?),
+        "--archive".to_string(),
+        "--gzip".to_string(),
+    ];
+    match source_db {
+        None => args.extend(["--dryRun".to_string(), "--verbose".to_string()]),
+        Some(source) => args.extend([
+            "--drop".to_string(),
+            format!("--nsInclude={source}.*"),
+            format!("--nsFrom={source}.*"),
+            format!("--nsTo={}.*", cfg.database),
+        ]),
+    }
+    Ok(ExecCommand::new(args))
+}
+
+pub async fn ping(cfg: &DatabaseConfig, container: &str) -> Result<bool> {
+    docker_exec::probe(cfg, container, ping_command(cfg)?).await
+}
+
+pub async fn backup(
+    cfg: &DatabaseConfig,
+    container: &str,
+    backup_dir: &Path,
+    file_extension: &str,
+    logger: &JobLogger,
+) -> Result<PathBuf> {
+    logger.log("info", format!("Running mongodump for {} in container {container}", cfg.name));
+    let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+    let out = docker_exec::run(cfg, container, "mongodump", dump_command(cfg)?, None, Some(&file_path), logger).await?;
+    logger.log("info", format!("MongoDB backup completed for {} ({} bytes)", cfg.name, out.stdout_bytes));
+    Ok(file_path)
+}
+
+pub async fn restore(cfg: &DatabaseConfig, container: &str, restore_file: &Path, logger: &JobLogger) -> Result<()> {
+    logger.log("debug", format!("Starting MongoDB restore for database {} in container {container}", cfg.name));
+
+    let dry = restore_command(cfg, None)?;
+    let dry_run = docker_exec::run(cfg, container, "mongorestore --dryRun", dry, Some(restore_file), None, logger).await?;
+    let source_db = extract_db_name(&dry_run.output).unwrap_or_else(|| {
+        logger.log("info", format!("Could not detect source database from archive, falling back to configured database: {}", cfg.database));
+        cfg.database.clone()
+    });
+    logger.log("info", format!("Using source database in archive: {}", source_db));
+
+    let command = restore_command(cfg, Some(&source_db))?;
+    docker_exec::run(cfg, container, "mongorestore", command, Some(restore_file), None, logger).await?;
+    logger.log("info", format!("MongoDB restore completed for {}", cfg.name));
+    Ok(())
+}
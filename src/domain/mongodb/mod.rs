@@ -1,5 +1,6 @@
 mod backup;
 mod connection;
 pub mod database;
+pub(crate) mod exec;
 mod ping;
 mod restore;
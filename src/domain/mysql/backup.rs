@@ -1,6 +1,7 @@
 use crate::domain::mysql::connection::server_version;
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
+use crate::utils::stream::{DumpStream, command_stream};
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -8,6 +9,45 @@ use std::process::Command;
 use std::sync::Arc;
 use std::time::Instant;
 
+fn dump_command(cfg: &DatabaseConfig, env: HashMap<String, String>) -> Command {
+    let mut cmd = Command::new("mysqldump");
+    cmd.arg("--host").arg(&cfg.host)
+        .arg("--port").arg(cfg.port.to_string())
+        .arg("--user").arg(&cfg.username)
+        .arg("--routines")
+        .arg("--events")
+        .arg("--triggers")
+        .arg("--verbose")
+        .arg("--single-transaction")
+        .arg("--set-gtid-purged=OFF")
+        .arg("--no-tablespaces")
+        .arg("--quick")
+        .arg("--skip-lock-tables")
+        .arg("--skip-add-drop-table")
+        .arg("--no-create-db")
+        .arg("--default-character-set=utf8mb4")
+        .arg("--network-timeout")
+        .arg(format!("--max-allowed-packet={}", cfg.max_packet_size))
+        .arg(&cfg.database)
+        .envs(env);
+    cmd
+}
+
+/// `mysqldump` writing to stdout, for streaming backups.
+pub async fn stream(
+    cfg: &DatabaseConfig,
+    env: HashMap<String, String>,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<DumpStream> {
+    logger.log("info", format!("Running mysqldump for {} (streaming)", cfg.name));
+    let cmd = tokio::process::Command::from(dump_command(cfg, env));
+    Ok(DumpStream {
+        file_name: format!("{}{}", cfg.generated_id, file_extension),
+        stream: command_stream(cmd, "mysqldump".to_string(), logger)?,
+    })
+}
+
 pub async fn run(
     cfg: DatabaseConfig,
     backup_dir: PathBuf,
@@ -38,27 +78,8 @@ pub async fn run(
         logger.log("info", format!("Running mysqldump for {}", cfg.name));
 
         let start = Instant::now();
-        let output = Command::new("mysqldump")
-            .arg("--host").arg(&cfg.host)
-            .arg("--port").arg(cfg.port.to_string())
-            .arg("--user").arg(&cfg.username)
-            .arg("--routines")
-            .arg("--events")
-            .arg("--triggers")
-            .arg("--verbose")
-            .arg("--single-transaction")
-            .arg("--set-gtid-purged=OFF")
-            .arg("--no-tablespaces")
-            .arg("--quick")
-            .arg("--skip-lock-tables")
-            .arg("--skip-add-drop-table")
-            .arg("--no-create-db")
-            .arg("--default-character-set=utf8mb4")
-            .arg("--network-timeout")
-            .arg(format!("--max-allowed-packet={}", cfg.max_packet_size))
-            .arg(&cfg.database)
+        let output = dump_command(&cfg, env)
             .arg("-r").arg(&file_path)
-            .envs(env)
             .output()
             .with_context(|| format!("Failed to run mysqldump for {}", cfg.name))?;
         let duration_ms = start.elapsed().as_millis() as f64;
@@ -29,3 +29,32 @@ pub async fn server_version(cfg: &DatabaseConfig) -> Result<String> {
 
     Ok(version)
 }
+
+/// Table data bytes of the database, from `information_schema`. Indexes
+/// are left out, as a dump only holds their definitions.
+pub async fn data_size(cfg: &DatabaseConfig) -> Result<u64> {
+    let sql = format!(
+        "SELECT COALESCE(SUM(data_length), 0) FROM information_schema.tables WHERE table_schema = '{}';",
+        cfg.database.replace('\'', "''")
+    );
+    let output = Command::new("mysql")
+        .arg("--host")
+        .arg(&cfg.host)
+        .arg("--port")
+        .arg(cfg.port.to_string())
+        .arg("--user")
+        .arg(&cfg.username)
+        .arg("--skip-column-names")
+        .arg("-e")
+        .arg(sql)
+        .env("MYSQL_PWD", &cfg.password)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Size query failed: {}", stderr);
+    }
+
+    let size = String::from_utf8_lossy(&output.stdout).trim().parse::<u64>()?;
+    Ok(size)
+}
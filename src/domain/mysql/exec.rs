@@ -0,0 +1,129 @@
+use crate::domain::docker_exec::{self, ExecCommand};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Client names differ between the two images: MariaDB 11 no longer ships the
+/// `mysql*` aliases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flavor {
+    Mysql,
+    Mariadb,
+}
+
+impl Flavor {
+    fn dump(self) -> &'static str {
+        match self {
+            Self::Mysql => "mysqldump",
+            Self::Mariadb => "mariadb-dump",
+        }
+    }
+
+    fn client(self) -> &'static str {
+        match self {
+            Self::Mysql => "mysql",
+            Self::Mariadb => "mariadb",
+        }
+    }
+
+    fn admin(self) -> &'static str {
+        match self {
+            Self::Mysql => "mysqladmin",
+            Self::Mariadb => "mariadb-admin",
+        }
+    }
+}
+
+/// Clients in the container connect over its local socket; the password goes
+/// through `MYSQL_PWD`.
+fn command<'a>(cfg: &DatabaseConfig, program: &str, args: impl IntoIterator<Item = &'a str>) -> ExecCommand {
+    let mut full = vec![program.to_string(), "--user".to_string(), cfg.username.clone()];
+    full.extend(args.into_iter().map(str::to_string));
+    ExecCommand::new(full).env("MYSQL_PWD", &cfg.password)
+}
+
+pub fn ping_command(cfg: &DatabaseConfig, flavor: Flavor) -> ExecCommand {
+    command(cfg, flavor.admin(), ["ping"])
+}
+
+/// Like [`ping_command`] but over TCP, which the official images only open
+/// once their initialisation server has been replaced by the real one.
+pub fn tcp_ping_command(cfg: &DatabaseConfig, flavor: Flavor) -> ExecCommand {
+    command(cfg, flavor.admin(), ["--host", "127.0.0.1", "--protocol=tcp", "ping"])
+}
+
+pub fn dump_command(cfg: &DatabaseConfig, flavor: Flavor) -> ExecCommand {
+    let max_packet = format!("--max-allowed-packet={}", cfg.max_packet_size);
+    let mut args = vec![
+        "--routines",
+        "--events",
+        "--triggers",
+        "--single-transaction",
+        "--quick",
+        "--skip-lock-tables",
+        "--no-tablespaces",
+        "--no-create-db",
+        "--skip-add-drop-table",
+        "--default-character-set=utf8mb4",
+        "--verbose",
+        max_packet.as_str(),
+    ];
+    if flavor == Flavor::Mysql {
+        args.push("--set-gtid-purged=OFF");
+    }
+    args.push(cfg.database.as_str());
+    command(cfg, flavor.dump(), args)
+}
+
+/// Client running `sql` against the database, one tab-separated row per line.
+pub fn query_command(cfg: &DatabaseConfig, flavor: Flavor, sql: &str) -> ExecCommand {
+    command(cfg, flavor.client(), ["--batch", "--skip-column-names", "--database", cfg.database.as_str(), "-e", sql])
+}
+
+pub fn recreate_command(cfg: &DatabaseConfig, flavor: Flavor) -> ExecCommand {
+    let name = cfg.database.replace('`', "``");
+    let sql = format!("DROP DATABASE IF EXISTS `{0}`; CREATE DATABASE `{0}`;", name);
+    command(cfg, flavor.client(), ["-e", sql.as_str()])
+}
+
+pub fn restore_command(cfg: &DatabaseConfig, flavor: Flavor) -> ExecCommand {
+    command(cfg, flavor.client(), ["--database", cfg.database.as_str()])
+}
+
+pub async fn ping(cfg: &DatabaseConfig, container: &str, flavor: Flavor) -> Result<bool> {
+    docker_exec::probe(cfg, container, ping_command(cfg, flavor)).await
+}
+
+pub async fn backup(
+    cfg: &DatabaseConfig,
+    container: &str,
+    flavor: Flavor,
+    backup_dir: &Path,
+    file_extension: &str,
+    logger: &JobLogger,
+) -> Result<PathBuf> {
+    logger.log("info", format!("Running {} for {} in container {container}", flavor.dump(), cfg.name));
+    let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+    let out = docker_exec::run(cfg, container, flavor.dump(), dump_command(cfg, flavor), None, Some(&file_path), logger)
+        .await?;
+    logger.log("info", format!("{} completed for {} ({} bytes)", flavor.dump(), cfg.name, out.stdout_bytes));
+    Ok(file_path)
+}
+
+pub async fn restore(
+    cfg: &DatabaseConfig,
+    container: &str,
+    flavor: Flavor,
+    restore_file: &Path,
+    logger: &JobLogger,
+) -> Result<()> {
+    logger.log("info", format!("Starting restore for database {} in container {container}", cfg.name));
+    docker_exec::run(cfg, container, flavor.client(), recreate_command(cfg, flavor), None, None, logger).await?;
+    logger.log("info", format!("Database {} dropped and recreated", cfg.name));
+
+    docker_exec::run(cfg, container, flavor.client(), restore_command(cfg, flavor), Some(restore_file), None, logger)
+        .await?;
+    logger.log("info", format!("Restore finished successfully for database {}", cfg.name));
+    Ok(())
+}
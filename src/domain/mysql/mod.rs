@@ -1,5 +1,6 @@
 pub mod backup;
-mod connection;
+pub(crate) mod connection;
 pub mod database;
+pub mod exec;
 mod ping;
 mod restore;
@@ -0,0 +1,142 @@
+use super::connection::{HELPER_WORKDIR, database_name, dump_script, is_online, online_backup_script};
+use super::helper::{self, Attach, HelperRun};
+use crate::domain::docker_volume::docker::{remove_helper, start_container, stop_container};
+use crate::domain::docker_volume::endpoint::connect;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use bollard::Docker;
+use bollard::query_parameters::DownloadFromContainerOptions;
+use futures_util::StreamExt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub async fn run(
+    cfg: DatabaseConfig,
+    backup_dir: PathBuf,
+    file_extension: &'static str,
+    logger: Arc<JobLogger>,
+) -> Result<PathBuf> {
+    tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+        futures::executor::block_on(async move {
+            let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
+            let docker = connect(&cfg).await?;
+            let image = helper::resolve_image(&docker, &cfg).await?;
+            logger.log("debug", format!("Helper image: {image}"));
+
+            if is_online(&cfg) {
+                online(&docker, &image, &cfg, &file_path, &logger).await?;
+            } else {
+                offline(&docker, &image, &cfg, &file_path, &logger).await?;
+            }
+            logger.log("info", format!("Neo4j backup completed for {}", cfg.name));
+            Ok(file_path)
+        })
+    })
+    .await?
+}
+
+/// Stops the server, dumps the database from its volume with a helper
+/// running the server's own image, and restarts the server.
+async fn offline(
+    docker: &Docker,
+    image: &str,
+    cfg: &DatabaseConfig,
+    file_path: &Path,
+    logger: &JobLogger,
+) -> Result<()> {
+    let db = database_name(cfg)?;
+    logger.log("info", format!("Starting Neo4j dump of {} for {}", db, cfg.name));
+
+    if let Some(name) = &cfg.container_name {
+        logger.log("info", format!("Stopping container {name} for consistent dump"));
+        stop_container(docker, name).await?;
+    }
+
+    let result = async {
+        let start = Instant::now();
+        let run = helper::run(docker, image, cfg, Attach::Volume, dump_script(db), None).await?;
+        let command = format!("neo4j-admin database dump {db}");
+        save_workdir(docker, run, command, start, file_path, logger).await
+    }
+    .await;
+
+    if let Some(name) = &cfg.container_name
+        && let Err(e) = start_container(docker, name).await
+    {
+        logger.log("error", format!("Failed to restart container {name}: {e}"));
+    }
+
+    result
+}
+
+/// Enterprise online backup through the backup port (`host:port`), run in a
+/// helper from the server's image so `neo4j-admin` matches its version.
+async fn online(
+    docker: &Docker,
+    image: &str,
+    cfg: &DatabaseConfig,
+    file_path: &Path,
+    logger: &JobLogger,
+) -> Result<()> {
+    let db = database_name(cfg)?;
+    if cfg.host.is_empty() || cfg.port == 0 {
+        anyhow::bail!("Neo4j online mode requires host and port of the backup server");
+    }
+    logger.log("info", format!("Starting Neo4j online backup of {} for {}", db, cfg.name));
+
+    let start = Instant::now();
+    let run = helper::run(docker, image, cfg, Attach::Network, online_backup_script(cfg, db)?, None).await?;
+    let command = format!("neo4j-admin database backup --from={}:{} {db}", cfg.host, cfg.port);
+    save_workdir(docker, run, command, start, file_path, logger).await
+}
+
+/// Logs the helper's run and, when it succeeded, saves its work directory
+/// as a tar at `file_path`. Removes the helper either way.
+async fn save_workdir(
+    docker: &Docker,
+    run: HelperRun,
+    command: String,
+    start: Instant,
+    file_path: &Path,
+    logger: &JobLogger,
+) -> Result<()> {
+    let duration_ms = start.elapsed().as_millis() as f64;
+    if run.exit_code != 0 {
+        let message = format!("{command} failed: {}", run.output);
+        logger.log_command(command, Some(run.output), Some(run.exit_code as i32), Some(duration_ms));
+        remove_helper(docker, &run.id).await;
+        anyhow::bail!(message);
+    }
+    logger.log_command(
+        command,
+        if run.output.is_empty() { None } else { Some(run.output) },
+        Some(0),
+        Some(duration_ms),
+    );
+
+    // The archive endpoint already returns a tar of the work directory;
+    // compression is left to the backup pipeline.
+    let mut out = std::fs::File::create(file_path)
+        .with_context(|| format!("Failed to create backup file {}", file_path.display()))?;
+    let opts = DownloadFromContainerOptions { path: HELPER_WORKDIR.to_string() };
+    let mut stream = docker.download_from_container(&run.id, Some(opts));
+    let mut failure = None;
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(bytes) => out.write_all(&bytes)?,
+            Err(e) => {
+                failure = Some(e);
+                break;
+            }
+        }
+    }
+    remove_helper(docker, &run.id).await;
+    if let Some(e) = failure {
+        return Err(e).context("Error streaming Neo4j backup from helper");
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::services::config::DatabaseConfig;
+use std::path::{Path, PathBuf};
+
+/// Directory inside the helper container where dumps are written to and
+/// loaded from. Kept outside the mounted volume so it never lands in the store.
+pub const HELPER_WORKDIR: &str = "/tmp/portabase-neo4j";
+const HELPER_CONF: &str = "/tmp/portabase-neo4j.conf";
+
+fn option_str<'a>(cfg: &'a DatabaseConfig, key: &str) -> Option<&'a str> {
+    cfg.options
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// `options.mode = "online"` uses `neo4j-admin database backup` against a
+/// running Enterprise server instead of an offline dump of the data volume.
+pub fn is_online(cfg: &DatabaseConfig) -> bool {
+    option_str(cfg, "mode") == Some("online")
+}
+
+pub fn helper_image(cfg: &DatabaseConfig) -> Option<&str> {
+    option_str(cfg, "image")
+}
+
+/// Neo4j database names are restricted to ASCII letters, digits, dots and
+/// dashes, which also keeps them safe to splice into the helper script.
+pub fn database_name(cfg: &DatabaseConfig) -> anyhow::Result<&str> {
+    let name = if cfg.database.is_empty() { "neo4j" } else { cfg.database.as_str() };
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    {
+        anyhow::bail!("Invalid Neo4j database name: {}", name);
+    }
+    Ok(name)
+}
+
+/// Points `neo4j-admin` at the data volume, which the helper mounts at `/vol`
+/// rather than the image's default `/data`.
+fn admin_prelude() -> String {
+    format!(
+        "set -e; mkdir -p {HELPER_WORKDIR}; printf 'server.directories.data=/vol\\n' > {HELPER_CONF}; "
+    )
+}
+
+pub fn dump_script(db: &str) -> String {
+    format!(
+        "{}neo4j-admin database dump {db} --to-path={HELPER_WORKDIR} --additional-config={HELPER_CONF}",
+        admin_prelude()
+    )
+}
+
+/// Loads `<db>.dump` (or restores a `.backup` artifact) and hands the
+/// restored files back to whoever owns the volume root, usually the neo4j user.
+pub fn restore_script(db: &str, artifact: &str) -> String {
+    let cmd = if artifact.ends_with(".backup") {
+        format!(
+            "neo4j-admin database restore --from-path={HELPER_WORKDIR}/{artifact} --overwrite-destination=true --additional-config={HELPER_CONF} {db}"
+        )
+    } else {
+        format!(
+            "neo4j-admin database load {db} --from-path={HELPER_WORKDIR} --overwrite-destination=true --additional-config={HELPER_CONF}"
+        )
+    };
+    format!("{}{cmd}; chown -R \"$(stat -c %u:%g /vol)\" /vol", admin_prelude())
+}
+
+/// Backs up `db` from the server's backup port into the work directory.
+/// The host is spliced into the script, so it is checked like a hostname.
+pub fn online_backup_script(cfg: &DatabaseConfig, db: &str) -> anyhow::Result<String> {
+    if cfg.host.is_empty()
+        || !cfg
+            .host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':' | '[' | ']'))
+    {
+        anyhow::bail!("Invalid Neo4j backup host: {}", cfg.host);
+    }
+    Ok(format!(
+        "set -e; mkdir -p {HELPER_WORKDIR}; neo4j-admin database backup --from={}:{} --to-path={HELPER_WORKDIR} {db}",
+        cfg.host, cfg.port
+    ))
+}
+
+/// Finds the `.dump` or `.backup` artifact inside an unpacked archive.
+pub fn find_artifact(root: &Path) -> Option<PathBuf> {
+    let entries: Vec<PathBuf> = std::fs::read_dir(root)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .collect();
+    entries
+        .iter()
+        .find(|p| {
+            p.is_file()
+                && p.extension()
+                    .is_some_and(|ext| ext == "dump" || ext == "backup")
+        })
+        .cloned()
+        .or_else(|| {
+            entries
+                .into_iter()
+                .filter(|p| p.is_dir())
+                .find_map(|p| find_artifact(&p))
+        })
+}
@@ -0,0 +1,109 @@
+use super::connection::helper_image;
+use crate::domain::docker_volume::docker::{create_helper, create_network_helper, remove_helper};
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use bollard::Docker;
+use bollard::query_parameters::{InspectContainerOptions, LogsOptions, UploadToContainerOptions};
+use futures_util::StreamExt;
+use std::path::Path;
+
+/// The helper has to ship `neo4j-admin` in the same version as the server,
+/// so it uses `options.image` or the image of the configured container.
+pub async fn resolve_image(docker: &Docker, cfg: &DatabaseConfig) -> Result<String> {
+    if let Some(image) = helper_image(cfg) {
+        return Ok(image.to_string());
+    }
+    let name = cfg
+        .container_name
+        .as_deref()
+        .context("Neo4j requires options.image or container_name to pick a helper image")?;
+    let info = docker
+        .inspect_container(name, None::<InspectContainerOptions>)
+        .await
+        .with_context(|| format!("Failed to inspect container {name}"))?;
+    info.config
+        .and_then(|c| c.image)
+        .with_context(|| format!("Container {name} has no image reference"))
+}
+
+/// What the helper works on.
+pub enum Attach {
+    /// The data volume, mounted read-write at `/vol`: `neo4j-admin` takes the
+    /// store lock files even to dump.
+    Volume,
+    /// No volume, on the network of the configured container, if any, to
+    /// reach the server's backup port.
+    Network,
+}
+
+pub struct HelperRun {
+    pub id: String,
+    pub exit_code: i64,
+    pub output: String,
+}
+
+/// Runs `script` in a helper container attached as `attach` says. `upload`
+/// is a tar stream extracted under `/tmp` before the helper starts. The
+/// container is left in place for the caller to download from and remove.
+pub async fn run(
+    docker: &Docker,
+    image: &str,
+    cfg: &DatabaseConfig,
+    attach: Attach,
+    script: String,
+    upload: Option<&Path>,
+) -> Result<HelperRun> {
+    let cmd = Some(vec!["sh".into(), "-c".into(), script]);
+    let helper = match attach {
+        Attach::Volume => {
+            create_helper(docker, image, &cfg.volume_name, &cfg.generated_id, false, cmd).await?
+        }
+        Attach::Network => {
+            create_network_helper(docker, image, cfg.container_name.as_deref(), &cfg.generated_id, cmd).await?
+        }
+    };
+
+    let result = async {
+        if let Some(tar_path) = upload {
+            let file = tokio::fs::File::open(tar_path)
+                .await
+                .with_context(|| format!("Failed to open {}", tar_path.display()))?;
+            let stream = tokio_util::io::ReaderStream::new(file);
+            let opts = UploadToContainerOptions { path: "/tmp".to_string(), ..Default::default() };
+            docker
+                .upload_to_container(&helper.id, Some(opts), bollard::body_try_stream(stream))
+                .await
+                .context("Failed to upload Neo4j artifact to helper")?;
+        }
+
+        docker
+            .start_container(&helper.id, None::<bollard::query_parameters::StartContainerOptions>)
+            .await
+            .context("Failed to start Neo4j helper")?;
+
+        let mut wait = docker.wait_container(&helper.id, None::<bollard::query_parameters::WaitContainerOptions>);
+        let exit_code = match wait.next().await {
+            Some(Ok(res)) => res.status_code,
+            Some(Err(bollard::errors::Error::DockerContainerWaitError { code, .. })) => code,
+            Some(Err(e)) => return Err(e).context("Failed waiting for Neo4j helper"),
+            None => -1,
+        };
+
+        let mut logs = docker.logs(
+            &helper.id,
+            Some(LogsOptions { stdout: true, stderr: true, ..Default::default() }),
+        );
+        let mut output = String::new();
+        while let Some(Ok(line)) = logs.next().await {
+            output.push_str(&line.to_string());
+        }
+
+        anyhow::Ok(HelperRun { id: helper.id.clone(), exit_code, output: output.trim().to_string() })
+    }
+    .await;
+
+    if result.is_err() {
+        remove_helper(docker, &helper.id).await;
+    }
+    result
+}
@@ -0,0 +1,6 @@
+mod backup;
+pub mod connection;
+pub mod database;
+mod helper;
+mod ping;
+mod restore;
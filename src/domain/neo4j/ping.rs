@@ -0,0 +1,25 @@
+use super::connection::is_online;
+use crate::domain::docker_volume;
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+use tokio::net::TcpStream;
+use tokio::time::{Duration, timeout};
+use tracing::info;
+
+pub async fn run(cfg: DatabaseConfig) -> Result<bool> {
+    if !is_online(&cfg) {
+        return docker_volume::ping::run(cfg).await;
+    }
+
+    match timeout(Duration::from_secs(10), TcpStream::connect((cfg.host.as_str(), cfg.port))).await {
+        Ok(Ok(_)) => Ok(true),
+        Ok(Err(e)) => {
+            info!("Failed to reach Neo4j backup port {}:{}: {}", cfg.host, cfg.port, e);
+            Ok(false)
+        }
+        Err(_) => {
+            info!("Timeout connecting to Neo4j at {}:{}", cfg.host, cfg.port);
+            Ok(false)
+        }
+    }
+}
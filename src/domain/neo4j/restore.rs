@@ -0,0 +1,101 @@
+use super::connection::{database_name, find_artifact, restore_script};
+use super::helper::{self, Attach};
+use crate::domain::docker_volume::docker::{remove_helper, start_container, stop_container};
+use crate::domain::docker_volume::endpoint::connect;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Restores a dump (or an online `.backup`) into the data volume with the
+/// server stopped. Dumps are renamed to `<database>.dump` on upload, so the
+/// configured database name decides the restore target.
+pub async fn run(cfg: DatabaseConfig, restore_file: PathBuf, logger: Arc<JobLogger>) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        futures::executor::block_on(async move {
+            let db = database_name(&cfg)?;
+            logger.log("debug", format!("Starting Neo4j restore of {} for {}", db, cfg.name));
+
+            if cfg.volume_name.is_empty() {
+                anyhow::bail!("Neo4j restore requires volume_name, the server's data volume");
+            }
+            if !restore_file.exists() {
+                anyhow::bail!("Restore file not found: {}", restore_file.display());
+            }
+
+            let unpacked = tempfile::TempDir::new()?;
+            let tar = std::fs::File::open(&restore_file)?;
+            tar::Archive::new(tar)
+                .unpack(unpacked.path())
+                .with_context(|| format!("Failed to unpack {}", restore_file.display()))?;
+
+            let artifact = find_artifact(unpacked.path())
+                .ok_or_else(|| anyhow::anyhow!("Invalid Neo4j archive: no .dump or .backup file found"))?;
+            let artifact_name = if artifact.extension().is_some_and(|e| e == "backup") {
+                artifact.file_name().unwrap().to_string_lossy().to_string()
+            } else {
+                format!("{db}.dump")
+            };
+
+            let upload_tar = unpacked.path().join("upload.tar");
+            {
+                let mut tar = tar::Builder::new(std::fs::File::create(&upload_tar)?);
+                tar.append_path_with_name(&artifact, format!("portabase-neo4j/{artifact_name}"))?;
+                tar.finish()?;
+            }
+
+            let docker = connect(&cfg).await?;
+            let image = helper::resolve_image(&docker, &cfg).await?;
+            logger.log("debug", format!("Helper image: {image}"));
+
+            if let Some(name) = &cfg.container_name {
+                logger.log("info", format!("Stopping container {name} for restore"));
+                stop_container(&docker, name).await?;
+            }
+
+            let result = async {
+                let start = Instant::now();
+                let run = helper::run(
+                    &docker,
+                    &image,
+                    &cfg,
+                    Attach::Volume,
+                    restore_script(db, &artifact_name),
+                    Some(&upload_tar),
+                )
+                .await?;
+                let duration_ms = start.elapsed().as_millis() as f64;
+                remove_helper(&docker, &run.id).await;
+
+                let command = format!("neo4j-admin restore {db} from {artifact_name}");
+                if run.exit_code != 0 {
+                    logger.log_command(command, Some(run.output.clone()), Some(run.exit_code as i32), Some(duration_ms));
+                    logger.log("error", format!("Neo4j restore failed for {}: {}", cfg.name, run.output));
+                    anyhow::bail!("Neo4j restore failed for {}: {}", cfg.name, run.output);
+                }
+                logger.log_command(
+                    command,
+                    if run.output.is_empty() { None } else { Some(run.output) },
+                    Some(0),
+                    Some(duration_ms),
+                );
+                anyhow::Ok(())
+            }
+            .await;
+
+            if let Some(name) = &cfg.container_name
+                && let Err(e) = start_container(&docker, name).await
+            {
+                logger.log("error", format!("Failed to restart container {name}: {e}"));
+            }
+
+            if result.is_ok() {
+                logger.log("info", format!("Neo4j restore completed for {}", cfg.name));
+            }
+            result
+        })
+    })
+    .await?
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
 use std::time::Instant;
@@ -9,6 +9,36 @@ use super::connection::{select_pg_path, server_version};
 use super::format::PostgresDumpFormat;
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
+use crate::utils::compress::{Compression, GzipWriter};
+use crate::utils::stream::{DumpStream, command_stream};
+
+fn fc_command(pg_dump: &Path, cfg: &DatabaseConfig, env: HashMap<String, String>) -> Command {
+    let mut cmd = Command::new(pg_dump);
+    cmd.arg("--host").arg(&cfg.host)
+        .arg("--port").arg(cfg.port.to_string())
+        .arg("--username").arg(&cfg.username)
+        .arg("--dbname").arg(&cfg.database)
+        .arg("-Fc")
+        .arg("-v")
+        .arg("--compress=3")
+        .envs(env);
+    cmd
+}
+
+/// Custom-format dump on stdout. Directory format needs a filesystem, so
+/// streaming always uses custom format whatever the database size.
+pub async fn stream(cfg: &DatabaseConfig, env: HashMap<String, String>, logger: Arc<JobLogger>) -> Result<DumpStream> {
+    logger.log("info", format!("Starting streaming backup for database {}", cfg.name));
+    let version = server_version(cfg).await?;
+    let pg_dump = select_pg_path(&version).join("pg_dump");
+    logger.log("debug", format!("Using pg_dump at {:?}", pg_dump));
+
+    let cmd = tokio::process::Command::from(fc_command(&pg_dump, cfg, env));
+    Ok(DumpStream {
+        file_name: format!("{}.dump", cfg.generated_id),
+        stream: command_stream(cmd, "pg_dump".to_string(), logger)?,
+    })
+}
 
 pub async fn run(
     cfg: DatabaseConfig,
@@ -41,16 +71,8 @@ pub async fn run(
                 let file_path = backup_dir.join(format!("{}.dump", cfg.generated_id));
 
                 let start = Instant::now();
-                let output = Command::new(&pg_dump)
-                    .arg("--host").arg(&cfg.host)
-                    .arg("--port").arg(cfg.port.to_string())
-                    .arg("--username").arg(&cfg.username)
-                    .arg("--dbname").arg(&cfg.database)
-                    .arg("-Fc")
+                let output = fc_command(&pg_dump, &cfg, env)
                     .arg("-f").arg(&file_path)
-                    .arg("-v")
-                    .arg("--compress=3")
-                    .envs(env)
                     .output();
                 let duration_ms = start.elapsed().as_millis() as f64;
 
@@ -125,16 +147,28 @@ pub async fn run(
                     }
                 }
 
+                let compression = Compression::from_config(&cfg)?;
+                if compression.threads > 1 {
+                    logger.log("info", format!("Compressing FD archive on {} threads", compression.threads));
+                }
+
                 match std::fs::File::create(&tar_file) {
                     Ok(tar_gz) => {
-                        let enc = flate2::write::GzEncoder::new(tar_gz, flate2::Compression::default());
+                        let enc = GzipWriter::new(tar_gz, &compression);
                         let mut tar = tar::Builder::new(enc);
                         if let Err(e) = tar.append_dir_all(".", &dump_dir) {
                             logger.log("error", format!("Failed to append dump_dir to tar for {}: {:?}", cfg.name, e));
                             return Err(e.into());
                         }
-                        if let Err(e) = tar.finish() {
-                            logger.log("error", format!("Failed to finish tar archive for {}: {:?}",  cfg.name, e));
+                        let enc = match tar.into_inner() {
+                            Ok(enc) => enc,
+                            Err(e) => {
+                                logger.log("error", format!("Failed to finish tar archive for {}: {:?}",  cfg.name, e));
+                                return Err(e.into());
+                            }
+                        };
+                        if let Err(e) = enc.finish() {
+                            logger.log("error", format!("Failed to finish gzip stream for {}: {:?}", cfg.name, e));
                             return Err(e.into());
                         }
                         logger.log("info", format!("FD backup archive created at {:?}", tar_file));
@@ -64,6 +64,43 @@ pub async fn can_drop_database(cfg: &DatabaseConfig) -> Result<bool> {
     Ok(row.get(0))
 }
 
+/// TimescaleDB versions on the target: the one the server would create and
+/// the one installed in the database, if any.
+pub async fn timescaledb_versions(cfg: &DatabaseConfig) -> Result<(Option<String>, Option<String>)> {
+    let client = connect(cfg).await?;
+    let row = client
+        .query_opt(
+            "SELECT default_version, installed_version FROM pg_available_extensions WHERE name = 'timescaledb'",
+            &[],
+        )
+        .await?;
+    Ok(match row {
+        Some(r) => (r.get(0), r.get(1)),
+        None => (None, None),
+    })
+}
+
+/// Puts the database in TimescaleDB restore mode; pg_restore's own sessions
+/// pick up the database-level `timescaledb.restoring` setting.
+pub async fn timescaledb_pre_restore(cfg: &DatabaseConfig) -> Result<()> {
+    let client = connect(cfg).await?;
+    client
+        .batch_execute("CREATE EXTENSION IF NOT EXISTS timescaledb; SELECT timescaledb_pre_restore();")
+        .await?;
+    Ok(())
+}
+
+pub async fn drop_timescaledb(cfg: &DatabaseConfig) -> Result<()> {
+    let client = connect(cfg).await?;
+    client.batch_execute("DROP EXTENSION IF EXISTS timescaledb CASCADE;").await?;
+    Ok(())
+}
+
+pub async fn timescaledb_post_restore(cfg: &DatabaseConfig) -> Result<()> {
+    let client = connect(cfg).await?;
+    client.batch_execute("SELECT timescaledb_post_restore();").await?;
+    Ok(())
+}
 
 pub fn select_pg_path(version: &str) -> std::path::PathBuf {
     select_pg_path_with(version, &CONFIG.pg_bin_dir)
@@ -350,26 +387,44 @@ pub async fn recreate_public_schema(cfg: &DatabaseConfig, owner: &str) -> Result
     Ok(())
 }
 
+pub async fn database_size(cfg: &DatabaseConfig) -> Result<u64> {
+    let client = connect(cfg).await?;
+    let row = client
+        .query_one("SELECT pg_database_size(current_database());", &[])
+        .await?;
+    let size_bytes: i64 = row.get(0);
+    Ok(size_bytes.max(0) as u64)
+}
+
+/// Bytes of table data, TOAST included, in user schemas. Indexes are left
+/// out, as a dump only holds their definitions.
+pub async fn data_size(cfg: &DatabaseConfig) -> Result<u64> {
+    let client = connect(cfg).await?;
+    let row = client
+        .query_one(
+            "SELECT COALESCE(SUM(pg_table_size(c.oid)), 0)::bigint FROM pg_class c \
+             JOIN pg_namespace n ON n.oid = c.relnamespace \
+             WHERE c.relkind IN ('r', 'm') \
+             AND n.nspname NOT IN ('pg_catalog', 'information_schema') \
+             AND n.nspname NOT LIKE 'pg_toast%';",
+            &[],
+        )
+        .await?;
+    let size_bytes: i64 = row.get(0);
+    Ok(size_bytes.max(0) as u64)
+}
+
 pub async fn detect_format_from_size(cfg: &DatabaseConfig) -> PostgresDumpFormat {
     info!(
         "Detecting database format {:?} - {:?}",
         cfg.name, cfg.generated_id
     );
 
-    let client = match connect(cfg).await {
-        Ok(c) => c,
+    let size_bytes = match database_size(cfg).await {
+        Ok(size) => size,
         Err(_) => return PostgresDumpFormat::Fc,
     };
 
-    let row = match client
-        .query_one("SELECT pg_database_size(current_database());", &[])
-        .await
-    {
-        Ok(r) => r,
-        Err(_) => return PostgresDumpFormat::Fc,
-    };
-
-    let size_bytes: i64 = row.get(0);
     info!("Size of database is {} bytes", size_bytes);
 
     // > 1 Go
@@ -3,11 +3,13 @@ use async_trait::async_trait;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use super::{backup, format::PostgresDumpFormat, ping, restore};
-use crate::domain::factory::Database;
+use super::{backup, connection, exec, format::PostgresDumpFormat, ping, restore};
+use crate::domain::docker_exec::{self, Execution};
+use crate::domain::factory::{Database, DumpInfo};
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
 use crate::utils::locks::{DbOpLock, FileLock};
+use crate::utils::stream::DumpStream;
 
 pub struct PostgresDatabase {
     pub cfg: DatabaseConfig,
@@ -36,33 +38,89 @@ impl Database for PostgresDatabase {
     }
 
     async fn ping(&self) -> Result<bool> {
-        ping::run(self.cfg.clone()).await
+        match Execution::from_config(&self.cfg)? {
+            Execution::Local => ping::run(self.cfg.clone()).await,
+            Execution::Container(container) => exec::ping(&self.cfg, &container).await,
+        }
     }
 
     async fn backup(&self, dir: &Path, logger: Arc<JobLogger>) -> Result<PathBuf> {
         FileLock::acquire(&self.cfg.generated_id, DbOpLock::Backup.as_str()).await?;
-        let res = backup::run(
-            self.cfg.clone(),
-            self.format,
-            dir.to_path_buf(),
-            self.build_env(),
-            logger,
-        )
-        .await;
+        let res = match Execution::from_config(&self.cfg) {
+            Ok(Execution::Local) => {
+                backup::run(
+                    self.cfg.clone(),
+                    self.format,
+                    dir.to_path_buf(),
+                    self.build_env(),
+                    logger,
+                )
+                .await
+            }
+            Ok(Execution::Container(container)) => {
+                exec::backup(&self.cfg, &container, self.format, dir, &logger).await
+            }
+            Err(e) => Err(e),
+        };
         FileLock::release(&self.cfg.generated_id).await?;
         res
     }
 
+    async fn backup_stream(&self, logger: Arc<JobLogger>) -> Result<Option<DumpStream>> {
+        if Execution::from_config(&self.cfg)? != Execution::Local {
+            return Ok(None);
+        }
+        FileLock::acquire(&self.cfg.generated_id, DbOpLock::Backup.as_str()).await?;
+        match backup::stream(&self.cfg, self.build_env(), logger).await {
+            Ok(dump) => Ok(Some(dump)),
+            Err(e) => {
+                FileLock::release(&self.cfg.generated_id).await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn describe(&self, streamed: bool) -> DumpInfo {
+        // Streaming always dumps in custom format, see `backup::stream`.
+        let format = if streamed { PostgresDumpFormat::Fc } else { self.format };
+        let server_version = connection::server_version(&self.cfg).await.ok();
+        let program = match (Execution::from_config(&self.cfg), &server_version) {
+            (Ok(Execution::Local), Some(version)) => {
+                connection::select_pg_path(version).join("pg_dump").to_string_lossy().into_owned()
+            }
+            _ => "pg_dump".to_string(),
+        };
+        DumpInfo {
+            tool_version: docker_exec::tool_version(&self.cfg, &program).await,
+            server_version,
+            tool: Some("pg_dump".to_string()),
+            format: Some(format.as_str().to_string()),
+            ..Default::default()
+        }
+    }
+
+    async fn estimated_size(&self) -> Option<u64> {
+        connection::data_size(&self.cfg).await.ok()
+    }
+
     async fn restore(&self, file: &Path, logger: Arc<JobLogger>) -> Result<()> {
         FileLock::acquire(&self.cfg.generated_id, DbOpLock::Restore.as_str()).await?;
-        let res = restore::run(
-            self.cfg.clone(),
-            self.format,
-            file.to_path_buf(),
-            self.build_env(),
-            logger,
-        )
-        .await;
+        let res = match Execution::from_config(&self.cfg) {
+            Ok(Execution::Local) => {
+                restore::run(
+                    self.cfg.clone(),
+                    self.format,
+                    file.to_path_buf(),
+                    self.build_env(),
+                    logger,
+                )
+                .await
+            }
+            Ok(Execution::Container(container)) => {
+                exec::restore(&self.cfg, &container, self.format, file, &logger).await
+            }
+            Err(e) => Err(e),
+        };
         FileLock::release(&self.cfg.generated_id).await?;
         res
     }
@@ -0,0 +1,126 @@
+use crate::domain::docker_exec::{self, ExecCommand};
+use crate::domain::postgres::clean_mode::RestoreCleanMode;
+use crate::domain::postgres::format::PostgresDumpFormat;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Dumps a directory-format archive to a temporary directory in the container
+/// and streams it back as `.tar.gz`, the layout local FD backups produce.
+const FD_DUMP_SCRIPT: &str = r#"set -e
+d=$(mktemp -d)
+trap 'rm -rf "$d"' EXIT
+pg_dump -Fd -j 4 -v -f "$d/dump" >&2
+tar -czf - -C "$d/dump" ."#;
+
+/// Unpacks a streamed `.tar.gz` FD archive and runs `pg_restore "$@"` on it.
+const FD_RESTORE_SCRIPT: &str = r#"set -e
+d=$(mktemp -d)
+trap 'rm -rf "$d"' EXIT
+tar -xzf - -C "$d"
+pg_restore "$@" "$d""#;
+
+/// Clients in the container connect over its local socket, so only the
+/// credentials are passed; `host` and `port` are not used.
+fn with_credentials(cmd: ExecCommand, cfg: &DatabaseConfig) -> ExecCommand {
+    cmd.env("PGUSER", &cfg.username)
+        .env("PGDATABASE", &cfg.database)
+        .env("PGPASSWORD", &cfg.password)
+}
+
+pub fn ping_command(cfg: &DatabaseConfig) -> ExecCommand {
+    with_credentials(ExecCommand::new(["pg_isready", "-q"]), cfg)
+}
+
+/// Like [`ping_command`] but over TCP, which the official image only opens
+/// once its initialisation server has been replaced by the real one.
+pub fn tcp_ping_command(cfg: &DatabaseConfig) -> ExecCommand {
+    with_credentials(ExecCommand::new(["pg_isready", "-q", "-h", "127.0.0.1"]), cfg)
+}
+
+/// `psql` running `sql` with unaligned, tuples-only output.
+pub fn query_command(cfg: &DatabaseConfig, sql: &str) -> ExecCommand {
+    with_credentials(
+        ExecCommand::new(["psql", "-X", "-A", "-t", "-v", "ON_ERROR_STOP=1", "-c", sql]),
+        cfg,
+    )
+}
+
+pub fn dump_command(cfg: &DatabaseConfig, format: PostgresDumpFormat) -> ExecCommand {
+    let cmd = match format {
+        PostgresDumpFormat::Fc => ExecCommand::new(["pg_dump", "-Fc", "-v", "--compress=3"]),
+        PostgresDumpFormat::Fd => ExecCommand::shell(FD_DUMP_SCRIPT, Vec::<String>::new()),
+    };
+    with_credentials(cmd, cfg)
+}
+
+/// `pg_restore` reading the archive from stdin. Clean modes that drop objects
+/// through a SQL connection are not available here.
+pub fn restore_command(cfg: &DatabaseConfig, format: PostgresDumpFormat) -> Result<ExecCommand> {
+    let (mode, _) = RestoreCleanMode::from_config(cfg);
+    let unsupported = match mode {
+        RestoreCleanMode::DropSchemas => Some("drop_schemas"),
+        RestoreCleanMode::DropDatabase => Some("drop_database"),
+        RestoreCleanMode::Clean | RestoreCleanMode::None => None,
+    };
+    if let Some(name) = unsupported {
+        anyhow::bail!("clean_mode={name} is not supported with execution 'docker_exec', use clean or none");
+    }
+    let keep_ownership = cfg.options.get("keep_ownership").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let mut args = Vec::new();
+    if !keep_ownership {
+        args.extend(["--no-owner", "--no-privileges"]);
+    }
+    if mode.uses_pg_restore_clean() {
+        args.extend(["--clean", "--if-exists"]);
+    }
+    args.push("-v");
+    args.extend(["--dbname", cfg.database.as_str()]);
+
+    let cmd = match format {
+        PostgresDumpFormat::Fc => ExecCommand::new(std::iter::once("pg_restore").chain(args)),
+        PostgresDumpFormat::Fd => {
+            args.extend(["-j", "4"]);
+            ExecCommand::shell(FD_RESTORE_SCRIPT, args)
+        }
+    };
+    Ok(with_credentials(cmd, cfg))
+}
+
+pub async fn ping(cfg: &DatabaseConfig, container: &str) -> Result<bool> {
+    docker_exec::probe(cfg, container, ping_command(cfg)).await
+}
+
+pub async fn backup(
+    cfg: &DatabaseConfig,
+    container: &str,
+    format: PostgresDumpFormat,
+    backup_dir: &Path,
+    logger: &JobLogger,
+) -> Result<PathBuf> {
+    logger.log("info", format!("Starting backup for database {} in container {container}", cfg.name));
+    let file_path = match format {
+        PostgresDumpFormat::Fc => backup_dir.join(format!("{}.dump", cfg.generated_id)),
+        PostgresDumpFormat::Fd => backup_dir.join(format!("{}.tar.gz", cfg.generated_id)),
+    };
+    let out = docker_exec::run(cfg, container, "pg_dump", dump_command(cfg, format), None, Some(&file_path), logger)
+        .await?;
+    logger.log("info", format!("Backup finished for database {} ({} bytes)", cfg.name, out.stdout_bytes));
+    Ok(file_path)
+}
+
+pub async fn restore(
+    cfg: &DatabaseConfig,
+    container: &str,
+    format: PostgresDumpFormat,
+    restore_file: &Path,
+    logger: &JobLogger,
+) -> Result<()> {
+    logger.log("info", format!("Starting restore for database {} in container {container}", cfg.name));
+    let command = restore_command(cfg, format)?;
+    docker_exec::run(cfg, container, "pg_restore", command, Some(restore_file), None, logger).await?;
+    logger.log("info", format!("Restore finished for database {}", cfg.name));
+    Ok(())
+}
@@ -3,3 +3,21 @@ pub enum PostgresDumpFormat {
     Fc,
     Fd,
 }
+
+impl PostgresDumpFormat {
+    /// Name recorded in backup manifests, after `pg_dump --format`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PostgresDumpFormat::Fc => "custom",
+            PostgresDumpFormat::Fd => "directory",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "custom" => Some(PostgresDumpFormat::Fc),
+            "directory" => Some(PostgresDumpFormat::Fd),
+            _ => None,
+        }
+    }
+}
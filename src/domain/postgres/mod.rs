@@ -3,6 +3,7 @@ pub(crate) mod cluster;
 pub(crate) mod clean_mode;
 pub(crate) mod connection;
 pub mod database;
+pub(crate) mod exec;
 pub(crate) mod format;
 mod ping;
 pub(crate) mod restore;
@@ -1,9 +1,11 @@
 mod command;
 mod prepare;
 mod run;
+mod timescale;
 mod toc;
 
 pub use run::run;
 pub(crate) use command::run_pg_restore;
 pub(crate) use prepare::prepare_archive;
-pub(crate) use toc::toc_creates_public_schema;
+pub(crate) use timescale::check_compat as check_timescaledb_compat;
+pub(crate) use toc::{toc_creates_public_schema, toc_has_extension};
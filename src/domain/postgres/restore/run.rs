@@ -4,11 +4,15 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::sync::Arc;
 
-use super::{prepare_archive, run_pg_restore, toc_creates_public_schema};
+use super::{
+    check_timescaledb_compat, prepare_archive, run_pg_restore, toc_creates_public_schema,
+    toc_has_extension,
+};
 use crate::domain::postgres::clean_mode::RestoreCleanMode;
 use crate::domain::postgres::connection::{
     can_drop_database, drop_all_schemas, drop_and_recreate_database, pg_restore_binary_name,
     recreate_public_schema, select_pg_path, server_version, terminate_connections,
+    drop_timescaledb, timescaledb_post_restore, timescaledb_pre_restore, timescaledb_versions,
 };
 use crate::domain::postgres::format::PostgresDumpFormat;
 use crate::services::backup::logger::JobLogger;
@@ -58,10 +62,23 @@ pub async fn run(
 
         let prepared = prepare_archive(format, &restore_file, &pg_restore, &logger)?;
 
+        // Checked before anything is dropped so an incompatible target is left untouched.
+        let (ts_available, ts_installed) = handle.block_on(timescaledb_versions(&cfg))?;
+        let timescale = toc_has_extension(prepared.toc(), "timescaledb") || ts_installed.is_some();
+        if timescale {
+            let expected = cfg.options.get("timescaledb_version").and_then(|v| v.as_str());
+            let ts_version = check_timescaledb_compat(ts_available.as_deref(), ts_installed.as_deref(), expected)?;
+            logger.log("info", format!("TimescaleDB {} detected, restoring in timescaledb restore mode", ts_version));
+        }
+
         match mode {
             RestoreCleanMode::DropSchemas => {
                 handle.block_on(terminate_connections(&cfg))?;
                 let owner = cfg.username.clone();
+                if timescale {
+                    // Its catalog schemas are extension members and cannot be dropped on their own.
+                    handle.block_on(drop_timescaledb(&cfg))?;
+                }
                 let dropped = handle.block_on(drop_all_schemas(&cfg))?;
                 logger.log("warn", format!("clean_mode=drop_schemas dropped schemas {:?} in {}", dropped, cfg.database));
                 if !toc_creates_public_schema(prepared.toc()) {
@@ -100,7 +117,26 @@ pub async fn run(
         }
         cmd.arg(prepared.path()).envs(env);
 
-        run_pg_restore(cmd, &logger, &cfg)?;
+        if timescale {
+            handle.block_on(timescaledb_pre_restore(&cfg))?;
+            logger.log("debug", "timescaledb_pre_restore() done");
+        }
+
+        let restored = run_pg_restore(cmd, &logger, &cfg);
+
+        // Always leave restore mode, even after a failed pg_restore, or the
+        // database keeps background workers disabled.
+        if timescale {
+            match handle.block_on(timescaledb_post_restore(&cfg)) {
+                Ok(()) => logger.log("debug", "timescaledb_post_restore() done"),
+                Err(e) => {
+                    logger.log("error", format!("timescaledb_post_restore() failed for {}: {}", cfg.name, e));
+                    restored?;
+                    return Err(e);
+                }
+            }
+        }
+        restored?;
         logger.log("info", format!("Restore finished for database {}", cfg.name));
         Ok(())
     })
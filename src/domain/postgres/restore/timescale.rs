@@ -0,0 +1,40 @@
+use anyhow::Result;
+
+/// Decides whether the target can take a TimescaleDB restore, returning the
+/// version that will be in place. `available` is the version the target would
+/// create, `installed` the one already in the database, `expected` the
+/// version pinned in `options.timescaledb_version` (the source's version).
+pub(crate) fn check_compat(
+    available: Option<&str>,
+    installed: Option<&str>,
+    expected: Option<&str>,
+) -> Result<String> {
+    let Some(available) = available else {
+        anyhow::bail!("Archive uses TimescaleDB but the extension is not available on the target server");
+    };
+
+    // An existing install must already be at the packaged version, otherwise
+    // the restored catalog and the loaded library disagree.
+    if let Some(installed) = installed
+        && installed != available
+    {
+        anyhow::bail!(
+            "TimescaleDB {} is installed in the target database but the server ships {}; run ALTER EXTENSION timescaledb UPDATE first",
+            installed,
+            available
+        );
+    }
+
+    let effective = installed.unwrap_or(available);
+    if let Some(expected) = expected
+        && expected != effective
+    {
+        anyhow::bail!(
+            "TimescaleDB version mismatch: backup was taken with {}, target has {}",
+            expected,
+            effective
+        );
+    }
+
+    Ok(effective.to_string())
+}
@@ -6,3 +6,14 @@ pub(crate) fn toc_creates_public_schema(toc: &str) -> bool {
             == Some("public")
     })
 }
+
+/// Whether the archive's TOC (`pg_restore -l`) creates extension `name`.
+pub(crate) fn toc_has_extension(toc: &str, name: &str) -> bool {
+    toc.lines().any(|l| {
+        !l.starts_with(';')
+            && l.split(" EXTENSION - ")
+                .nth(1)
+                .and_then(|rest| rest.split_whitespace().next())
+                == Some(name)
+    })
+}
@@ -1,7 +1,13 @@
+use super::connection::{
+    Mode, Node, auth_env, connection_args, parse_cluster_nodes, parse_cluster_shards,
+    parse_sentinel_master, pick_sentinel_replica, sentinel_auth_env, sentinel_connection_args,
+};
+use super::info::engine_version;
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use serde_json::json;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
 use std::time::Instant;
@@ -20,77 +26,182 @@ pub async fn run(
 
         let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
 
-        let mut cmd = Command::new("redis-cli");
-        cmd.arg("-h")
-            .arg(&cfg.host)
-            .arg("-p")
-            .arg(cfg.port.to_string());
-
-        if !cfg.username.is_empty() {
-            cmd.arg("--user").arg(&cfg.username);
-        }
-        if !cfg.password.is_empty() {
-            cmd.arg("-a").arg(&cfg.password);
-        }
-        cmd.arg("--rdb").arg(&file_path);
-
-        logger.log("info", format!("Running redis-cli --rdb for {}", cfg.name));
-
-        let start = Instant::now();
-        let output = cmd.output().context("Redis backup command failed")?;
-        let duration_ms = start.elapsed().as_millis() as f64;
-        let exit_code = output.status.code().unwrap_or(-1);
-
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-
-        if !output.status.success() {
-            if stderr.contains("NOAUTH") {
+        match Mode::from_config(&cfg)? {
+            Mode::Standalone => dump_node(&cfg, &cfg.host, cfg.port, &file_path, &logger)?,
+            Mode::Sentinel { master, replica } => {
+                let (host, port) = resolve_sentinel(&cfg, &master, replica, &logger)?;
                 logger.log(
-                    "error",
+                    "info",
                     format!(
-                        "Redis backup failed for {}: Authentication required (NOAUTH)",
-                        cfg.name
+                        "Sentinel resolved {} {} to {}:{}",
+                        master,
+                        if replica { "replica" } else { "master" },
+                        host,
+                        port
                     ),
                 );
-                logger.log_command(
-                    "redis-cli",
-                    Some("Authentication required (NOAUTH)".into()),
-                    Some(exit_code),
-                    Some(duration_ms),
-                );
-                anyhow::bail!(
-                    "Redis backup failed for {}: Authentication required",
-                    cfg.name
-                );
-            } else {
-                logger.log(
-                    "error",
-                    format!("Redis backup failed for {}: {}", cfg.name, stderr),
-                );
-                logger.log_command(
-                    "redis-cli",
-                    Some(stderr.to_string()),
-                    Some(exit_code),
-                    Some(duration_ms),
-                );
-                anyhow::bail!("Redis backup failed for {}: {}", cfg.name, stderr);
+                dump_node(&cfg, &host, port, &file_path, &logger)?;
             }
+            Mode::Cluster => backup_cluster(&cfg, &backup_dir, &file_path, &logger)?,
         }
 
-        logger.log_command(
-            "redis-cli",
-            if stdout.is_empty() {
-                None
-            } else {
-                Some(stdout.to_string())
-            },
-            Some(0),
-            Some(duration_ms),
-        );
         logger.log("info", format!("Redis backup completed for {}", cfg.name));
 
         Ok(file_path)
     })
     .await?
 }
+
+/// Dumps every shard master into `<shard>.rdb` and packages them with a
+/// manifest of node ids and slot ranges into a single plain tar archive.
+fn backup_cluster(cfg: &DatabaseConfig, backup_dir: &Path, file_path: &Path, logger: &JobLogger) -> Result<()> {
+    let masters = discover_masters(cfg, logger)?;
+    if masters.is_empty() {
+        anyhow::bail!("No healthy cluster masters found via {}:{}", cfg.host, cfg.port);
+    }
+    logger.log("info", format!("Discovered {} cluster masters", masters.len()));
+
+    let shard_dir = backup_dir.join(format!("{}_shards", cfg.generated_id));
+    std::fs::create_dir_all(&shard_dir)?;
+
+    let mut manifest = Vec::with_capacity(masters.len());
+    for (i, node) in masters.iter().enumerate() {
+        let file = format!("shard-{i}.rdb");
+        logger.log("info", format!("Dumping shard {} from {}:{} (slots {})", i, node.host, node.port, node.slots));
+        dump_node(cfg, &node.host, node.port, &shard_dir.join(&file), logger)?;
+        manifest.push(json!({
+            "file": file,
+            "node_id": node.id,
+            "address": format!("{}:{}", node.host, node.port),
+            "slots": node.slots,
+        }));
+    }
+    std::fs::write(shard_dir.join("manifest.json"), serde_json::to_vec_pretty(&manifest)?)?;
+
+    let out = std::fs::File::create(file_path)
+        .with_context(|| format!("Failed to create {}", file_path.display()))?;
+    let mut tar = tar::Builder::new(out);
+    tar.append_dir_all(".", &shard_dir)?;
+    tar.finish()?;
+    std::fs::remove_dir_all(&shard_dir).ok();
+    Ok(())
+}
+
+fn discover_masters(cfg: &DatabaseConfig, logger: &JobLogger) -> Result<Vec<Node>> {
+    let mut shards = Command::new("redis-cli");
+    shards
+        .args(connection_args(cfg, &cfg.host, cfg.port))
+        .envs(auth_env(cfg))
+        .args(["--json", "CLUSTER", "SHARDS"]);
+    match query(shards, "redis-cli CLUSTER SHARDS", logger) {
+        Ok(out) if !out.starts_with("ERR") => return parse_cluster_shards(&out),
+        Ok(_) | Err(_) => logger.log("info", "CLUSTER SHARDS unavailable, falling back to CLUSTER NODES"),
+    }
+
+    let mut nodes = Command::new("redis-cli");
+    nodes
+        .args(connection_args(cfg, &cfg.host, cfg.port))
+        .envs(auth_env(cfg))
+        .args(["CLUSTER", "NODES"]);
+    Ok(parse_cluster_nodes(&query(nodes, "redis-cli CLUSTER NODES", logger)?))
+}
+
+fn resolve_sentinel(cfg: &DatabaseConfig, master: &str, replica: bool, logger: &JobLogger) -> Result<(String, u16)> {
+    let mut cmd = Command::new("redis-cli");
+    cmd.args(sentinel_connection_args(cfg))
+        .envs(sentinel_auth_env(cfg));
+
+    if replica {
+        cmd.args(["--json", "SENTINEL", "replicas", master]);
+        let out = query(cmd, "redis-cli SENTINEL replicas", logger)?;
+        pick_sentinel_replica(&out)?
+            .ok_or_else(|| anyhow::anyhow!("Sentinel reports no healthy replica for {}", master))
+    } else {
+        cmd.args(["SENTINEL", "get-master-addr-by-name", master]);
+        let out = query(cmd, "redis-cli SENTINEL get-master-addr-by-name", logger)?;
+        parse_sentinel_master(&out)
+            .ok_or_else(|| anyhow::anyhow!("Sentinel does not know master {}", master))
+    }
+}
+
+fn query(mut cmd: Command, display: &str, logger: &JobLogger) -> Result<String> {
+    let start = Instant::now();
+    let output = cmd.output().with_context(|| format!("{display} failed to start"))?;
+    let duration_ms = start.elapsed().as_millis() as f64;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        logger.log_command(display, Some(stderr.clone()), output.status.code(), Some(duration_ms));
+        anyhow::bail!("{} failed: {}", display, stderr.trim());
+    }
+    logger.log_command(display, None, Some(0), Some(duration_ms));
+    Ok(stdout)
+}
+
+fn dump_node(cfg: &DatabaseConfig, host: &str, port: u16, file_path: &Path, logger: &JobLogger) -> Result<()> {
+    let mut cmd = Command::new("redis-cli");
+    cmd.args(connection_args(cfg, host, port))
+        .envs(auth_env(cfg))
+        .arg("--rdb")
+        .arg(file_path);
+
+    if let Some(version) = engine_version(cfg, "redis-cli", host, port) {
+        logger.log("info", format!("{} {} at {}:{}", cfg.db_type.as_str(), version, host, port));
+    }
+    logger.log("info", format!("Running redis-cli --rdb for {} ({}:{})", cfg.name, host, port));
+
+    let start = Instant::now();
+    let output = cmd.output().context("Redis backup command failed")?;
+    let duration_ms = start.elapsed().as_millis() as f64;
+    let exit_code = output.status.code().unwrap_or(-1);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if !output.status.success() {
+        if stderr.contains("NOAUTH") {
+            logger.log(
+                "error",
+                format!(
+                    "Redis backup failed for {}: Authentication required (NOAUTH)",
+                    cfg.name
+                ),
+            );
+            logger.log_command(
+                "redis-cli",
+                Some("Authentication required (NOAUTH)".into()),
+                Some(exit_code),
+                Some(duration_ms),
+            );
+            anyhow::bail!(
+                "Redis backup failed for {}: Authentication required",
+                cfg.name
+            );
+        } else {
+            logger.log(
+                "error",
+                format!("Redis backup failed for {}: {}", cfg.name, stderr),
+            );
+            logger.log_command(
+                "redis-cli",
+                Some(stderr.to_string()),
+                Some(exit_code),
+                Some(duration_ms),
+            );
+            anyhow::bail!("Redis backup failed for {}: {}", cfg.name, stderr);
+        }
+    }
+
+    logger.log_command(
+        "redis-cli",
+        if stdout.is_empty() {
+            None
+        } else {
+            Some(stdout.to_string())
+        },
+        Some(0),
+        Some(duration_ms),
+    );
+    Ok(())
+}
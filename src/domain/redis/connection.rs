@@ -0,0 +1,235 @@
+use crate::services::config::DatabaseConfig;
+use serde_json::Value;
+
+fn option_str<'a>(cfg: &'a DatabaseConfig, key: &str) -> Option<&'a str> {
+    cfg.options
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// Topology selected with `options.mode`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Standalone,
+    /// Every master discovered through `CLUSTER SHARDS` is dumped.
+    Cluster,
+    /// `host`/`port` point at a Sentinel which resolves the node to dump.
+    Sentinel { master: String, replica: bool },
+}
+
+impl Mode {
+    pub fn from_config(cfg: &DatabaseConfig) -> anyhow::Result<Self> {
+        match option_str(cfg, "mode") {
+            None | Some("standalone") => Ok(Self::Standalone),
+            Some("cluster") => Ok(Self::Cluster),
+            Some("sentinel") => {
+                let master = option_str(cfg, "sentinel_master")
+                    .ok_or_else(|| anyhow::anyhow!("Sentinel mode requires options.sentinel_master"))?;
+                Ok(Self::Sentinel {
+                    master: master.to_string(),
+                    replica: option_str(cfg, "sentinel_target") == Some("replica"),
+                })
+            }
+            Some(other) => anyhow::bail!("Unknown Redis mode: {}", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub id: String,
+    pub host: String,
+    pub port: u16,
+    pub slots: String,
+}
+
+fn option_bool(cfg: &DatabaseConfig, key: &str) -> bool {
+    cfg.options.get(key).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+pub fn uses_tls(cfg: &DatabaseConfig) -> bool {
+    option_bool(cfg, "tls")
+        || option_str(cfg, "ca_cert").is_some()
+        || option_str(cfg, "client_cert").is_some()
+}
+
+/// TLS flags understood by both `redis-cli` and `valkey-cli`.
+pub fn tls_args(cfg: &DatabaseConfig) -> Vec<String> {
+    if !uses_tls(cfg) {
+        return Vec::new();
+    }
+    let mut args = vec!["--tls".to_string()];
+    for (key, flag) in [
+        ("ca_cert", "--cacert"),
+        ("client_cert", "--cert"),
+        ("client_key", "--key"),
+        ("sni", "--sni"),
+    ] {
+        if let Some(value) = option_str(cfg, key) {
+            args.push(flag.to_string());
+            args.push(value.to_string());
+        }
+    }
+    if option_bool(cfg, "insecure_skip_tls_verify") {
+        args.push("--insecure".to_string());
+    }
+    args
+}
+
+fn password_env(password: &str) -> Vec<(String, String)> {
+    if password.is_empty() {
+        return Vec::new();
+    }
+    // valkey-cli reads its own variable and falls back to the Redis one on
+    // older releases; setting both covers either binary.
+    vec![
+        ("REDISCLI_AUTH".to_string(), password.to_string()),
+        ("VALKEYCLI_AUTH".to_string(), password.to_string()),
+    ]
+}
+
+/// Connection flags for a data node. The password goes through
+/// [`auth_env`] so it never shows up in the process list.
+pub fn connection_args(cfg: &DatabaseConfig, host: &str, port: u16) -> Vec<String> {
+    let mut args = vec!["-h".to_string(), host.to_string(), "-p".to_string(), port.to_string()];
+    args.extend(tls_args(cfg));
+    if !cfg.username.is_empty() {
+        args.push("--user".to_string());
+        args.push(cfg.username.clone());
+    }
+    args
+}
+
+pub fn auth_env(cfg: &DatabaseConfig) -> Vec<(String, String)> {
+    password_env(&cfg.password)
+}
+
+/// Sentinels usually have their own credentials (`sentinel_username` /
+/// `sentinel_password`), distinct from the data nodes.
+pub fn sentinel_connection_args(cfg: &DatabaseConfig) -> Vec<String> {
+    let mut args = vec!["-h".to_string(), cfg.host.clone(), "-p".to_string(), cfg.port.to_string()];
+    args.extend(tls_args(cfg));
+    if let Some(user) = option_str(cfg, "sentinel_username") {
+        args.push("--user".to_string());
+        args.push(user.to_string());
+    }
+    args
+}
+
+pub fn sentinel_auth_env(cfg: &DatabaseConfig) -> Vec<(String, String)> {
+    password_env(option_str(cfg, "sentinel_password").unwrap_or_default())
+}
+
+/// RESP2 replies come back from `redis-cli --json` as flat `[k, v, k, v]`
+/// arrays, RESP3 ones as objects; accept both.
+fn field<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(map) => map.get(key),
+        Value::Array(items) => items
+            .chunks(2)
+            .find(|kv| kv.first().and_then(|k| k.as_str()) == Some(key))
+            .and_then(|kv| kv.get(1)),
+        _ => None,
+    }
+}
+
+fn field_str(value: &Value, key: &str) -> Option<String> {
+    match field(value, key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Masters from `CLUSTER SHARDS` (Redis 7+), one per shard.
+pub fn parse_cluster_shards(json: &str) -> anyhow::Result<Vec<Node>> {
+    let shards: Value = serde_json::from_str(json)?;
+    let shards = shards
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("Unexpected CLUSTER SHARDS reply"))?;
+
+    let mut masters = Vec::new();
+    for shard in shards {
+        let slots = field(shard, "slots")
+            .and_then(|s| s.as_array())
+            .map(|s| {
+                s.chunks(2)
+                    .map(|r| {
+                        let bound = |v: Option<&Value>| v.and_then(|v| v.as_i64()).unwrap_or_default();
+                        format!("{}-{}", bound(r.first()), bound(r.get(1)))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .unwrap_or_default();
+
+        let nodes = field(shard, "nodes").and_then(|n| n.as_array()).cloned().unwrap_or_default();
+        let master = nodes.iter().find(|n| {
+            field_str(n, "role").as_deref() == Some("master")
+                && field_str(n, "health").as_deref() != Some("fail")
+        });
+        let Some(master) = master else { continue };
+
+        let host = field_str(master, "endpoint")
+            .filter(|e| !e.is_empty() && e != "?")
+            .or_else(|| field_str(master, "ip"))
+            .ok_or_else(|| anyhow::anyhow!("Shard master without address"))?;
+        let port = field_str(master, "port")
+            .or_else(|| field_str(master, "tls-port"))
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("Shard master without port"))?;
+
+        masters.push(Node {
+            id: field_str(master, "id").unwrap_or_default(),
+            host,
+            port,
+            slots,
+        });
+    }
+    Ok(masters)
+}
+
+/// Masters from `CLUSTER NODES`, for servers older than Redis 7.
+pub fn parse_cluster_nodes(text: &str) -> Vec<Node> {
+    text.lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let flags = parts.get(2)?;
+            if !flags.split(',').any(|f| f == "master") || flags.contains("fail") {
+                return None;
+            }
+            let addr = parts.get(1)?.split('@').next()?;
+            let (host, port) = addr.rsplit_once(':')?;
+            Some(Node {
+                id: parts[0].to_string(),
+                host: host.to_string(),
+                port: port.parse().ok()?,
+                slots: parts.get(8..).map(|s| s.join(",")).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Reply of `SENTINEL get-master-addr-by-name`: the address on two lines.
+pub fn parse_sentinel_master(text: &str) -> Option<(String, u16)> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let host = lines.next()?.to_string();
+    let port = lines.next()?.parse().ok()?;
+    Some((host, port))
+}
+
+/// First healthy replica of `SENTINEL replicas <name>`.
+pub fn pick_sentinel_replica(json: &str) -> anyhow::Result<Option<(String, u16)>> {
+    let replicas: Value = serde_json::from_str(json)?;
+    Ok(replicas.as_array().into_iter().flatten().find_map(|r| {
+        let flags = field_str(r, "flags").unwrap_or_default();
+        if ["s_down", "o_down", "disconnected"].iter().any(|f| flags.contains(f)) {
+            return None;
+        }
+        let host = field_str(r, "ip")?;
+        let port = field_str(r, "port")?.parse().ok()?;
+        Some((host, port))
+    }))
+}
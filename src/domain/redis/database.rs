@@ -4,6 +4,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::domain::factory::Database;
+use crate::domain::redis::connection::Mode;
 use crate::domain::redis::{backup, ping};
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
@@ -22,7 +23,10 @@ impl RedisDatabase {
 #[async_trait]
 impl Database for RedisDatabase {
     fn file_extension(&self) -> &'static str {
-        ".rdb"
+        match Mode::from_config(&self.cfg) {
+            Ok(Mode::Cluster) => ".tar",
+            _ => ".rdb",
+        }
     }
 
     async fn ping(&self) -> Result<bool> {
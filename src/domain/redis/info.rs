@@ -0,0 +1,36 @@
+use super::connection::{auth_env, connection_args};
+use crate::services::config::{DatabaseConfig, DbType};
+use std::process::Command;
+
+/// `INFO server` field carrying the engine's own version. KeyDB reports its
+/// release in `redis_version`.
+pub fn version_field(db_type: &DbType) -> &'static str {
+    match db_type {
+        DbType::Dragonfly => "dragonfly_version",
+        DbType::Valkey => "valkey_version",
+        _ => "redis_version",
+    }
+}
+
+pub fn parse_info_field(info: &str, field: &str) -> Option<String> {
+    info.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(key, _)| *key == field)
+        .map(|(_, value)| value.trim().to_string())
+}
+
+/// Best-effort engine version of the node at `host:port`, for job logs.
+pub fn engine_version(cfg: &DatabaseConfig, program: &str, host: &str, port: u16) -> Option<String> {
+    let output = Command::new(program)
+        .args(connection_args(cfg, host, port))
+        .envs(auth_env(cfg))
+        .args(["INFO", "server"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let info = String::from_utf8_lossy(&output.stdout);
+    parse_info_field(&info, version_field(&cfg.db_type))
+        .or_else(|| parse_info_field(&info, "redis_version"))
+}
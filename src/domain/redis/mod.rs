@@ -1,3 +1,5 @@
 mod backup;
+pub mod connection;
 pub mod database;
-mod ping;
+pub mod info;
+pub mod ping;
@@ -1,3 +1,6 @@
+use super::connection::{
+    Mode, auth_env, connection_args, sentinel_auth_env, sentinel_connection_args,
+};
 use crate::services::config::DatabaseConfig;
 use anyhow::{Context, Result};
 use tokio::process::Command;
@@ -6,22 +9,20 @@ use tracing::{debug, error, info};
 
 pub async fn run(cfg: DatabaseConfig) -> Result<bool> {
     let mut cmd = Command::new("redis-cli");
-    cmd.arg("-h")
-        .arg(&cfg.host)
-        .arg("-p")
-        .arg(cfg.port.to_string());
 
-    if !cfg.username.is_empty() {
-        cmd.arg("--user").arg(&cfg.username);
-    }
-
-    if !cfg.password.is_empty() {
-        cmd.arg("-a").arg(&cfg.password);
-    }
+    // In sentinel mode host/port address the Sentinel itself.
+    match Mode::from_config(&cfg) {
+        Ok(Mode::Sentinel { .. }) => cmd
+            .args(sentinel_connection_args(&cfg))
+            .envs(sentinel_auth_env(&cfg)),
+        _ => cmd
+            .args(connection_args(&cfg, &cfg.host, cfg.port))
+            .envs(auth_env(&cfg)),
+    };
 
     cmd.arg("PING");
 
-    debug!("Command Ping Redis: {:?}", cmd);
+    debug!("Command Ping Redis: {:?}", cmd.as_std().get_args().collect::<Vec<_>>());
 
     let result = timeout(Duration::from_secs(10), cmd.output()).await;
 
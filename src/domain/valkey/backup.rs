@@ -1,3 +1,4 @@
+use crate::domain::redis::connection::{auth_env, connection_args};
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
 use anyhow::{Context, Result};
@@ -21,18 +22,10 @@ pub async fn run(
         let file_path = backup_dir.join(format!("{}{}", cfg.generated_id, file_extension));
 
         let mut cmd = Command::new("valkey-cli");
-        cmd.arg("-h")
-            .arg(&cfg.host)
-            .arg("-p")
-            .arg(cfg.port.to_string());
-
-        if !cfg.username.is_empty() {
-            cmd.arg("--user").arg(&cfg.username);
-        }
-        if !cfg.password.is_empty() {
-            cmd.arg("-a").arg(&cfg.password);
-        }
-        cmd.arg("--rdb").arg(&file_path);
+        cmd.args(connection_args(&cfg, &cfg.host, cfg.port))
+            .envs(auth_env(&cfg))
+            .arg("--rdb")
+            .arg(&file_path);
 
         logger.log("info", format!("Running valkey-cli --rdb for {}", cfg.name));
 
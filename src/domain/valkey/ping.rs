@@ -1,3 +1,4 @@
+use crate::domain::redis::connection::{auth_env, connection_args};
 use crate::services::config::DatabaseConfig;
 use anyhow::{Context, Result};
 use tokio::process::Command;
@@ -6,22 +7,11 @@ use tracing::{debug, info, error};
 
 pub async fn run(cfg: DatabaseConfig) -> Result<bool> {
     let mut cmd = Command::new("valkey-cli");
-    cmd.arg("-h")
-        .arg(&cfg.host)
-        .arg("-p")
-        .arg(cfg.port.to_string());
+    cmd.args(connection_args(&cfg, &cfg.host, cfg.port))
+        .envs(auth_env(&cfg))
+        .arg("PING");
 
-    if !cfg.username.is_empty() {
-        cmd.arg("--user").arg(&cfg.username);
-    }
-
-    if !cfg.password.is_empty() {
-        cmd.arg("-a").arg(&cfg.password);
-    }
-
-    cmd.arg("PING");
-
-    debug!("Command Ping Valkey: {:?}", cmd);
+    debug!("Command Ping Valkey: {:?}", cmd.as_std().get_args().collect::<Vec<_>>());
 
     let result = timeout(Duration::from_secs(10), cmd.output()).await;
 
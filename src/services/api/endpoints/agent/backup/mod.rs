@@ -3,6 +3,7 @@ pub mod upload;
 use crate::services::api::models::agent::backup::BackupResponse;
 use crate::services::api::{ApiClient, ApiError};
 use crate::services::backup::logger::JobLogEntry;
+use crate::services::backup::models::VerificationReport;
 use anyhow::Result;
 use reqwest::Method;
 use serde::Serialize;
@@ -25,6 +26,8 @@ pub struct BackupUpdateRequest {
     pub logs: Vec<JobLogEntry>,
     #[serde(rename = "durationMs")]
     pub duration_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<VerificationReport>,
 }
 
 impl ApiClient {
@@ -55,6 +58,7 @@ impl ApiClient {
         generated_id: impl Into<String>,
         job_logs: Vec<JobLogEntry>,
         duration_ms: f64,
+        verification: Option<VerificationReport>,
     ) -> Result<Option<BackupResponse>, ApiError> {
         let body = BackupUpdateRequest {
             backup_id: backup_id.into(),
@@ -63,6 +67,7 @@ impl ApiClient {
             generated_id: generated_id.into(),
             logs: job_logs,
             duration_ms: duration_ms,
+            verification,
         };
 
         let agent_id = agent_id.into();
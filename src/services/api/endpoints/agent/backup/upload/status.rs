@@ -1,5 +1,6 @@
 use crate::services::api::models::agent::backup::BackupUploadResponse;
 use crate::services::api::{ApiClient, ApiError};
+use crate::utils::stream::Checksums;
 use anyhow::Result;
 use reqwest::Method;
 use serde::Serialize;
@@ -15,6 +16,12 @@ pub struct StatusUploadRequest {
     pub size: u64,
     #[serde(rename = "backupId")]
     pub backup_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(rename = "plaintextSha256", skip_serializing_if = "Option::is_none")]
+    pub plaintext_sha256: Option<String>,
+    #[serde(rename = "manifestPath", skip_serializing_if = "Option::is_none")]
+    pub manifest_path: Option<String>,
 }
 
 impl ApiClient {
@@ -27,6 +34,8 @@ impl ApiClient {
         remote_path: impl Into<String>,
         total_size: impl Into<u64>,
         backup_id: impl Into<String>,
+        checksums: Option<&Checksums>,
+        manifest_path: Option<String>,
     ) -> Result<Option<BackupUploadResponse>, ApiError> {
         let body = StatusUploadRequest {
             generated_id: generated_id.into(),
@@ -35,6 +44,9 @@ impl ApiClient {
             path: remote_path.into(),
             size: total_size.into(),
             backup_id: backup_id.into(),
+            sha256: checksums.map(|c| c.sha256.clone()),
+            plaintext_sha256: checksums.map(|c| c.plaintext_sha256.clone()),
+            manifest_path,
         };
 
         let agent_id = agent_id.into();
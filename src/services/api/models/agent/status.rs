@@ -63,4 +63,7 @@ pub struct RestoreInfo {
     pub meta_file: Option<String>,
     #[serde(default, deserialize_with = "string_or_number_to_string")]
     pub size: Option<String>,
+    /// SHA-256 of the stored file, as reported when it was uploaded.
+    #[serde(default)]
+    pub sha256: Option<String>,
 }
@@ -2,6 +2,7 @@ use super::logger::JobLogger;
 use super::service::BackupService;
 use crate::services::api::models::agent::status::DatabaseStorage;
 use crate::services::config::DatabaseConfig;
+use crate::utils::compress::Compression;
 use crate::utils::common::BackupMethod;
 use crate::utils::locks::FileLock;
 
@@ -33,27 +34,47 @@ impl BackupService {
         let temp_dir = TempDir::new()?;
         let tmp_path = temp_dir.path();
 
-        let mut result = Self::run(db_cfg, tmp_path, Arc::clone(&logger)).await?;
+        let compression = Compression::from_config(&db_cfg)?;
+        let mut result = Self::run(db_cfg.clone(), &compression, tmp_path, Arc::clone(&logger)).await?;
 
         if result.status == "failed" {
             let duration_ms = start.elapsed().as_millis() as f64;
             let logs = Arc::try_unwrap(logger).unwrap_or_else(|_| JobLogger::new()).into_entries();
-            self.send_result(result, vec![], &backup_id, logs, duration_ms).await?;
+            self.send_result(result, vec![], &backup_id, logs, duration_ms, None).await?;
             return Ok(());
         }
 
-        let compressed = self.compress_backup(result.backup_file.take(), Arc::clone(&logger)).await?;
-        result.backup_file = Some(compressed);
+        // A streamed archive is compressed on its way to the uploader.
+        let streaming = result.stream.is_some();
+        if !streaming {
+            let (compressed, codec) = self
+                .compress_backup(result.backup_file.take(), &compression, Arc::clone(&logger))
+                .await?;
+            result.backup_file = Some(compressed);
+            result.codec = codec;
+        }
 
         let uploads = self
             .upload(result.clone(), method, storages, encrypt, &backup_id, Arc::clone(&logger))
-            .await?;
+            .await;
+
+        // The dump ran for as long as the uploads did, so its lock is held until now.
+        if streaming {
+            FileLock::release(&generated_id).await?;
+        }
+        let uploads = uploads?;
+
+        let verification = if uploads.iter().any(|u| u.success) {
+            self.verify_restore(&db_cfg, &result, Arc::clone(&logger)).await
+        } else {
+            None
+        };
 
         logger.log("info", "Database backup job finished".to_string());
 
         let duration_ms = start.elapsed().as_millis() as f64;
         let logs = Arc::try_unwrap(logger).unwrap_or_else(|_| JobLogger::new()).into_entries();
-        self.send_result(result, uploads, &backup_id, logs, duration_ms).await?;
+        self.send_result(result, uploads, &backup_id, logs, duration_ms, verification).await?;
 
         Ok(())
     }
@@ -0,0 +1,70 @@
+use super::models::{BackupResult, UploadResult};
+use crate::domain::factory::DumpInfo;
+use crate::services::config::DbType;
+use crate::settings::CONFIG;
+use crate::utils::compress::Codec;
+use crate::utils::file::EncryptionMetadataFile;
+use anyhow::Result;
+use bytes::Bytes;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Bumped when a manifest field changes meaning.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Appended to an archive's remote path to name its manifest.
+pub const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// JSON sidecar uploaded next to each archive, describing how it was made so
+/// a restore does not have to guess from its name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub version: u32,
+    pub agent_version: String,
+    pub generated_id: String,
+    pub created_at: String,
+    pub engine: DbType,
+    pub dump: DumpInfo,
+    pub codec: Codec,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptionMetadataFile>,
+    /// Archive bytes, before encryption.
+    pub size: u64,
+    /// Bytes stored, after encryption.
+    pub stored_size: u64,
+    pub sha256: String,
+    pub plaintext_sha256: String,
+    pub upload_duration_ms: u64,
+}
+
+impl BackupManifest {
+    /// Manifest of a successful upload of `result`; `None` when the upload
+    /// reported no checksums.
+    pub fn new(result: &BackupResult, upload: &UploadResult, encrypt: bool, upload_duration_ms: u64) -> Option<Self> {
+        let checksums = upload.checksums.as_ref()?;
+        Some(Self {
+            version: MANIFEST_VERSION,
+            agent_version: CONFIG.app_version.clone(),
+            generated_id: result.generated_id.clone(),
+            created_at: Utc::now().to_rfc3339(),
+            engine: result.db_type.clone(),
+            dump: result.dump.clone(),
+            codec: result.codec,
+            encryption: encrypt.then(EncryptionMetadataFile::current),
+            size: upload.total_size.unwrap_or_default(),
+            stored_size: checksums.size,
+            sha256: checksums.sha256.clone(),
+            plaintext_sha256: checksums.plaintext_sha256.clone(),
+            upload_duration_ms,
+        })
+    }
+
+    pub fn path_for(remote_file_path: &str) -> String {
+        format!("{remote_file_path}{MANIFEST_SUFFIX}")
+    }
+
+    pub fn to_bytes(&self) -> Result<Bytes> {
+        Ok(Bytes::from(serde_json::to_vec_pretty(self)?))
+    }
+}
@@ -1,5 +1,6 @@
 pub mod compressor;
 pub mod logger;
+pub mod manifest;
 pub mod dispatcher;
 pub mod executor;
 pub mod helpers;
@@ -8,5 +9,6 @@ pub mod result;
 pub mod runner;
 pub mod service;
 pub mod uploader;
+pub mod verifier;
 
 pub use service::BackupService;
@@ -1,6 +1,10 @@
 #![allow(dead_code)]
 
+use crate::domain::factory::DumpInfo;
 use crate::services::config::DbType;
+use crate::utils::compress::Codec;
+use crate::utils::stream::{Checksums, SharedStream};
+use serde::Serialize;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
@@ -9,6 +13,13 @@ pub struct BackupResult {
     pub db_type: DbType,
     pub status: String,
     pub backup_file: Option<PathBuf>,
+    /// Set instead of `backup_file` for streaming backups: the compressed
+    /// archive, produced while it is uploaded.
+    pub stream: Option<SharedStream>,
+    /// Compression of the uploaded archive, which names the remote file.
+    pub codec: Codec,
+    /// Server and tool behind the dump, for the backup manifest.
+    pub dump: DumpInfo,
     pub code: Option<String>,
 }
 
@@ -19,4 +30,28 @@ pub struct UploadResult {
     pub error: Option<String>,
     pub remote_file_path: Option<String>,
     pub total_size: Option<u64>,
+    pub checksums: Option<Checksums>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Outcome of restoring a fresh backup into a throwaway database.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationReport {
+    pub status: VerificationStatus,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: f64,
+    pub image: Option<String>,
+    /// User tables found after the restore.
+    pub tables: Option<u64>,
+    /// Output of `options.verify_query`, when set.
+    #[serde(rename = "queryResult")]
+    pub query_result: Option<String>,
+    pub error: Option<String>,
 }
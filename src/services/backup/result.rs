@@ -1,5 +1,5 @@
 use super::logger::JobLogEntry;
-use super::models::{BackupResult, UploadResult};
+use super::models::{BackupResult, UploadResult, VerificationReport};
 use super::service::BackupService;
 use crate::services::api::ApiError;
 use crate::services::api::models::agent::backup::BackupResponse;
@@ -14,6 +14,7 @@ impl BackupService {
         backup_id: &String,
         logs: Vec<JobLogEntry>,
         duration_ms: f64,
+        verification: Option<VerificationReport>,
     ) -> Result<Option<BackupResponse>, ApiError> {
         let status = if upload_results.iter().any(|r| r.success) {
             "success"
@@ -36,7 +37,8 @@ impl BackupService {
                 file_size,
                 &result.generated_id,
                 logs,
-                duration_ms
+                duration_ms,
+                verification,
             )
             .await
             .map_err(|e| {
@@ -4,14 +4,27 @@ use super::service::BackupService;
 
 use crate::domain::factory::DatabaseFactory;
 use crate::services::config::DatabaseConfig;
+use crate::domain::factory::Database;
+use crate::utils::compress::{Compression, tar_stream};
+use crate::utils::disk::{BACKUP_MARGIN, InsufficientSpace, Space, check_space, human_size, margin_option};
+use crate::utils::stream::SharedStream;
 
 use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::error;
 
+/// Dump bytes buffered per archive entry in streaming mode.
+const STREAM_PART_SIZE: usize = 64 * 1024 * 1024;
+
 impl BackupService {
-    pub async fn run(cfg: DatabaseConfig, tmp_path: &Path, logger: Arc<JobLogger>) -> Result<BackupResult> {
+    pub async fn run(
+        cfg: DatabaseConfig,
+        compression: &Compression,
+        tmp_path: &Path,
+        logger: Arc<JobLogger>,
+    ) -> Result<BackupResult> {
         let db = DatabaseFactory::create_for_backup(cfg.clone()).await;
 
         let generated_id = cfg.generated_id.clone();
@@ -35,18 +48,49 @@ impl BackupService {
                 db_type,
                 status: "failed".into(),
                 backup_file: None,
+                stream: None,
+                codec: compression.codec,
+                dump: Default::default(),
                 code: None,
             });
         }
 
-        match db.backup(tmp_path, Arc::clone(&logger)).await {
-            Ok(file) => Ok(BackupResult {
-                generated_id,
-                db_type,
-                status: "success".into(),
-                backup_file: Some(file),
-                code: None,
-            }),
+        let streaming = cfg.options.get("streaming").and_then(|v| v.as_bool()).unwrap_or(false);
+        let start = Instant::now();
+        let output = if streaming {
+            match db.backup_stream(Arc::clone(&logger)).await {
+                Ok(Some(dump)) => {
+                    logger.log("info", format!("Streaming {} to storage", dump.file_name));
+                    let archive = tar_stream(dump, *compression, STREAM_PART_SIZE);
+                    Ok((None, Some(SharedStream::new(archive))))
+                }
+                Ok(None) => {
+                    logger.log("info", "Streaming not supported for this database, using a temporary file");
+                    backup_to_file(db.as_ref(), &cfg, tmp_path, &logger).await.map(|file| (Some(file), None))
+                }
+                Err(e) => Err(e),
+            }
+        } else {
+            backup_to_file(db.as_ref(), &cfg, tmp_path, &logger).await.map(|file| (Some(file), None))
+        };
+
+        match output {
+            Ok((backup_file, stream)) => {
+                let mut dump = db.describe(stream.is_some()).await;
+                if stream.is_none() {
+                    dump.duration_ms = Some(start.elapsed().as_millis() as u64);
+                }
+                Ok(BackupResult {
+                    generated_id,
+                    db_type,
+                    status: "success".into(),
+                    backup_file,
+                    stream,
+                    codec: compression.codec,
+                    dump,
+                    code: None,
+                })
+            }
 
             Err(e) if e.to_string() == "backup_already_in_progress" => {
                 logger.log("warn", "Backup already in progress");
@@ -55,10 +99,27 @@ impl BackupService {
                     db_type,
                     status: "failed".into(),
                     backup_file: None,
+                    stream: None,
+                    codec: compression.codec,
+                    dump: Default::default(),
                     code: Some("backup_already_in_progress".into()),
                 })
             }
 
+            Err(e) if e.downcast_ref::<InsufficientSpace>().is_some() => {
+                logger.log("error", format!("{}, backup aborted", e));
+                Ok(BackupResult {
+                    generated_id,
+                    db_type,
+                    status: "failed".into(),
+                    backup_file: None,
+                    stream: None,
+                    codec: compression.codec,
+                    dump: Default::default(),
+                    code: Some("insufficient_disk_space".into()),
+                })
+            }
+
             Err(e) => {
                 logger.log("error", format!("Backup failed: {}", e));
                 Ok(BackupResult {
@@ -66,9 +127,38 @@ impl BackupService {
                     db_type,
                     status: "failed".into(),
                     backup_file: None,
+                    stream: None,
+                    codec: compression.codec,
+                    dump: Default::default(),
                     code: None,
                 })
             }
         }
     }
 }
+
+/// Dumps to a file in `tmp_path`, first checking the disk has room for it
+/// when the database can tell its size.
+async fn backup_to_file(
+    db: &dyn Database,
+    cfg: &DatabaseConfig,
+    tmp_path: &Path,
+    logger: &Arc<JobLogger>,
+) -> Result<PathBuf> {
+    if let Some(size) = db.estimated_size().await {
+        logger.log("info", format!("Database holds about {} of data to dump", human_size(size)));
+        let margin = margin_option(cfg).map_err(anyhow::Error::msg)?.unwrap_or(BACKUP_MARGIN);
+        if let Space::Tight { wanted, available } = check_space(tmp_path, size, margin)? {
+            logger.log(
+                "warn",
+                format!(
+                    "Only {} free in {}, backup may want about {}",
+                    human_size(available),
+                    tmp_path.display(),
+                    human_size(wanted)
+                ),
+            );
+        }
+    }
+    db.backup(tmp_path, Arc::clone(logger)).await
+}
@@ -1,12 +1,15 @@
 use super::logger::JobLogger;
+use super::manifest::BackupManifest;
 use super::models::{BackupResult, UploadResult};
 use super::service::BackupService;
 use crate::services::api::models::agent::status::DatabaseStorage;
 use crate::services::storage;
 use crate::utils::common::BackupMethod;
+use crate::utils::stream::{SharedStream, fan_out};
 use anyhow::{Result, bail};
 use futures::future::join_all;
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::info;
 
 impl BackupService {
@@ -26,9 +29,16 @@ impl BackupService {
 
         let ctx = self.ctx.clone();
 
-        let futures = storages.into_iter().map(|storage| {
+        // A streamed archive is read once and copied to every storage.
+        let streams: Vec<Option<SharedStream>> = match result.stream.as_ref().and_then(SharedStream::take) {
+            Some(source) => fan_out(source, storages.len()).into_iter().map(|s| Some(SharedStream::new(s))).collect(),
+            None => vec![None; storages.len()],
+        };
+
+        let futures = storages.into_iter().zip(streams).map(|(storage, stream)| {
             let ctx_clone = ctx.clone();
-            let result_clone = result.clone();
+            let mut result_clone = result.clone();
+            result_clone.stream = stream;
             let provider = storage::get_provider(&storage);
             let logger_clone = Arc::clone(&logger);
 
@@ -61,6 +71,7 @@ impl BackupService {
                             error: Some("backup_upload_init failed".into()),
                             remote_file_path: None,
                             total_size: None,
+                            checksums: None,
                         };
                     }
                 };
@@ -75,6 +86,7 @@ impl BackupService {
                             error: Some("backup_upload_init returned empty response".into()),
                             remote_file_path: None,
                             total_size: None,
+                            checksums: None,
                         };
                     }
                 };
@@ -91,16 +103,18 @@ impl BackupService {
                         error: Some("missing provider".into()),
                         remote_file_path: None,
                         total_size: None,
+                        checksums: None,
                     };
                 };
 
                 /*
                  STORAGE UPLOAD
                 */
+                let upload_start = Instant::now();
                 let upload_result = provider
                     .upload(
                         ctx_clone.clone(),
-                        result_clone,
+                        result_clone.clone(),
                         method,
                         &storage,
                         Some(encrypt),
@@ -129,6 +143,8 @@ impl BackupService {
                             String::new(),
                             0u64,
                             backup_id,
+                            None,
+                            None,
                         )
                         .await
                     {
@@ -162,10 +178,40 @@ impl BackupService {
                                 error: Some("remote_file_path or total_size missing".into()),
                                 remote_file_path: None,
                                 total_size: None,
+                                checksums: None,
                             };
                         }
                     };
 
+                /*
+                 MANIFEST
+                */
+                let upload_duration_ms = upload_start.elapsed().as_millis() as u64;
+                let manifest_path = match BackupManifest::new(&result_clone, &upload_result, encrypt, upload_duration_ms) {
+                    Some(manifest) => {
+                        let path = BackupManifest::path_for(&remote_path);
+                        let stored = match manifest.to_bytes() {
+                            Ok(body) => provider.upload_sidecar(&storage, &path, body).await,
+                            Err(e) => Err(e),
+                        };
+                        match stored {
+                            Ok(true) => {
+                                logger_clone.log("info", format!("Manifest uploaded to {}", path));
+                                Some(path)
+                            }
+                            Ok(false) => {
+                                logger_clone.log("info", format!("Storage {} does not keep manifests", storage_id));
+                                None
+                            }
+                            Err(e) => {
+                                logger_clone.log("warn", format!("Manifest upload failed for storage {}: {}", storage_id, e));
+                                None
+                            }
+                        }
+                    }
+                    None => None,
+                };
+
                 /*
                  STATUS UPDATE
                 */
@@ -179,6 +225,8 @@ impl BackupService {
                         remote_path,
                         total_size,
                         backup_id,
+                        upload_result.checksums.as_ref(),
+                        manifest_path,
                     )
                     .await
                 {
@@ -193,6 +241,7 @@ impl BackupService {
                             error: Some(err.to_string()),
                             remote_file_path: None,
                             total_size: None,
+                            checksums: None,
                         }
                     }
                 }
@@ -0,0 +1,320 @@
+use super::logger::JobLogger;
+use super::models::{BackupResult, VerificationReport, VerificationStatus};
+use super::service::BackupService;
+use crate::domain::docker_exec::{self, ExecCommand, Execution};
+use crate::domain::docker_volume::docker::{ensure_image, remove_helper, run_ephemeral};
+use crate::domain::docker_volume::endpoint::connect;
+use crate::domain::factory::DatabaseFactory;
+use crate::domain::mysql::exec::{self as mysql_exec, Flavor};
+use crate::domain::postgres::exec as postgres_exec;
+use crate::services::config::{DatabaseConfig, DbType};
+use crate::utils::common::choose_restore_path;
+use crate::utils::compress::decompress_large_tar_gz;
+use anyhow::{Context, Result};
+use bollard::query_parameters::InspectContainerOptions;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long a throwaway database gets to start accepting connections.
+const READY_TIMEOUT: Duration = Duration::from_secs(180);
+const READY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest `verify_query` output kept in the report.
+const QUERY_RESULT_LIMIT: usize = 1024;
+
+/// Options the throwaway database inherits: where the Docker daemon is.
+const ENDPOINT_OPTIONS: [&str; 6] = [
+    "docker_host",
+    "docker_cert_path",
+    "docker_tls_ca",
+    "docker_tls_cert",
+    "docker_tls_key",
+    "ssh_key",
+];
+
+/// Restore verification for a database: `options.verify_restore`, with an
+/// optional `options.verify_image` overriding the detected image and an
+/// optional `options.verify_query` run after the restore.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyOptions {
+    pub image: Option<String>,
+    pub query: Option<String>,
+}
+
+impl VerifyOptions {
+    pub fn from_config(cfg: &DatabaseConfig) -> Option<Self> {
+        let enabled = cfg.options.get("verify_restore").and_then(|v| v.as_bool()).unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let text = |key: &str| {
+            cfg.options
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        Some(Self {
+            image: text("verify_image"),
+            query: text("verify_query"),
+        })
+    }
+}
+
+/// Engines a backup can be verified for, restored through their exec mode
+/// into the official image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Postgres,
+    Mysql(Flavor),
+}
+
+impl Engine {
+    pub fn for_type(db_type: &DbType) -> Option<Self> {
+        match db_type {
+            DbType::Postgresql => Some(Self::Postgres),
+            DbType::Mysql => Some(Self::Mysql(Flavor::Mysql)),
+            DbType::Mariadb => Some(Self::Mysql(Flavor::Mariadb)),
+            _ => None,
+        }
+    }
+
+    /// Official image for a server version string such as `16.2 (Debian
+    /// 16.2-1.pgdg120+2)` or `10.11.6-MariaDB-1:10.11.6+maria~ubu2204`.
+    pub fn image_for(self, version: &str) -> Result<String> {
+        let version = version.trim();
+        let tag = match self {
+            Self::Postgres => version.split_whitespace().next(),
+            Self::Mysql(_) => version.split('-').next(),
+        }
+        .filter(|tag| !tag.is_empty() && tag.chars().all(|c| c.is_ascii_digit() || c == '.'))
+        .with_context(|| format!("Cannot derive an image tag from server version '{version}'"))?;
+
+        let repository = match self {
+            Self::Postgres => "postgres",
+            Self::Mysql(Flavor::Mysql) => "mysql",
+            Self::Mysql(Flavor::Mariadb) => "mariadb",
+        };
+        Ok(format!("{repository}:{tag}"))
+    }
+
+    /// Config restoring into a throwaway container of this engine, and the
+    /// environment its image initialises the matching credentials from.
+    pub fn target(self, source: &DatabaseConfig) -> (DatabaseConfig, Vec<String>) {
+        let mut cfg = source.clone();
+        cfg.generated_id = Uuid::new_v4().to_string();
+        cfg.password = Uuid::new_v4().simple().to_string();
+        cfg.host = String::new();
+        cfg.port = 0;
+        cfg.container_name = None;
+        cfg.options = ENDPOINT_OPTIONS
+            .iter()
+            .filter_map(|key| source.options.get(*key).map(|v| (key.to_string(), v.clone())))
+            .collect();
+        cfg.options.insert("execution".into(), Value::from("docker_exec"));
+        // The database is empty, there is nothing to clean.
+        cfg.options.insert("clean_mode".into(), Value::from("none"));
+
+        let env = match self {
+            Self::Postgres => vec![
+                format!("POSTGRES_USER={}", cfg.username),
+                format!("POSTGRES_PASSWORD={}", cfg.password),
+                format!("POSTGRES_DB={}", cfg.database),
+            ],
+            Self::Mysql(flavor) => {
+                cfg.username = "root".into();
+                let prefix = match flavor {
+                    Flavor::Mysql => "MYSQL",
+                    Flavor::Mariadb => "MARIADB",
+                };
+                vec![
+                    format!("{prefix}_ROOT_PASSWORD={}", cfg.password),
+                    format!("{prefix}_DATABASE={}", cfg.database),
+                ]
+            }
+        };
+        (cfg, env)
+    }
+
+    fn ready_command(self, cfg: &DatabaseConfig) -> ExecCommand {
+        match self {
+            Self::Postgres => postgres_exec::tcp_ping_command(cfg),
+            Self::Mysql(flavor) => mysql_exec::tcp_ping_command(cfg, flavor),
+        }
+    }
+
+    fn query_command(self, cfg: &DatabaseConfig, sql: &str) -> ExecCommand {
+        match self {
+            Self::Postgres => postgres_exec::query_command(cfg, sql),
+            Self::Mysql(flavor) => mysql_exec::query_command(cfg, flavor, sql),
+        }
+    }
+
+    fn table_count_sql(self) -> &'static str {
+        match self {
+            Self::Postgres => {
+                "SELECT count(*) FROM information_schema.tables \
+                 WHERE table_type = 'BASE TABLE' AND table_schema NOT IN ('pg_catalog', 'information_schema')"
+            }
+            Self::Mysql(_) => {
+                "SELECT COUNT(*) FROM information_schema.tables \
+                 WHERE table_type = 'BASE TABLE' AND table_schema = DATABASE()"
+            }
+        }
+    }
+}
+
+impl BackupService {
+    /// Restores the backup archive into a throwaway container and checks the
+    /// result, when `options.verify_restore` is set. A failed verification is
+    /// reported but does not fail the backup.
+    pub async fn verify_restore(
+        &self,
+        cfg: &DatabaseConfig,
+        result: &BackupResult,
+        logger: Arc<JobLogger>,
+    ) -> Option<VerificationReport> {
+        let options = VerifyOptions::from_config(cfg)?;
+        let start = Instant::now();
+        let mut report = VerificationReport {
+            status: VerificationStatus::Passed,
+            duration_ms: 0.0,
+            image: None,
+            tables: None,
+            query_result: None,
+            error: None,
+        };
+
+        let skipped = match (Engine::for_type(&cfg.db_type), result.backup_file.as_deref()) {
+            (None, _) => Some(format!("Restore verification is not supported for {}", cfg.db_type.as_str())),
+            (_, None) => Some("Streamed backups keep no local archive to verify".to_string()),
+            (Some(engine), Some(archive)) => {
+                logger.log("info", "Start restore verification".to_string());
+                match verify(cfg, engine, archive, &options, &mut report, &logger).await {
+                    Ok(()) => logger.log(
+                        "info",
+                        format!("Restore verification passed ({} tables)", report.tables.unwrap_or_default()),
+                    ),
+                    Err(e) => {
+                        logger.log("error", format!("Restore verification failed: {e:#}"));
+                        report.status = VerificationStatus::Failed;
+                        report.error = Some(format!("{e:#}"));
+                    }
+                }
+                None
+            }
+        };
+        if let Some(reason) = skipped {
+            logger.log("warn", reason.clone());
+            report.status = VerificationStatus::Skipped;
+            report.error = Some(reason);
+        }
+
+        report.duration_ms = start.elapsed().as_millis() as f64;
+        Some(report)
+    }
+}
+
+/// Image of the database the backup came from: its container's own image in
+/// docker_exec mode, otherwise the official image for its server version.
+async fn source_image(cfg: &DatabaseConfig, engine: Engine) -> Result<String> {
+    if let Execution::Container(container) = Execution::from_config(cfg)? {
+        let docker = connect(cfg).await?;
+        let info = docker
+            .inspect_container(&container, None::<InspectContainerOptions>)
+            .await
+            .with_context(|| format!("Failed to inspect container {container}"))?;
+        return info
+            .config
+            .and_then(|c| c.image)
+            .with_context(|| format!("Container {container} reports no image"));
+    }
+
+    let version = match engine {
+        Engine::Postgres => crate::domain::postgres::connection::server_version(cfg).await?,
+        Engine::Mysql(Flavor::Mysql) => crate::domain::mysql::connection::server_version(cfg).await?,
+        Engine::Mysql(Flavor::Mariadb) => crate::domain::mariadb::connection::server_version(cfg).await?,
+    };
+    engine.image_for(&version)
+}
+
+async fn verify(
+    cfg: &DatabaseConfig,
+    engine: Engine,
+    archive: &Path,
+    options: &VerifyOptions,
+    report: &mut VerificationReport,
+    logger: &Arc<JobLogger>,
+) -> Result<()> {
+    let image = match &options.image {
+        Some(image) => image.clone(),
+        None => source_image(cfg, engine).await?,
+    };
+    report.image = Some(image.clone());
+
+    let extract_dir = tempfile::TempDir::new()?;
+    let files = decompress_large_tar_gz(archive, extract_dir.path()).await?;
+    if files.is_empty() {
+        anyhow::bail!("Archive is empty");
+    }
+    let restore_file = choose_restore_path(&files, extract_dir.path(), archive);
+
+    let docker = connect(cfg).await?;
+    ensure_image(&docker, &image).await?;
+    let (mut target, env) = engine.target(cfg);
+    let container = run_ephemeral(&docker, &image, env, &cfg.generated_id).await?;
+    logger.log("info", format!("Started throwaway {image} container {}", &container.id[..12.min(container.id.len())]));
+    target.container_name = Some(container.id.clone());
+
+    let res = restore_and_check(engine, &target, &container.id, &restore_file, options, report, logger).await;
+    remove_helper(&docker, &container.id).await;
+    res
+}
+
+async fn restore_and_check(
+    engine: Engine,
+    target: &DatabaseConfig,
+    container: &str,
+    restore_file: &Path,
+    options: &VerifyOptions,
+    report: &mut VerificationReport,
+    logger: &Arc<JobLogger>,
+) -> Result<()> {
+    let start = Instant::now();
+    while !docker_exec::probe(target, container, engine.ready_command(target)).await? {
+        if start.elapsed() > READY_TIMEOUT {
+            anyhow::bail!("Throwaway database not ready after {}s", READY_TIMEOUT.as_secs());
+        }
+        tokio::time::sleep(READY_INTERVAL).await;
+    }
+
+    let db = DatabaseFactory::create_for_restore(target.clone(), restore_file).await;
+    db.restore(restore_file, Arc::clone(logger))
+        .await
+        .context("Restore into throwaway database failed")?;
+
+    let count = engine.query_command(target, engine.table_count_sql());
+    let out = docker_exec::run(target, container, "table count", count, None, None, logger).await?;
+    let tables: u64 = out
+        .output
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .and_then(|line| line.parse().ok())
+        .with_context(|| format!("Unexpected table count output: {}", out.output))?;
+    report.tables = Some(tables);
+    if tables == 0 {
+        anyhow::bail!("No tables found after restore");
+    }
+
+    if let Some(sql) = &options.query {
+        let query = engine.query_command(target, sql);
+        let out = docker_exec::run(target, container, "verify_query", query, None, None, logger).await?;
+        report.query_result = Some(out.output.trim().chars().take(QUERY_RESULT_LIMIT).collect());
+    }
+    Ok(())
+}
@@ -1,7 +1,9 @@
 #![allow(dead_code)]
 
 use crate::core::context::Context;
-use serde::Deserialize;
+use crate::services::discovery::{self, DiscoveryMode};
+use crate::utils::compress::Compression;
+use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::HashMap;
 use std::fs::File;
@@ -9,10 +11,10 @@ use std::io::Read;
 use std::path::Path;
 use std::sync::Arc;
 use toml;
-use tracing::info;
+use tracing::{info, warn};
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum DbType {
     Mysql,
@@ -28,6 +30,18 @@ pub enum DbType {
     Mssql,
     #[serde(rename = "docker-volume")]
     DockerVolume,
+    #[serde(rename = "docker-compose")]
+    DockerCompose,
+    Etcd,
+    Duckdb,
+    Couchdb,
+    Influxdb,
+    Neo4j,
+    Cassandra,
+    Scylladb,
+    Dragonfly,
+    Keydb,
+    Filesystem,
 }
 
 impl DbType {
@@ -44,6 +58,17 @@ impl DbType {
             DbType::Firebird => "firebird",
             DbType::Mssql => "mssql",
             DbType::DockerVolume => "docker-volume",
+            DbType::DockerCompose => "docker-compose",
+            DbType::Etcd => "etcd",
+            DbType::Duckdb => "duckdb",
+            DbType::Couchdb => "couchdb",
+            DbType::Influxdb => "influxdb",
+            DbType::Neo4j => "neo4j",
+            DbType::Cassandra => "cassandra",
+            DbType::Scylladb => "scylladb",
+            DbType::Dragonfly => "dragonfly",
+            DbType::Keydb => "keydb",
+            DbType::Filesystem => "filesystem",
         }
     }
 }
@@ -74,7 +99,7 @@ pub struct DatabasesConfig {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct InputDatabaseConfig {
     pub name: String,
     pub database: Option<String>,
@@ -93,7 +118,7 @@ pub struct InputDatabaseConfig {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct InputDatabasesConfig {
     pub databases: Vec<InputDatabaseConfig>,
 }
@@ -108,7 +133,50 @@ impl ConfigService {
     }
 
     pub fn load(&self, file_path: Option<&str>) -> Result<DatabasesConfig, String> {
-        let path: String = if let Some(fp) = file_path {
+        let path = Self::config_path(file_path);
+        let input_config = Self::read_input(&path)?;
+        Self::resolve(input_config)
+    }
+
+    /// Loads the config file and merges in the databases found by Docker
+    /// discovery (`DOCKER_DISCOVERY`). Entries from the file take precedence
+    /// over discovered ones with the same id or name.
+    pub async fn load_with_discovery(&self) -> Result<DatabasesConfig, String> {
+        let mode = DiscoveryMode::from_settings();
+        if mode == DiscoveryMode::Off {
+            return self.load(None);
+        }
+
+        let path = Self::config_path(None);
+        let mut input_config = if Path::new(&path).exists() || !mode.generates() {
+            Self::read_input(&path)?
+        } else {
+            info!("No config file at {}, using discovered databases only", path);
+            InputDatabasesConfig { databases: Vec::new() }
+        };
+
+        let discovered = match discovery::discover(mode, &self.ctx.edge_key.agent_id).await {
+            Ok(found) => found,
+            Err(e) => {
+                warn!("Docker discovery failed: {:#}", e);
+                Vec::new()
+            }
+        };
+
+        if mode.generates() {
+            discovery::merge(&mut input_config.databases, discovered);
+        } else if !discovered.is_empty() && discovery::proposal_changed(&discovered) {
+            match discovery::proposal_snippet(&discovered) {
+                Ok(snippet) => info!("Docker discovery proposes:\n{}", snippet),
+                Err(e) => warn!("Failed to render discovery proposal: {}", e),
+            }
+        }
+
+        Self::resolve(input_config)
+    }
+
+    fn config_path(file_path: Option<&str>) -> String {
+        if let Some(fp) = file_path {
             fp.to_string()
         } else {
             format!(
@@ -116,8 +184,10 @@ impl ConfigService {
                 crate::settings::CONFIG.data_path,
                 crate::settings::CONFIG.databases_config_file
             )
-        };
+        }
+    }
 
+    fn read_input(path: &str) -> Result<InputDatabasesConfig, String> {
         info!("Loading databases config from: {}", path);
 
         let path_obj = Path::new(&path);
@@ -149,6 +219,11 @@ impl ConfigService {
             }
             _ => return Err("Unsupported config file format. Use .json or .toml".to_string()),
         };
+        Ok(input_config)
+    }
+
+    /// Validates input entries and fills per-type defaults.
+    pub fn resolve(input_config: InputDatabasesConfig) -> Result<DatabasesConfig, String> {
 
         fn required<T: Clone>(
             opt: &Option<T>,
@@ -199,7 +274,32 @@ impl ConfigService {
                 _ => optional(&db.password),
             };
 
+            // Exec mode reaches the server through its container, not the network.
+            let via_exec = db
+                .options
+                .as_ref()
+                .and_then(|o| o.get("execution"))
+                .and_then(|v| v.as_str())
+                == Some("docker_exec");
+            if via_exec && db.container_name.is_none() {
+                return Err(format!(
+                    "Missing required field 'container_name' for database '{}' (execution docker_exec)",
+                    db.name
+                ));
+            }
+
+            // Neo4j online backups go through the backup port, not the volume.
+            let neo4j_online = db.db_type == DbType::Neo4j
+                && db
+                    .options
+                    .as_ref()
+                    .and_then(|o| o.get("mode"))
+                    .and_then(|v| v.as_str())
+                    == Some("online");
+
             let host = match db.db_type {
+                _ if via_exec => optional(&db.host),
+                DbType::Neo4j if neo4j_online => required(&db.host, &db.name, "host")?,
                 DbType::Postgresql
                 | DbType::PostgresqlCluster
                 | DbType::Mysql
@@ -208,11 +308,27 @@ impl ConfigService {
                 | DbType::Redis
                 | DbType::Firebird
                 | DbType::Valkey
-                | DbType::Mssql => required(&db.host, &db.name, "host")?,
-                DbType::Sqlite | DbType::DockerVolume => optional(&db.host),
+                | DbType::Mssql
+                | DbType::Etcd
+                | DbType::Couchdb
+                | DbType::Influxdb
+                | DbType::Cassandra
+                | DbType::Scylladb
+                | DbType::Dragonfly
+                | DbType::Keydb => required(&db.host, &db.name, "host")?,
+                DbType::Sqlite
+                | DbType::Duckdb
+                | DbType::DockerVolume
+                | DbType::DockerCompose
+                | DbType::Neo4j
+                | DbType::Filesystem => {
+                    optional(&db.host)
+                }
             };
 
             let port = match db.db_type {
+                _ if via_exec => db.port.unwrap_or(0),
+                DbType::Neo4j if neo4j_online => required(&db.port, &db.name, "port")?,
                 DbType::Postgresql
                 | DbType::PostgresqlCluster
                 | DbType::Mysql
@@ -221,23 +337,48 @@ impl ConfigService {
                 | DbType::Redis
                 | DbType::Firebird
                 | DbType::Valkey
-                | DbType::Mssql => required(&db.port, &db.name, "port")?,
-                DbType::Sqlite | DbType::DockerVolume => db.port.unwrap_or(0),
+                | DbType::Mssql
+                | DbType::Etcd
+                | DbType::Couchdb
+                | DbType::Influxdb
+                | DbType::Cassandra
+                | DbType::Scylladb
+                | DbType::Dragonfly
+                | DbType::Keydb => required(&db.port, &db.name, "port")?,
+                DbType::Sqlite
+                | DbType::Duckdb
+                | DbType::DockerVolume
+                | DbType::DockerCompose
+                | DbType::Neo4j
+                | DbType::Filesystem => {
+                    db.port.unwrap_or(0)
+                }
             };
 
             let database_name = match db.db_type {
-                DbType::Sqlite | DbType::Redis | DbType::Valkey | DbType::DockerVolume => {
-                    optional(&db.database)
-                }
+                DbType::Sqlite
+                | DbType::Duckdb
+                | DbType::Redis
+                | DbType::Valkey
+                | DbType::Dragonfly
+                | DbType::Keydb
+                | DbType::DockerVolume
+                | DbType::Etcd
+                | DbType::Couchdb
+                | DbType::Influxdb
+                | DbType::Filesystem => optional(&db.database),
                 DbType::PostgresqlCluster => db
                     .database
                     .clone()
                     .unwrap_or_else(|| "postgres".to_string()),
+                DbType::Neo4j => db.database.clone().unwrap_or_else(|| "neo4j".to_string()),
                 _ => required(&db.database, &db.name, "database")?,
             };
 
             let path_val = match db.db_type {
-                DbType::Sqlite => required(&db.path, &db.name, "path")?,
+                DbType::Sqlite | DbType::Duckdb | DbType::Filesystem | DbType::Etcd => {
+                    required(&db.path, &db.name, "path")?
+                }
                 _ => optional(&db.path),
             };
 
@@ -249,12 +390,15 @@ impl ConfigService {
             };
 
             let volume_name = match db.db_type {
-                DbType::DockerVolume => required(&db.volume_name, &db.name, "volume_name")?,
+                DbType::Neo4j if neo4j_online => optional(&db.volume_name),
+                DbType::DockerVolume | DbType::Neo4j => {
+                    required(&db.volume_name, &db.name, "volume_name")?
+                }
                 _ => optional(&db.volume_name),
             };
             let container_name = db.container_name.clone();
 
-            databases.push(DatabaseConfig {
+            let database = DatabaseConfig {
                 name: db.name,
                 database: database_name,
                 db_type: db.db_type,
@@ -268,10 +412,40 @@ impl ConfigService {
                 volume_name,
                 container_name,
                 options: db.options.unwrap_or_default(),
-            });
+            };
+
+            if let Err(e) = Compression::from_config(&database) {
+                return Err(format!("Invalid compression for database '{}': {}", database.name, e));
+            }
+            if let Err(e) = crate::utils::disk::margin_option(&database) {
+                return Err(format!("Invalid options for database '{}': {}", database.name, e));
+            }
+            if let Err(e) = Self::validate_options(&database) {
+                return Err(format!("Invalid options for database '{}': {}", database.name, e));
+            }
+
+            databases.push(database);
         }
 
         info!("Databases: {} instances loaded", databases.len());
         Ok(DatabasesConfig { databases })
     }
+
+    /// Engine options whose unknown values must not fall back to a default.
+    fn validate_options(cfg: &DatabaseConfig) -> anyhow::Result<()> {
+        use crate::domain::couchdb::connection::ConflictMode;
+        use crate::domain::filesystem::connection::{RestoreMode, SymlinkPolicy};
+
+        match cfg.db_type {
+            DbType::Filesystem => {
+                SymlinkPolicy::from_config(cfg)?;
+                RestoreMode::from_config(cfg)?;
+            }
+            DbType::Couchdb => {
+                ConflictMode::from_config(cfg)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
 }
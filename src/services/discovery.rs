@@ -0,0 +1,361 @@
+use crate::domain::docker_volume::docker::{client, self_container_id};
+use crate::services::config::{DbType, InputDatabaseConfig, InputDatabasesConfig};
+use anyhow::{Context, Result};
+use bollard::Docker;
+use bollard::models::ContainerInspectResponse;
+use bollard::query_parameters::{InspectContainerOptions, ListContainersOptions};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// `true` opts a container in, `false` keeps it out of discovery in every mode.
+pub const BACKUP_LABEL: &str = "io.portabase.backup";
+/// Overrides the detected engine, for images not recognised by name.
+pub const TYPE_LABEL: &str = "io.portabase.type";
+/// Overrides the database name (defaults to the container name).
+pub const NAME_LABEL: &str = "io.portabase.name";
+/// Overrides the database to back up.
+pub const DATABASE_LABEL: &str = "io.portabase.database";
+/// Pins the generated id instead of deriving it from the container name.
+pub const ID_LABEL: &str = "io.portabase.id";
+
+/// Written in place of discovered passwords in proposals.
+pub const REDACTED_PASSWORD: &str = "<redacted>";
+
+/// Ids in the last logged proposal, so an unchanged one is not logged again
+/// on every config reload.
+static LAST_PROPOSAL: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// What the agent does with databases found on the local Docker daemon
+/// (`DOCKER_DISCOVERY`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoveryMode {
+    Off,
+    /// Log the entries that would be generated, as a `databases.toml` snippet.
+    Propose,
+    /// Back up containers labelled `io.portabase.backup=true` only.
+    Labeled,
+    /// Back up every recognised container not labelled `io.portabase.backup=false`.
+    All,
+}
+
+impl DiscoveryMode {
+    pub fn parse(value: &str) -> (Self, Option<String>) {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "off" | "false" => (Self::Off, None),
+            "propose" => (Self::Propose, None),
+            "labeled" | "labelled" => (Self::Labeled, None),
+            "all" | "true" => (Self::All, None),
+            other => (Self::Off, Some(other.to_string())),
+        }
+    }
+
+    pub fn from_settings() -> Self {
+        let (mode, unknown) = Self::parse(&crate::settings::CONFIG.docker_discovery);
+        if let Some(value) = unknown {
+            warn!("Unknown DOCKER_DISCOVERY '{}', expected off, propose, labeled or all", value);
+        }
+        mode
+    }
+
+    /// Whether discovered entries are added to the loaded config.
+    pub fn generates(&self) -> bool {
+        matches!(self, Self::Labeled | Self::All)
+    }
+
+    /// Whether a container with the given `io.portabase.backup` label value is
+    /// considered.
+    pub fn accepts(&self, label: Option<&str>) -> bool {
+        match (self, label.map(parse_bool)) {
+            (Self::Off, _) => false,
+            (_, Some(Some(false))) => false,
+            (Self::Labeled, label) => label == Some(Some(true)),
+            _ => true,
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// The parts of an inspected container discovery works from.
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveredContainer {
+    pub name: String,
+    pub image: String,
+    pub env: HashMap<String, String>,
+    pub labels: HashMap<String, String>,
+    /// Network name to the container's IP address on it.
+    pub networks: BTreeMap<String, String>,
+}
+
+impl DiscoveredContainer {
+    pub fn from_inspect(info: &ContainerInspectResponse) -> Option<Self> {
+        let config = info.config.as_ref()?;
+        let networks = info
+            .network_settings
+            .as_ref()
+            .and_then(|s| s.networks.as_ref())
+            .map(|nets| {
+                nets.iter()
+                    .map(|(name, ep)| (name.clone(), ep.ip_address.clone().unwrap_or_default()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            name: info.name.as_deref().unwrap_or_default().trim_start_matches('/').to_string(),
+            image: config.image.clone().unwrap_or_default(),
+            env: parse_env(config.env.as_deref().unwrap_or_default()),
+            labels: config.labels.clone().unwrap_or_default(),
+            networks,
+        })
+    }
+}
+
+pub fn parse_env(vars: &[String]) -> HashMap<String, String> {
+    vars.iter()
+        .filter_map(|v| v.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Engine of an image from its repository name, ignoring registry, namespace,
+/// tag and digest: `docker.io/bitnami/postgresql:16` is PostgreSQL.
+pub fn detect_type(image: &str) -> Option<DbType> {
+    let repo = image.split('@').next().unwrap_or(image);
+    let last = repo.rsplit('/').next().unwrap_or(repo);
+    let name = last.split(':').next().unwrap_or(last);
+    match name {
+        "postgres" | "postgresql" => Some(DbType::Postgresql),
+        "mysql" => Some(DbType::Mysql),
+        "mariadb" => Some(DbType::Mariadb),
+        "mongo" | "mongodb" => Some(DbType::MongoDB),
+        "redis" => Some(DbType::Redis),
+        "valkey" => Some(DbType::Valkey),
+        _ => None,
+    }
+}
+
+fn default_port(db_type: &DbType) -> u16 {
+    match db_type {
+        DbType::Postgresql => 5432,
+        DbType::Mysql | DbType::Mariadb => 3306,
+        DbType::MongoDB => 27017,
+        _ => 6379,
+    }
+}
+
+/// Address the agent reaches the container on. Container names resolve on
+/// user-defined networks shared with the agent; otherwise the container's IP
+/// is used, which only works when the agent runs on the host or shares the
+/// network.
+pub fn pick_host(
+    container: &DiscoveredContainer,
+    agent_networks: Option<&BTreeSet<String>>,
+) -> Option<String> {
+    let reachable = |name: &String, ip: &String| {
+        !ip.is_empty() && agent_networks.is_none_or(|nets| nets.contains(name))
+    };
+    let user_defined = |name: &String| !matches!(name.as_str(), "bridge" | "host" | "none");
+
+    if agent_networks.is_some()
+        && container.networks.iter().any(|(n, ip)| reachable(n, ip) && user_defined(n))
+    {
+        return Some(container.name.clone());
+    }
+    container
+        .networks
+        .iter()
+        .find(|(n, ip)| reachable(n, ip))
+        .map(|(_, ip)| ip.clone())
+}
+
+/// Stable id for a discovered database, so backups keep landing on the same
+/// Portabase entry across restarts and container re-creations.
+pub fn stable_id(agent_id: &str, db_type: &DbType, container: &str) -> String {
+    let namespace = Uuid::new_v5(&Uuid::NAMESPACE_URL, b"https://portabase.io/agent/discovery");
+    Uuid::new_v5(&namespace, format!("{agent_id}/{}/{container}", db_type.as_str()).as_bytes()).to_string()
+}
+
+/// Builds a config entry from the container's image, labels and the
+/// credentials the official images are initialised with. Returns why the
+/// container cannot be backed up otherwise.
+pub fn propose(
+    container: &DiscoveredContainer,
+    agent_id: &str,
+    agent_networks: Option<&BTreeSet<String>>,
+) -> std::result::Result<InputDatabaseConfig, String> {
+    let label = |key: &str| container.labels.get(key).map(|v| v.trim()).filter(|v| !v.is_empty());
+    let env = |key: &str| container.env.get(key).map(String::as_str);
+    // MariaDB images read MARIADB_* first and fall back to MYSQL_*.
+    let maria_env = |key: &str| env(&format!("MARIADB_{key}")).or_else(|| env(&format!("MYSQL_{key}")));
+
+    let db_type = match label(TYPE_LABEL) {
+        Some(value) => detect_type(value).ok_or_else(|| format!("unsupported {TYPE_LABEL} '{value}'"))?,
+        None => detect_type(&container.image).ok_or_else(|| format!("unrecognised image {}", container.image))?,
+    };
+
+    let host = pick_host(container, agent_networks)
+        .ok_or_else(|| "not attached to a network the agent can reach".to_string())?;
+
+    let (username, password, database) = match db_type {
+        DbType::Postgresql => {
+            let user = env("POSTGRES_USER").unwrap_or("postgres");
+            let password = match (env("POSTGRES_PASSWORD"), env("POSTGRES_HOST_AUTH_METHOD")) {
+                (Some(p), _) => p,
+                (None, Some("trust")) => "",
+                (None, _) => return Err("POSTGRES_PASSWORD is not set (secrets files are not read)".into()),
+            };
+            (Some(user), Some(password), Some(env("POSTGRES_DB").unwrap_or(user)))
+        }
+        DbType::Mysql | DbType::Mariadb => {
+            let get = |key: &str| if db_type == DbType::Mariadb { maria_env(key) } else { env(&format!("MYSQL_{key}")) };
+            let (user, password) = match (get("ROOT_PASSWORD"), get("USER"), get("PASSWORD")) {
+                (Some(root), _, _) => ("root", root),
+                (None, Some(user), Some(password)) => (user, password),
+                _ if get("ALLOW_EMPTY_ROOT_PASSWORD").or(get("ALLOW_EMPTY_PASSWORD")).is_some() => ("root", ""),
+                _ => return Err("no root or user password in the container environment".into()),
+            };
+            (Some(user), Some(password), get("DATABASE"))
+        }
+        DbType::MongoDB => (
+            env("MONGO_INITDB_ROOT_USERNAME"),
+            env("MONGO_INITDB_ROOT_PASSWORD"),
+            env("MONGO_INITDB_DATABASE"),
+        ),
+        _ => {
+            let password = match db_type {
+                DbType::Valkey => env("VALKEY_PASSWORD"),
+                _ => env("REDIS_PASSWORD"),
+            };
+            (None, password, None)
+        }
+    };
+
+    let database = label(DATABASE_LABEL).or(database);
+    if database.is_none() && matches!(db_type, DbType::Mysql | DbType::Mariadb | DbType::MongoDB) {
+        return Err(format!("no database in the container environment, set the {DATABASE_LABEL} label"));
+    }
+
+    let generated_id = match label(ID_LABEL) {
+        Some(id) if Uuid::parse_str(id).is_ok() => id.to_string(),
+        Some(id) => return Err(format!("{ID_LABEL} '{id}' is not a valid UUID")),
+        None => stable_id(agent_id, &db_type, &container.name),
+    };
+
+    Ok(InputDatabaseConfig {
+        name: label(NAME_LABEL).unwrap_or(&container.name).to_string(),
+        database: database.map(str::to_string),
+        port: Some(default_port(&db_type)),
+        db_type,
+        username: username.map(str::to_string),
+        password: password.map(str::to_string),
+        host: Some(host),
+        generated_id,
+        path: None,
+        max_packet_size: None,
+        volume_name: None,
+        container_name: None,
+        options: None,
+    })
+}
+
+/// Renders discovered entries as a `databases.toml` snippet, with passwords
+/// replaced by [`REDACTED_PASSWORD`].
+pub fn proposal_snippet(entries: &[InputDatabaseConfig]) -> std::result::Result<String, toml::ser::Error> {
+    let databases = entries
+        .iter()
+        .cloned()
+        .map(|mut entry| {
+            if entry.password.as_deref().is_some_and(|p| !p.is_empty()) {
+                entry.password = Some(REDACTED_PASSWORD.to_string());
+            }
+            entry
+        })
+        .collect();
+    toml::to_string(&InputDatabasesConfig { databases })
+}
+
+/// Whether `entries` differ, by id, from the proposal last passed here.
+pub fn proposal_changed(entries: &[InputDatabaseConfig]) -> bool {
+    let ids: BTreeSet<String> = entries.iter().map(|e| e.generated_id.clone()).collect();
+    let mut last = LAST_PROPOSAL.lock().unwrap_or_else(|e| e.into_inner());
+    if *last == ids {
+        return false;
+    }
+    *last = ids;
+    true
+}
+
+/// Appends discovered entries unless the config already covers them: same id,
+/// same name, or same engine at the same host.
+pub fn merge(explicit: &mut Vec<InputDatabaseConfig>, discovered: Vec<InputDatabaseConfig>) {
+    for entry in discovered {
+        let duplicate = explicit.iter().any(|db| {
+            db.generated_id == entry.generated_id
+                || db.name == entry.name
+                || (db.db_type == entry.db_type && db.host.is_some() && db.host == entry.host)
+        });
+        if duplicate {
+            debug!("Discovered database {} is already configured", entry.name);
+        } else {
+            info!("Discovered {} database {} ({})", entry.db_type.as_str(), entry.name, entry.generated_id);
+            explicit.push(entry);
+        }
+    }
+}
+
+async fn agent_networks(docker: &Docker) -> Option<BTreeSet<String>> {
+    let own_id = self_container_id()?;
+    let info = docker.inspect_container(&own_id, None::<InspectContainerOptions>).await.ok()?;
+    Some(info.network_settings?.networks?.into_keys().collect())
+}
+
+/// Scans running containers on the local Docker daemon for supported database
+/// images and returns the entries `mode` accepts.
+pub async fn discover(mode: DiscoveryMode, agent_id: &str) -> Result<Vec<InputDatabaseConfig>> {
+    let docker = client()?;
+    let summaries = docker
+        .list_containers(Some(ListContainersOptions::default()))
+        .await
+        .context("Failed to list containers")?;
+    let networks = agent_networks(&docker).await;
+    let own_id = self_container_id();
+
+    let mut found = Vec::new();
+    for summary in summaries {
+        let Some(id) = summary.id.as_deref() else { continue };
+        if own_id.as_deref() == Some(id) {
+            continue;
+        }
+        let labels = summary.labels.clone().unwrap_or_default();
+        if !mode.accepts(labels.get(BACKUP_LABEL).map(String::as_str)) {
+            continue;
+        }
+        let image = summary.image.as_deref().unwrap_or_default();
+        if !labels.contains_key(TYPE_LABEL) && detect_type(image).is_none() {
+            continue;
+        }
+
+        let info = match docker.inspect_container(id, None::<InspectContainerOptions>).await {
+            Ok(info) => info,
+            Err(e) => {
+                // Containers can stop between the listing and the inspect.
+                warn!("Docker discovery skipped container {}: inspect failed: {}", id, e);
+                continue;
+            }
+        };
+        let Some(container) = DiscoveredContainer::from_inspect(&info) else { continue };
+        match propose(&container, agent_id, networks.as_ref()) {
+            Ok(entry) => found.push(entry),
+            Err(reason) => warn!("Docker discovery skipped container {}: {}", container.name, reason),
+        }
+    }
+    Ok(found)
+}
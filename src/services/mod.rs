@@ -2,6 +2,7 @@ pub mod api;
 pub mod backup;
 pub mod config;
 pub mod cron;
+pub mod discovery;
 pub mod restore;
 pub mod status;
 pub mod storage;
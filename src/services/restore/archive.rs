@@ -5,6 +5,7 @@ use anyhow::Result;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use crate::services::backup::logger::JobLogger;
+use crate::services::backup::manifest::BackupManifest;
 use crate::services::config::DbType;
 use crate::utils::common::choose_restore_path;
 
@@ -14,6 +15,7 @@ impl RestoreService {
         downloaded_file: PathBuf,
         tmp_path: &Path,
         db_type: &DbType,
+        manifest: Option<&BackupManifest>,
         logger: Arc<JobLogger>
     ) -> Result<PathBuf> {
         logger.log("info", "Start preparing backup archive".to_string());
@@ -26,21 +28,28 @@ impl RestoreService {
 
         logger.log("debug", format!("Archive filename: {}", filename));
 
-        let is_legacy = filename.ends_with(".sql") || filename.ends_with(".dump");
+        // Without a manifest, the file name is all there is to go on.
+        let is_legacy = manifest.is_none() && (filename.ends_with(".sql") || filename.ends_with(".dump"));
 
         if is_legacy {
             logger.log("info", "Legacy archive detected, skipping extraction".to_string());
             return Ok(downloaded_file);
         }
 
-        let encrypted = filename.ends_with(".tar.gz.enc");
+        let encrypted = match manifest {
+            Some(m) => m.encryption.is_some(),
+            None => filename.ends_with(".enc"),
+        };
 
         let mut archive = downloaded_file.clone();
 
         if encrypted {
             logger.log("info", "Archive is encrypted, decrypting".to_string());
 
-            let new_name = filename.strip_suffix(".enc").unwrap();
+            let new_name = filename
+                .strip_suffix(".enc")
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{filename}.decrypted"));
 
             let decrypted = tmp_path.join(new_name);
 
@@ -60,10 +69,24 @@ impl RestoreService {
             archive = decrypted;
         }
 
-        if matches!(db_type, DbType::DockerVolume) {
-            let raw_tar = tmp_path.join("volume.tar");
-            crate::utils::compress::gunzip_to_file(archive.as_path(), &raw_tar).await?;
-            logger.log("info", format!("Docker volume archive gunzipped to {}", raw_tar.display()));
+        // These engines restore from the raw tar themselves: volumes and files
+        // keep ownership, permissions and xattrs that a generic extraction would
+        // drop, and the other engines unpack the tar they built at backup time.
+        if matches!(
+            db_type,
+            DbType::DockerVolume
+                | DbType::DockerCompose
+                | DbType::Filesystem
+                | DbType::Duckdb
+                | DbType::Influxdb
+                | DbType::Neo4j
+                | DbType::Cassandra
+                | DbType::Scylladb
+                | DbType::Couchdb
+        ) {
+            let raw_tar = tmp_path.join(format!("{}.tar", db_type.as_str()));
+            crate::utils::compress::decompress_to_file(archive.as_path(), &raw_tar).await?;
+            logger.log("info", format!("Archive decompressed to {}", raw_tar.display()));
             return Ok(raw_tar);
         }
 
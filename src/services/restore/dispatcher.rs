@@ -21,6 +21,8 @@ impl RestoreService {
         };
 
         let expected_size = db.data.restore.size.clone();
+        let expected_sha256 = db.data.restore.sha256.clone();
+        let meta_file = db.data.restore.meta_file.clone();
 
         let service = Self {
             ctx: self.ctx.clone(),
@@ -30,7 +32,7 @@ impl RestoreService {
 
         tokio::spawn(async move {
             if let Err(e) = service
-                .execute_restore(db_cfg, file_to_restore, expected_size)
+                .execute_restore(db_cfg, file_to_restore, meta_file, expected_size, expected_sha256)
                 .await
             {
                 error!("Restore failed: {}", e);
@@ -2,111 +2,123 @@ use super::service::RestoreService;
 
 use anyhow::Result;
 use futures::StreamExt;
-use reqwest::{Client, Url};
+use openssl::sha::Sha256;
+use reqwest::{Client, StatusCode, Url, header};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::io::AsyncWriteExt;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
 use crate::services::backup::logger::JobLogger;
+use crate::utils::disk::human_size;
+use crate::services::backup::manifest::{BackupManifest, MANIFEST_VERSION};
 
-fn human_size(bytes: u64) -> String {
-    if bytes >= 1024 * 1024 {
-        format!("{} MB", bytes / 1024 / 1024)
-    } else if bytes >= 1024 {
-        format!("{} KB", bytes / 1024)
-    } else {
-        format!("{bytes} B")
-    }
-}
+/// Attempts per download, counting the first.
+const MAX_ATTEMPTS: u32 = 6;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest silence on an open download before it counts as dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(120);
 
 impl RestoreService {
+    /// Fetches the manifest stored next to a backup. Failures are logged and
+    /// give `None`, leaving the restore to go by the archive's file name.
+    pub async fn download_manifest(&self, url: &str, logger: Arc<JobLogger>) -> Option<BackupManifest> {
+        let fetched = async {
+            let response = http_client()?.get(url).send().await?.error_for_status()?;
+            let manifest: BackupManifest = serde_json::from_slice(&response.bytes().await?)?;
+            anyhow::Ok(manifest)
+        }
+        .await;
+
+        match fetched {
+            Ok(manifest) => {
+                if manifest.version > MANIFEST_VERSION {
+                    logger.log(
+                        "warn",
+                        format!("Manifest version {} is newer than this agent supports", manifest.version),
+                    );
+                }
+                logger.log(
+                    "info",
+                    format!(
+                        "Manifest: {} backup ({} archive{}) by agent {}",
+                        manifest.engine.as_str(),
+                        manifest.codec.as_str(),
+                        if manifest.encryption.is_some() { ", encrypted" } else { "" },
+                        manifest.agent_version
+                    ),
+                );
+                Some(manifest)
+            }
+            Err(e) => {
+                logger.log("warn", format!("Could not read backup manifest: {}", e));
+                None
+            }
+        }
+    }
+
     pub async fn download_backup(
         &self,
         file_url: &str,
         tmp_path: &Path,
         logger: Arc<JobLogger>,
         expected_size: Option<String>,
+        expected_sha256: Option<String>,
     ) -> Result<PathBuf> {
         logger.log("info", "Start downloading backup archive".to_string());
 
-        let client = Client::new();
-
-        let response = client.get(file_url).send().await?;
-        let status = response.status();
-
-        if !status.is_success() {
-            logger.log("error", "Failed to download".to_string());
-            anyhow::bail!("download failed");
-        }
-
-        let filename_from_header = response
-            .headers()
-            .get(reqwest::header::CONTENT_DISPOSITION)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.split("filename=").nth(1))
-            .map(|f| f.trim_matches('"').to_string());
-
-        let filename_from_url = Url::parse(file_url).ok().and_then(|u| {
-            u.path_segments()?
-                .last()
-                .filter(|s| !s.is_empty())
-                .map(|s| s.to_string())
-        });
-
-        let filename = filename_from_header
-            .or(filename_from_url)
-            .unwrap_or_else(|| "downloaded_file".to_string());
-
-        let path = tmp_path.join(&filename);
+        let client = http_client()?;
 
-        let total = expected_size
+        let expected_size = expected_size
             .as_deref()
             .and_then(|s| s.trim().parse::<u64>().ok())
             .filter(|&n| n > 0);
-
-        logger.log(
-            "info",
-            format!(
-                "Downloading backup '{}' ({})",
-                filename,
-                total.map(human_size).unwrap_or_else(|| "unknown size".to_string())
-            ),
-        );
+        let mut total = expected_size;
 
         let start = Instant::now();
-        let mut file = tokio::fs::File::create(&path).await?;
-        let mut stream = response.bytes_stream();
-        let mut downloaded: u64 = 0;
-        let mut next_pct: u64 = 10;
-
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            file.write_all(&chunk).await?;
-            downloaded += chunk.len() as u64;
-
-            if let Some(total) = total {
-                let pct = (downloaded.saturating_mul(100) / total).min(100);
-                let milestone = pct / 10 * 10;
-                if milestone >= next_pct {
+        let mut partial: Option<Partial> = None;
+        let mut attempt = 1;
+
+        loop {
+            match download_attempt(&client, file_url, tmp_path, &mut partial, &mut total, &logger).await {
+                Ok(()) => break,
+                Err(Interrupted::Retry(e)) if attempt < MAX_ATTEMPTS => {
+                    let delay = retry_delay(attempt);
+                    attempt += 1;
                     logger.log(
-                        "info",
+                        "warn",
                         format!(
-                            "Download progress: {}% ({} / {} bytes)",
-                            milestone, downloaded, total
+                            "Download interrupted: {}; retrying in {}s (attempt {}/{})",
+                            e,
+                            delay.as_secs(),
+                            attempt,
+                            MAX_ATTEMPTS
                         ),
                     );
-                    next_pct = milestone + 10;
+                    tokio::time::sleep(delay).await;
+                }
+                Err(Interrupted::Retry(e)) | Err(Interrupted::Fatal(e)) => {
+                    logger.log("error", format!("Failed to download: {}", e));
+                    return Err(e);
                 }
             }
         }
 
-        file.flush().await?;
+        let Partial { path, downloaded, hasher, .. } = partial.expect("a finished download has a file");
 
-        if downloaded == 0 {
+        if let Some(expected) = expected_size
+            && downloaded != expected
+        {
             logger.log(
-                "warn",
-                format!("Downloaded 0 bytes (status {status}); backup body was empty"),
+                "error",
+                format!("Size mismatch: expected {} bytes, downloaded {}", expected, downloaded),
             );
+            anyhow::bail!("downloaded backup does not match its size");
+        }
+
+        if downloaded == 0 {
+            logger.log("warn", "Downloaded 0 bytes; backup body was empty".to_string());
         }
 
         logger.log(
@@ -119,6 +131,201 @@ impl RestoreService {
             ),
         );
 
+        let sha256 = hex::encode(hasher.finish());
+        match expected_sha256.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+            Some(expected) if expected.eq_ignore_ascii_case(&sha256) => {
+                logger.log("info", format!("Checksum verified (sha256 {sha256})"));
+            }
+            Some(expected) => {
+                logger.log(
+                    "error",
+                    format!("Checksum mismatch: expected sha256 {expected}, got {sha256}"),
+                );
+                anyhow::bail!("downloaded backup does not match its checksum");
+            }
+            None => {
+                logger.log(
+                    "warn",
+                    format!("No checksum provided; skipping verification (sha256 {sha256})"),
+                );
+            }
+        }
+
         Ok(path)
     }
 }
+
+/// A download in progress, kept across attempts so that a retry resumes it.
+struct Partial {
+    path: PathBuf,
+    file: tokio::fs::File,
+    downloaded: u64,
+    hasher: Sha256,
+    next_pct: u64,
+}
+
+impl Partial {
+    async fn restart(&mut self) -> std::io::Result<()> {
+        self.file.set_len(0).await?;
+        self.file.rewind().await?;
+        self.downloaded = 0;
+        self.hasher = Sha256::new();
+        self.next_pct = 10;
+        Ok(())
+    }
+}
+
+/// Why a download attempt stopped before the end of the file.
+enum Interrupted {
+    /// Worth another attempt: the connection dropped or the server is busy.
+    Retry(anyhow::Error),
+    Fatal(anyhow::Error),
+}
+
+fn http_client() -> reqwest::Result<Client> {
+    Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .build()
+}
+
+/// First byte of a `Content-Range: bytes <first>-<last>/<total>` header.
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    let value = response.headers().get(header::CONTENT_RANGE)?.to_str().ok()?;
+    let range = value.trim().strip_prefix("bytes ")?;
+    range.split(['-', '/']).next()?.trim().parse().ok()
+}
+
+fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY
+        .saturating_mul(1 << (attempt - 1).min(16))
+        .min(RETRY_MAX_DELAY)
+}
+
+/// Requests the rest of the file, from where `partial` stopped, and appends
+/// it. Succeeds once `total` bytes are in, or when the body ends and the
+/// total is unknown.
+async fn download_attempt(
+    client: &Client,
+    file_url: &str,
+    tmp_path: &Path,
+    partial: &mut Option<Partial>,
+    total: &mut Option<u64>,
+    logger: &JobLogger,
+) -> Result<(), Interrupted> {
+    let retry = |e: anyhow::Error| Interrupted::Retry(e);
+    let fatal = |e: anyhow::Error| Interrupted::Fatal(e);
+
+    let offset = partial.as_ref().map_or(0, |p| p.downloaded);
+    let mut request = client.get(file_url);
+    if offset > 0 {
+        request = request.header(header::RANGE, format!("bytes={offset}-"));
+    }
+    let response = request.send().await.map_err(|e| retry(e.into()))?;
+    let status = response.status();
+
+    if status == StatusCode::RANGE_NOT_SATISFIABLE && offset > 0 && *total == Some(offset) {
+        return Ok(());
+    }
+    if !status.is_success() {
+        let err = anyhow::anyhow!("download failed (status {status})");
+        let transient = status.is_server_error()
+            || status == StatusCode::REQUEST_TIMEOUT
+            || status == StatusCode::TOO_MANY_REQUESTS;
+        return Err(if transient { retry(err) } else { fatal(err) });
+    }
+
+    let partial = match partial {
+        Some(partial) => {
+            if status != StatusCode::PARTIAL_CONTENT {
+                logger.log("warn", "Server cannot resume downloads, starting over".to_string());
+                partial.restart().await.map_err(|e| fatal(e.into()))?;
+            } else if content_range_start(&response) != Some(offset) {
+                // Appending another range would corrupt the file; the next
+                // attempt fetches it whole.
+                logger.log("warn", "Server resumed at the wrong offset, starting over".to_string());
+                partial.restart().await.map_err(|e| fatal(e.into()))?;
+                return Err(retry(anyhow::anyhow!("response range does not start at byte {offset}")));
+            } else {
+                logger.log("info", format!("Resuming download at byte {}", offset));
+            }
+            partial
+        }
+        None => {
+            let filename = file_name(&response, file_url);
+            if total.is_none() {
+                *total = response.content_length().filter(|&n| n > 0);
+            }
+            logger.log(
+                "info",
+                format!(
+                    "Downloading backup '{}' ({})",
+                    filename,
+                    total.map(human_size).unwrap_or_else(|| "unknown size".to_string())
+                ),
+            );
+            let path = tmp_path.join(&filename);
+            let file = tokio::fs::File::create(&path).await.map_err(|e| fatal(e.into()))?;
+            partial.insert(Partial {
+                path,
+                file,
+                downloaded: 0,
+                hasher: Sha256::new(),
+                next_pct: 10,
+            })
+        }
+    };
+
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| retry(e.into()))?;
+        partial.file.write_all(&chunk).await.map_err(|e| fatal(e.into()))?;
+        partial.hasher.update(&chunk);
+        partial.downloaded += chunk.len() as u64;
+
+        if let Some(total) = *total {
+            let pct = (partial.downloaded.saturating_mul(100) / total).min(100);
+            let milestone = pct / 10 * 10;
+            if milestone >= partial.next_pct {
+                logger.log(
+                    "info",
+                    format!(
+                        "Download progress: {}% ({} / {} bytes)",
+                        milestone, partial.downloaded, total
+                    ),
+                );
+                partial.next_pct = milestone + 10;
+            }
+        }
+    }
+    partial.file.flush().await.map_err(|e| fatal(e.into()))?;
+
+    match *total {
+        Some(total) if partial.downloaded < total => Err(retry(anyhow::anyhow!(
+            "connection closed after {} of {} bytes",
+            partial.downloaded,
+            total
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn file_name(response: &reqwest::Response, file_url: &str) -> String {
+    let filename_from_header = response
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.split("filename=").nth(1))
+        .map(|f| f.trim_matches('"').to_string());
+
+    let filename_from_url = Url::parse(file_url).ok().and_then(|u| {
+        u.path_segments()?
+            .last()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+    });
+
+    filename_from_header
+        .or(filename_from_url)
+        .unwrap_or_else(|| "downloaded_file".to_string())
+}
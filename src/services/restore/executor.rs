@@ -1,6 +1,8 @@
+use super::models::RestoreResult;
 use super::service::RestoreService;
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::DatabaseConfig;
+use crate::utils::disk::{Space, check_space, human_size, margin_option, restore_margin};
 use anyhow::Result;
 use std::sync::Arc;
 use std::time::Instant;
@@ -11,7 +13,9 @@ impl RestoreService {
         &self,
         cfg: DatabaseConfig,
         file_url: String,
+        meta_file: Option<String>,
         expected_size: Option<String>,
+        expected_sha256: Option<String>,
     ) -> Result<()> {
         let logger = Arc::new(JobLogger::new());
         let start = Instant::now();
@@ -23,15 +27,77 @@ impl RestoreService {
 
         logger.log("info", format!("Created temp directory {}", tmp_path.display()));
 
+        // The manifest fills in what the server did not send.
+        let manifest = match meta_file {
+            Some(url) => self.download_manifest(&url, Arc::clone(&logger)).await,
+            None => {
+                logger.log("info", "No manifest for this backup, relying on its file name".to_string());
+                None
+            }
+        };
+        let expected_size = expected_size.or_else(|| manifest.as_ref().map(|m| m.stored_size.to_string()));
+        let expected_sha256 = expected_sha256.or_else(|| manifest.as_ref().map(|m| m.sha256.clone()));
+
+        if let Some(m) = &manifest
+            && m.engine != cfg.db_type
+        {
+            logger.log(
+                "error",
+                format!("Backup was taken from {}, cannot restore into {}", m.engine.as_str(), cfg.db_type.as_str()),
+            );
+            anyhow::bail!("backup engine mismatch");
+        }
+
+        if let Some(size) = expected_size.as_deref().and_then(|s| s.trim().parse::<u64>().ok()) {
+            let encrypted = match &manifest {
+                Some(m) => m.encryption.is_some(),
+                None => file_url.contains(".enc"),
+            };
+            let margin = margin_option(&cfg).ok().flatten().unwrap_or_else(|| restore_margin(encrypted));
+
+            match check_space(tmp_path, size, margin) {
+                Ok(Space::Enough) => {}
+                Ok(Space::Tight { wanted, available }) => logger.log(
+                    "warn",
+                    format!(
+                        "Only {} free in {}, restore may want about {}",
+                        human_size(available),
+                        tmp_path.display(),
+                        human_size(wanted)
+                    ),
+                ),
+                Err(e) => {
+                    logger.log("error", format!("{}, restore aborted", e));
+                    let result = RestoreResult {
+                        generated_id: cfg.generated_id.clone(),
+                        status: "failed".into(),
+                    };
+                    let duration_ms = start.elapsed().as_millis() as f64;
+                    let logs = Arc::try_unwrap(logger)
+                        .unwrap_or_else(|_| JobLogger::new())
+                        .into_entries();
+                    self.send_result(result, logs, duration_ms).await?;
+                    return Err(e.into());
+                }
+            }
+        }
+
         let downloaded = self
-            .download_backup(&file_url, tmp_path, Arc::clone(&logger), expected_size)
+            .download_backup(
+                &file_url,
+                tmp_path,
+                Arc::clone(&logger),
+                expected_size,
+                expected_sha256,
+            )
             .await?;
 
         let backup_file = self
-            .prepare_archive(downloaded, tmp_path, &cfg.db_type, Arc::clone(&logger))
+            .prepare_archive(downloaded, tmp_path, &cfg.db_type, manifest.as_ref(), Arc::clone(&logger))
             .await?;
 
-        let result = self.run_restore(cfg, backup_file, Arc::clone(&logger)).await?;
+        let format = manifest.and_then(|m| m.dump.format);
+        let result = self.run_restore(cfg, backup_file, format, Arc::clone(&logger)).await?;
 
         logger.log("info", "Database restore job finished".to_string());
 
@@ -14,6 +14,7 @@ impl RestoreService {
         &self,
         cfg: DatabaseConfig,
         backup_file: PathBuf,
+        format: Option<String>,
         logger: Arc<JobLogger>,
     ) -> Result<RestoreResult> {
         let generated_id = cfg.generated_id.clone();
@@ -23,7 +24,7 @@ impl RestoreService {
             format!("Preparing restore for database {}", cfg.name),
         );
 
-        let db = DatabaseFactory::create_for_restore(cfg.clone(), &backup_file).await;
+        let db = DatabaseFactory::create_for_restore_with_format(cfg.clone(), &backup_file, format.as_deref()).await;
 
         logger.log(
             "debug",
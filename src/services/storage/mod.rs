@@ -4,7 +4,9 @@ use crate::core::context::Context;
 use crate::services::api::models::agent::status::DatabaseStorage;
 use crate::services::backup::models::{BackupResult, UploadResult};
 use crate::utils::common::BackupMethod;
+use anyhow::Result;
 use async_trait::async_trait;
+use bytes::Bytes;
 use providers::azure_blob;
 use providers::google_cloud_storage;
 use providers::google_drive;
@@ -24,6 +26,18 @@ pub trait StorageProvider: Send + Sync {
         encrypt: Option<bool>,
         backup_storage_id: &str,
     ) -> UploadResult;
+
+    /// Stores a small file such as a backup manifest at `remote_path`, next
+    /// to an uploaded archive.
+    /// `Ok(false)` when the provider has nowhere to put it.
+    async fn upload_sidecar(
+        &self,
+        _config: &DatabaseStorage,
+        _remote_path: &str,
+        _body: Bytes,
+    ) -> Result<bool> {
+        Ok(false)
+    }
 }
 
 /// Factory to create provider instance from storage config
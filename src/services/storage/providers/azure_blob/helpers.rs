@@ -8,7 +8,8 @@ use openssl::sign::Signer;
 use url::Url;
 use azure_core::http::RequestContent;
 use azure_storage_blob::clients::{BlobClient, BlockBlobClient};
-use azure_storage_blob::models::BlockLookupList;
+use azure_storage_blob::models::{BlockBlobClientCommitBlockListOptions, BlockLookupList};
+use crate::utils::stream::Digests;
 use bytes::{Bytes, BytesMut};
 use futures::{Stream, StreamExt};
 use std::pin::Pin;
@@ -133,6 +134,7 @@ pub async fn upload_stream_to_azure(
     blob: &str,
     mut body: ByteStream,
     block_size: usize,
+    digests: Option<&Digests>,
 ) -> Result<()> {
     let url = build_sas_url(resolved, container, blob, SasResource::Blob, "cw")?;
     let blob_client = BlobClient::new(url, None, None).context("blob client")?;
@@ -166,7 +168,12 @@ pub async fn upload_stream_to_azure(
         latest: Some(block_ids),
         ..Default::default()
     };
-    bbc.commit_block_list(block_list.try_into()?, None)
+    // The body has been read by now, so its checksums are final.
+    let options = BlockBlobClientCommitBlockListOptions {
+        metadata: digests.map(|d| d.checksums().metadata()),
+        ..Default::default()
+    };
+    bbc.commit_block_list(block_list.try_into()?, Some(options))
         .await
         .map_err(|e| anyhow!("commit_block_list failed: {e}"))?;
 
@@ -9,10 +9,10 @@ use crate::services::storage::providers::azure_blob::helpers::{BLOCK_SIZE, uploa
 use crate::services::storage::providers::azure_blob::models::AzureBlobProviderConfig;
 use crate::utils::common::BackupMethod;
 use crate::utils::file::{full_file_name, full_file_path};
-use crate::utils::stream::build_stream;
+use crate::utils::stream::open_stream;
 use async_trait::async_trait;
+use bytes::Bytes;
 use std::sync::Arc;
-use tokio::fs;
 use tracing::{error, info};
 
 pub struct AzureBlobProvider {}
@@ -28,33 +28,9 @@ impl StorageProvider for AzureBlobProvider {
         encrypt: Option<bool>,
         _backup_storage_id: &str,
     ) -> UploadResult {
-        let Some(file_path) = result.backup_file else {
-            return UploadResult {
-                storage_id: storage.id.clone(),
-                success: false,
-                error: Some("Missing backup file path".to_string()),
-                remote_file_path: None,
-                total_size: None,
-            };
-        };
-
-        let total_size = match fs::metadata(&file_path).await {
-            Ok(meta) => meta.len(),
-            Err(e) => {
-                error!("Failed to get file size: {}", e);
-                return UploadResult {
-                    storage_id: storage.id.clone(),
-                    success: false,
-                    error: Some(e.to_string()),
-                    remote_file_path: None,
-                    total_size: None,
-                };
-            }
-        };
-
         let encrypt = encrypt.unwrap_or(false);
 
-        let upload = match build_stream(&file_path, encrypt, &ctx.edge_key.master_key_b64).await {
+        let upload = match open_stream(&result, encrypt, &ctx.edge_key.master_key_b64).await {
             Ok(u) => u,
             Err(e) => {
                 error!("Stream build failed: {}", e);
@@ -64,6 +40,7 @@ impl StorageProvider for AzureBlobProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
@@ -77,6 +54,7 @@ impl StorageProvider for AzureBlobProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
@@ -90,11 +68,12 @@ impl StorageProvider for AzureBlobProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
 
-        let file_name = full_file_name(encrypt);
+        let file_name = full_file_name(encrypt, result.codec);
         let remote_file_path = full_file_path(&file_name, storage.folder_name.as_deref());
         info!(
             "Starting block upload to azure blob {}/{}",
@@ -107,6 +86,7 @@ impl StorageProvider for AzureBlobProvider {
             &remote_file_path,
             upload.stream,
             BLOCK_SIZE,
+            Some(&upload.digests),
         )
         .await
         {
@@ -117,7 +97,8 @@ impl StorageProvider for AzureBlobProvider {
                     success: true,
                     error: None,
                     remote_file_path: Some(remote_file_path),
-                    total_size: Some(total_size),
+                    total_size: Some(upload.read.get()),
+                    checksums: Some(upload.digests.checksums()),
                 }
             }
             Err(e) => {
@@ -128,8 +109,22 @@ impl StorageProvider for AzureBlobProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 }
             }
         }
     }
+
+    async fn upload_sidecar(
+        &self,
+        storage: &DatabaseStorage,
+        remote_path: &str,
+        body: Bytes,
+    ) -> anyhow::Result<bool> {
+        let config: AzureBlobProviderConfig = storage.clone().config.try_into()?;
+        let resolved = config.resolve()?;
+        let stream = Box::pin(futures::stream::once(async move { Ok(body) }));
+        upload_stream_to_azure(&resolved, &config.container_name, remote_path, stream, BLOCK_SIZE, None).await?;
+        Ok(true)
+    }
 }
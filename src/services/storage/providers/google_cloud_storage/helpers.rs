@@ -4,7 +4,10 @@ use bytes::Bytes;
 use futures::Stream;
 use futures::StreamExt;
 use google_cloud_auth::credentials::Credentials;
-use google_cloud_storage::client::Storage;
+use google_cloud_storage::client::{Storage, StorageControl};
+use google_cloud_storage::model::Object;
+use google_cloud_wkt::FieldMask;
+use std::collections::HashMap;
 use google_cloud_storage::streaming_source::{SizeHint, StreamingSource};
 use std::pin::Pin;
 
@@ -50,20 +53,60 @@ pub async fn build_client(cfg: &GoogleCloudStorageProviderConfig) -> Result<Stor
     builder.build().await.context("failed to build GCS client")
 }
 
+/// Control-plane client for metadata updates, with the same credentials and
+/// endpoint choice as `build_client`.
+pub async fn build_control_client(
+    cfg: &GoogleCloudStorageProviderConfig,
+) -> Result<StorageControl> {
+    let endpoint = cfg.api_endpoint.as_deref().filter(|s| !s.trim().is_empty());
+    let builder = if let Some(ep) = endpoint {
+        let creds = google_cloud_auth::credentials::anonymous::Builder::new().build();
+        StorageControl::builder()
+            .with_credentials(creds)
+            .with_endpoint(ep.to_string())
+    } else {
+        StorageControl::builder().with_credentials(build_credentials(cfg)?)
+    };
+
+    builder.build().await.context("failed to build GCS control client")
+}
+
+/// Replaces the custom metadata of an uploaded object. Used for checksums,
+/// which are only known once the whole stream has been written.
+pub async fn set_object_metadata(
+    client: &StorageControl,
+    bucket: &str,
+    object: &str,
+    metadata: HashMap<String, String>,
+) -> Result<()> {
+    let object = Object::new()
+        .set_bucket(format!("projects/_/buckets/{bucket}"))
+        .set_name(object)
+        .set_metadata(metadata);
+    client
+        .update_object()
+        .set_object(object)
+        .set_update_mask(FieldMask::default().set_paths(["metadata"]))
+        .send()
+        .await
+        .context("GCS update_object failed")?;
+    Ok(())
+}
+
 /// Bridges `build_stream`'s `Send`-only byte stream into the SDK's `StreamingSource`
 /// (which `send_buffered` requires to be `Send + Sync + 'static`) via a bounded mpsc
-/// channel. Also reports an exact `size_hint`: the SDK picks single-shot vs resumable
-/// upload from `size_hint().upper()` — an unknown bound forces resumable unconditionally
-/// (see `upload_with_client`).
+/// channel. Also reports an exact `size_hint` when the size is known: the SDK picks
+/// single-shot vs resumable upload from `size_hint().upper()` — an unknown bound (streamed
+/// archives) forces resumable unconditionally (see `upload_with_client`).
 pub struct StreamSource {
     rx: tokio::sync::mpsc::Receiver<Result<Bytes, std::io::Error>>,
-    total_size: u64,
+    total_size: Option<u64>,
 }
 
 impl StreamSource {
     pub fn from_stream(
         mut stream: Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>,
-        total_size: u64,
+        total_size: Option<u64>,
     ) -> Self {
         let (tx, rx) = tokio::sync::mpsc::channel(8);
         tokio::spawn(async move {
@@ -86,7 +129,7 @@ impl StreamingSource for StreamSource {
     // Report the exact size so the SDK can choose single-shot uploads. The default
     // impl returns an unknown bound, which forces the resumable path unconditionally.
     async fn size_hint(&self) -> Result<SizeHint, Self::Error> {
-        Ok(SizeHint::with_exact(self.total_size))
+        Ok(self.total_size.map(SizeHint::with_exact).unwrap_or_default())
     }
 }
 
@@ -6,16 +6,16 @@ use crate::services::api::models::agent::status::DatabaseStorage;
 use crate::services::backup::models::{BackupResult, UploadResult};
 use crate::services::storage::StorageProvider;
 use crate::services::storage::providers::google_cloud_storage::helpers::{
-    StreamSource, build_client, upload_with_client,
+    StreamSource, build_client, build_control_client, set_object_metadata, upload_with_client,
 };
 use crate::services::storage::providers::google_cloud_storage::models::GoogleCloudStorageProviderConfig;
 use crate::utils::common::BackupMethod;
 use crate::utils::file::{full_file_name, full_file_path};
-use crate::utils::stream::build_stream;
+use crate::utils::stream::open_stream;
 use async_trait::async_trait;
+use bytes::Bytes;
 use std::sync::Arc;
-use tokio::fs;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub struct GoogleCloudStorageProvider {}
 
@@ -30,33 +30,9 @@ impl StorageProvider for GoogleCloudStorageProvider {
         encrypt: Option<bool>,
         _backup_storage_id: &str,
     ) -> UploadResult {
-        let Some(file_path) = result.backup_file else {
-            return UploadResult {
-                storage_id: storage.id.clone(),
-                success: false,
-                error: Some("Missing backup file path".to_string()),
-                remote_file_path: None,
-                total_size: None,
-            };
-        };
-
-        let total_size = match fs::metadata(&file_path).await {
-            Ok(meta) => meta.len(),
-            Err(e) => {
-                error!("Failed to get file size: {}", e);
-                return UploadResult {
-                    storage_id: storage.id.clone(),
-                    success: false,
-                    error: Some(e.to_string()),
-                    remote_file_path: None,
-                    total_size: None,
-                };
-            }
-        };
-
         let encrypt = encrypt.unwrap_or(false);
 
-        let upload = match build_stream(&file_path, encrypt, &ctx.edge_key.master_key_b64).await {
+        let upload = match open_stream(&result, encrypt, &ctx.edge_key.master_key_b64).await {
             Ok(u) => u,
             Err(e) => {
                 error!("Stream build failed: {}", e);
@@ -66,6 +42,7 @@ impl StorageProvider for GoogleCloudStorageProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
@@ -79,11 +56,12 @@ impl StorageProvider for GoogleCloudStorageProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
 
-        let file_name = full_file_name(encrypt);
+        let file_name = full_file_name(encrypt, result.codec);
         info!("Uploading file {}", file_name);
         let remote_file_path = full_file_path(&file_name, storage.folder_name.as_deref());
 
@@ -97,11 +75,12 @@ impl StorageProvider for GoogleCloudStorageProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
 
-        let source = StreamSource::from_stream(upload.stream, total_size);
+        let source = StreamSource::from_stream(upload.stream, upload.size);
 
         // A custom apiEndpoint (self-hosted / emulator) on a non-443 port trips an
         // upstream SDK bug in the resumable-upload path; force single-shot for it.
@@ -126,12 +105,32 @@ impl StorageProvider for GoogleCloudStorageProvider {
         {
             Ok(_) => {
                 info!("GCS upload successful: {}", remote_file_path);
+                let checksums = upload.digests.checksums();
+                let stored = match build_control_client(&config).await {
+                    Ok(control) => {
+                        set_object_metadata(
+                            &control,
+                            &config.bucket_name,
+                            &remote_file_path,
+                            checksums.metadata(),
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = stored {
+                    warn!(
+                        "Could not store checksums on gs://{}/{}: {:?}",
+                        config.bucket_name, remote_file_path, e
+                    );
+                }
                 UploadResult {
                     storage_id: storage.id.clone(),
                     success: true,
                     error: None,
                     remote_file_path: Some(remote_file_path),
-                    total_size: Some(total_size),
+                    total_size: Some(upload.read.get()),
+                    checksums: Some(checksums),
                 }
             }
             Err(e) => {
@@ -141,9 +140,25 @@ impl StorageProvider for GoogleCloudStorageProvider {
                     success: false,
                     error: Some(e.to_string()),
                     remote_file_path: None,
-                    total_size: Some(total_size),
+                    total_size: Some(upload.read.get()),
+                    checksums: None,
                 }
             }
         }
     }
+
+    async fn upload_sidecar(
+        &self,
+        storage: &DatabaseStorage,
+        remote_path: &str,
+        body: Bytes,
+    ) -> anyhow::Result<bool> {
+        let config: GoogleCloudStorageProviderConfig = storage.clone().config.try_into()?;
+        let client = build_client(&config).await?;
+        let size = body.len() as u64;
+        let source = StreamSource::from_stream(Box::pin(futures::stream::once(async move { Ok(body) })), Some(size));
+        let force_single_shot = config.api_endpoint.as_deref().is_some_and(|s| !s.trim().is_empty());
+        upload_with_client(&client, &config.bucket_name, remote_path, source, force_single_shot).await?;
+        Ok(true)
+    }
 }
@@ -1,6 +1,6 @@
 use crate::services::storage::providers::google_drive::models::GoogleDriveProviderConfig;
 use anyhow::{Context, Result, anyhow};
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use futures::Stream;
 use futures::StreamExt;
 use oauth2::{
@@ -142,9 +142,8 @@ pub async fn upload_stream_to_google_drive(
     config: &GoogleDriveProviderConfig,
     full_path: &str,
     mut content_stream: impl Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static,
-    total_size: u64,
     mime_type: Option<&str>,
-) -> Result<()> {
+) -> Result<String> {
     let path_parts: Vec<&str> = full_path.split('/').filter(|s| !s.is_empty()).collect();
     if path_parts.is_empty() {
         return Err(anyhow::anyhow!("Invalid path: empty"));
@@ -178,7 +177,6 @@ pub async fn upload_stream_to_google_drive(
         .post("https://www.googleapis.com/upload/drive/v3/files?uploadType=resumable")
         .bearer_auth(&token)
         .header("X-Upload-Content-Type", mime)
-        .header("X-Upload-Content-Length", total_size.to_string()) // Helps a lot
         .json(&metadata)
         .send()
         .await
@@ -196,86 +194,112 @@ pub async fn upload_stream_to_google_drive(
         .to_str()?
         .to_string();
 
-    const CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+    const CHUNK_SIZE: usize = 8 * 1024 * 1024;
 
+    // Full chunks are sent as they fill up with an open-ended range; the total
+    // is only known once the stream ends, and goes with the last request.
     let mut uploaded: u64 = 0;
+    let mut buffer = BytesMut::with_capacity(CHUNK_SIZE);
+    let mut done = false;
+
+    while !done {
+        match content_stream.next().await {
+            Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+            Some(Err(e)) => return Err(e).context("Stream error during chunk"),
+            None => done = true,
+        }
 
-    while uploaded < total_size {
-        let chunk_size = (total_size - uploaded).min(CHUNK_SIZE);
+        while !done && buffer.len() >= CHUNK_SIZE {
+            let chunk = buffer.split_to(CHUNK_SIZE).freeze();
+            let len = chunk.len() as u64;
+            put_chunk(&client, &upload_url, format!("bytes {}-{}/*", uploaded, uploaded + len - 1), chunk).await?;
+            uploaded += len;
+            tracing::info!("Uploaded {} bytes", uploaded);
+        }
+    }
 
-        let mut chunk_bytes = Vec::with_capacity(chunk_size as usize);
-        let mut remaining = chunk_size;
+    let chunk = buffer.split().freeze();
+    let total = uploaded + chunk.len() as u64;
+    let content_range = if chunk.is_empty() {
+        format!("bytes */{}", total)
+    } else {
+        format!("bytes {}-{}/{}", uploaded, total - 1, total)
+    };
+    let file = put_chunk(&client, &upload_url, content_range, chunk).await?;
+    tracing::info!("Uploaded {}/{} bytes", total, total);
+
+    file.as_ref()
+        .and_then(|f| f["id"].as_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("Upload finished without a file id"))
+}
 
-        while remaining > 0 {
-            match content_stream.next().await {
-                Some(Ok(bytes)) => {
-                    let to_take = remaining.min(bytes.len() as u64) as usize;
-                    chunk_bytes.extend_from_slice(&bytes[..to_take]);
-                    remaining -= to_take as u64;
+/// Sets custom properties on an uploaded file.
+pub async fn set_app_properties(
+    config: &GoogleDriveProviderConfig,
+    file_id: &str,
+    properties: &std::collections::HashMap<String, String>,
+) -> Result<()> {
+    let token = get_google_drive_token(config).await?;
 
-                    if to_take < bytes.len() {
-                        // TODO : Put remainder back
-                    }
-                }
-                Some(Err(e)) => return Err(e).context("Stream error during chunk"),
-                None => {
-                    if uploaded + chunk_bytes.len() as u64 != total_size {
-                        return Err(anyhow::anyhow!("Stream ended early"));
-                    }
-                }
-            }
-        }
+    let res = Client::new()
+        .patch(format!(
+            "https://www.googleapis.com/drive/v3/files/{}?supportsAllDrives=true",
+            file_id
+        ))
+        .bearer_auth(&token)
+        .json(&json!({ "appProperties": properties }))
+        .send()
+        .await
+        .context("Failed to update file properties")?;
 
-        if chunk_bytes.is_empty() && uploaded < total_size {
-            return Err(anyhow::anyhow!("Unexpected end of stream"));
-        }
+    if !res.status().is_success() {
+        let text = res.text().await.unwrap_or_default();
+        return Err(anyhow!("Property update failed: {}", text));
+    }
+    Ok(())
+}
 
-        let range_end = uploaded + chunk_bytes.len() as u64 - 1;
-        let content_range = if uploaded + chunk_bytes.len() as u64 == total_size {
-            format!("bytes {}-{}/{}", uploaded, range_end, total_size)
-        } else {
-            format!("bytes {}-{}/*", uploaded, range_end)
-        };
-
-        let mut retries = 0;
-        loop {
-            let res = client
-                .put(&upload_url)
-                .header("Content-Range", &content_range)
-                .header("Content-Length", chunk_bytes.len().to_string())
-                .body(chunk_bytes.clone()) // clone is cheap if small; optimize later if needed
-                .send()
-                .await;
-
-            match res {
-                Ok(resp)
-                    if resp.status().is_success()
-                        || resp.status() == StatusCode::PERMANENT_REDIRECT =>
-                {
-                    // 200 or 308 = good
-                    uploaded += chunk_bytes.len() as u64;
-                    tracing::info!("Uploaded {}/{} bytes", uploaded, total_size);
-                    break;
-                }
-                Ok(resp) if resp.status() == StatusCode::TOO_MANY_REQUESTS => {
-                    // Backoff on 429
-                    tokio::time::sleep(std::time::Duration::from_secs(5 * (1 << retries))).await;
-                }
-                Ok(resp) => {
-                    let text = resp.text().await.unwrap_or_default();
-                    return Err(anyhow::anyhow!("Chunk upload failed: {}", text));
+/// Sends one chunk of a resumable upload. The final chunk is answered with
+/// the created file, which is returned.
+async fn put_chunk(client: &Client, upload_url: &str, content_range: String, chunk: Bytes) -> Result<Option<Value>> {
+    let mut retries = 0;
+    loop {
+        let res = client
+            .put(upload_url)
+            .header("Content-Range", &content_range)
+            .header("Content-Length", chunk.len().to_string())
+            .body(chunk.clone())
+            .send()
+            .await;
+
+        match res {
+            Ok(resp)
+                if resp.status().is_success()
+                    || resp.status() == StatusCode::PERMANENT_REDIRECT =>
+            {
+                // 200 or 308 = good
+                if resp.status() == StatusCode::PERMANENT_REDIRECT {
+                    return Ok(None);
                 }
-                Err(e) if e.is_timeout() || e.is_connect() => {
-                    if retries > 5 {
-                        return Err(e).context("Too many retries");
-                    }
-                    retries += 1;
-                    tokio::time::sleep(std::time::Duration::from_secs(2u64.pow(retries))).await;
+                return Ok(resp.json::<Value>().await.ok());
+            }
+            Ok(resp) if resp.status() == StatusCode::TOO_MANY_REQUESTS => {
+                // Backoff on 429
+                tokio::time::sleep(std::time::Duration::from_secs(5 * (1 << retries))).await;
+            }
+            Ok(resp) => {
+                let text = resp.text().await.unwrap_or_default();
+                return Err(anyhow::anyhow!("Chunk upload failed: {}", text));
+            }
+            Err(e) if e.is_timeout() || e.is_connect() => {
+                if retries > 5 {
+                    return Err(e).context("Too many retries");
                 }
-                Err(e) => return Err(e).context("Chunk request failed"),
+                retries += 1;
+                tokio::time::sleep(std::time::Duration::from_secs(2u64.pow(retries))).await;
             }
+            Err(e) => return Err(e).context("Chunk request failed"),
         }
     }
-
-    Ok(())
 }
@@ -5,15 +5,15 @@ use crate::core::context::Context;
 use crate::services::api::models::agent::status::DatabaseStorage;
 use crate::services::backup::models::{BackupResult, UploadResult};
 use crate::services::storage::StorageProvider;
-use crate::services::storage::providers::google_drive::helpers::upload_stream_to_google_drive;
+use crate::services::storage::providers::google_drive::helpers::{set_app_properties, upload_stream_to_google_drive};
 use crate::services::storage::providers::google_drive::models::GoogleDriveProviderConfig;
 use crate::utils::common::BackupMethod;
 use crate::utils::file::{full_file_name, full_file_path};
-use crate::utils::stream::build_stream;
+use crate::utils::stream::open_stream;
 use async_trait::async_trait;
+use bytes::Bytes;
 use std::sync::Arc;
-use tokio::fs;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub struct GoogleDriveProvider {}
 
@@ -28,33 +28,9 @@ impl StorageProvider for GoogleDriveProvider {
         encrypt: Option<bool>,
         _backup_storage_id: &str,
     ) -> UploadResult {
-        let Some(file_path) = result.backup_file else {
-            return UploadResult {
-                storage_id: storage.id.clone(),
-                success: false,
-                error: Some("Missing backup file path".to_string()),
-                remote_file_path: None,
-                total_size: None,
-            };
-        };
-
-        let total_size = match fs::metadata(&file_path).await {
-            Ok(meta) => meta.len(),
-            Err(e) => {
-                error!("Failed to get file size: {}", e);
-                return UploadResult {
-                    storage_id: storage.id.clone(),
-                    success: false,
-                    error: Some(e.to_string()),
-                    remote_file_path: None,
-                    total_size: None,
-                };
-            }
-        };
-
         let encrypt = encrypt.unwrap_or(false);
 
-        let upload = match build_stream(&file_path, encrypt, &ctx.edge_key.master_key_b64).await {
+        let upload = match open_stream(&result, encrypt, &ctx.edge_key.master_key_b64).await {
             Ok(u) => u,
             Err(e) => {
                 error!("Stream build failed: {}", e);
@@ -64,6 +40,7 @@ impl StorageProvider for GoogleDriveProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
@@ -77,11 +54,12 @@ impl StorageProvider for GoogleDriveProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
 
-        let file_name = full_file_name(encrypt);
+        let file_name = full_file_name(encrypt, result.codec);
 
         info!("Uploading file {}", file_name);
 
@@ -91,20 +69,25 @@ impl StorageProvider for GoogleDriveProvider {
             &config,
             &remote_file_path,
             upload.stream,
-            total_size,
             Some("application/octet-stream"),
         )
         .await
         {
-            Ok(_file_id) => {
+            Ok(file_id) => {
                 info!("Google Drive upload successful");
 
+                let checksums = upload.digests.checksums();
+                if let Err(e) = set_app_properties(&config, &file_id, &checksums.metadata()).await {
+                    warn!("Could not store checksums on Google Drive file {}: {}", file_id, e);
+                }
+
                 UploadResult {
                     storage_id: storage.id.clone(),
                     success: true,
                     error: None,
                     remote_file_path: Some(remote_file_path),
-                    total_size: Some(total_size),
+                    total_size: Some(upload.read.get()),
+                    checksums: Some(checksums),
                 }
             }
             Err(e) => {
@@ -115,9 +98,22 @@ impl StorageProvider for GoogleDriveProvider {
                     success: false,
                     error: Some(e.to_string()),
                     remote_file_path: None,
-                    total_size: Some(total_size),
+                    total_size: Some(upload.read.get()),
+                    checksums: None,
                 }
             }
         }
     }
+
+    async fn upload_sidecar(
+        &self,
+        storage: &DatabaseStorage,
+        remote_path: &str,
+        body: Bytes,
+    ) -> anyhow::Result<bool> {
+        let config: GoogleDriveProviderConfig = storage.clone().config.try_into()?;
+        let stream = Box::pin(futures::stream::once(async move { Ok(body) }));
+        upload_stream_to_google_drive(&config, remote_path, stream, Some("application/json")).await?;
+        Ok(true)
+    }
 }
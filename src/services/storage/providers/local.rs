@@ -4,12 +4,11 @@ use crate::services::backup::models::{BackupResult, UploadResult};
 use crate::services::storage::StorageProvider;
 use crate::utils::common::BackupMethod;
 use crate::utils::file::{full_file_name, full_file_path};
-use crate::utils::stream::build_stream;
+use crate::utils::stream::open_stream;
 use crate::utils::tus::upload_to_tus_stream_with_headers;
 use async_trait::async_trait;
 use reqwest::header::{HeaderMap, HeaderValue};
 use std::sync::Arc;
-use tokio::fs;
 use tracing::error;
 
 pub struct LocalProvider;
@@ -25,36 +24,12 @@ impl StorageProvider for LocalProvider {
         encrypt: Option<bool>,
         backup_storage_id: &str,
     ) -> UploadResult {
-        let Some(file_path) = result.backup_file else {
-            return UploadResult {
-                storage_id: storage.id.clone(),
-                success: false,
-                error: Some("File path error".to_string()),
-                remote_file_path: None,
-                total_size: None,
-            };
-        };
-
         let encrypt = encrypt.unwrap_or(false);
 
-        let file_name = full_file_name(encrypt);
+        let file_name = full_file_name(encrypt, result.codec);
         let remote_file_path = full_file_path(&file_name, storage.folder_name.as_deref());
 
-        let total_size = match fs::metadata(&file_path).await {
-            Ok(meta) => meta.len(),
-            Err(e) => {
-                error!("Failed to get file size: {}", e);
-                return UploadResult {
-                    storage_id: storage.id.clone(),
-                    success: false,
-                    error: Some(e.to_string()),
-                    remote_file_path: None,
-                    total_size: None,
-                };
-            }
-        };
-
-        let upload = match build_stream(&file_path, encrypt, &ctx.edge_key.master_key_b64).await {
+        let upload = match open_stream(&result, encrypt, &ctx.edge_key.master_key_b64).await {
             Ok(u) => u,
             Err(e) => {
                 error!("Stream build failed: {}", e);
@@ -64,6 +39,7 @@ impl StorageProvider for LocalProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
@@ -71,10 +47,13 @@ impl StorageProvider for LocalProvider {
         let mut extra_headers = HeaderMap::new();
 
         extra_headers.insert("X-File-Name", HeaderValue::from_str(&file_name).unwrap());
-        extra_headers.insert(
-            "X-File-Size",
-            HeaderValue::from_str(&total_size.to_string()).unwrap(),
-        );
+        // Streamed archives have no size yet; the TUS finalize request reports it.
+        if let Some(size) = upload.size {
+            extra_headers.insert(
+                "X-File-Size",
+                HeaderValue::from_str(&size.to_string()).unwrap(),
+            );
+        }
         extra_headers.insert(
             "X-File-Path",
             HeaderValue::from_str(&remote_file_path).unwrap(),
@@ -99,7 +78,7 @@ impl StorageProvider for LocalProvider {
             upload.stream,
             &tus_endpoint,
             extra_headers,
-            total_size,
+            upload.size,
         )
         .await
         {
@@ -108,7 +87,8 @@ impl StorageProvider for LocalProvider {
                 success: true,
                 error: None,
                 remote_file_path: Some(remote_file_path),
-                total_size: Some(total_size),
+                total_size: Some(upload.read.get()),
+                checksums: Some(upload.digests.checksums()),
             },
             Err(e) => {
                 error!("Local upload failed: {}", e);
@@ -118,6 +98,7 @@ impl StorageProvider for LocalProvider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 }
             }
         }
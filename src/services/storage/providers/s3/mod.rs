@@ -7,8 +7,9 @@ use crate::services::storage::StorageProvider;
 use crate::services::storage::providers::s3::models::S3ProviderConfig;
 use crate::utils::common::BackupMethod;
 use crate::utils::file::{full_file_name, full_file_path};
-use crate::utils::stream::build_stream;
+use crate::utils::stream::{Checksums, open_stream};
 use async_trait::async_trait;
+use bytes::Bytes;
 use aws_config::retry::RetryConfig;
 use aws_sdk_s3 as s3;
 use aws_sdk_s3::config::BehaviorVersion;
@@ -17,13 +18,12 @@ use aws_sdk_s3::config::RequestChecksumCalculation;
 use aws_sdk_s3::config::retry::ReconnectMode;
 use aws_sdk_s3::error::DisplayErrorContext;
 use aws_sdk_s3::primitives::ByteStream;
-use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart, MetadataDirective};
 use futures::StreamExt;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::fs;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub struct S3Provider {}
 
@@ -38,33 +38,9 @@ impl StorageProvider for S3Provider {
         encrypt: Option<bool>,
         _backup_storage_id: &str,
     ) -> UploadResult {
-        let Some(file_path) = result.backup_file else {
-            return UploadResult {
-                storage_id: storage.id.clone(),
-                success: false,
-                error: Some("Missing backup file path".to_string()),
-                remote_file_path: None,
-                total_size: None,
-            };
-        };
-
-        let total_size = match fs::metadata(&file_path).await {
-            Ok(meta) => meta.len(),
-            Err(e) => {
-                error!("Failed to get file size: {}", e);
-                return UploadResult {
-                    storage_id: storage.id.clone(),
-                    success: false,
-                    error: Some(e.to_string()),
-                    remote_file_path: None,
-                    total_size: None,
-                };
-            }
-        };
-
         let encrypt = encrypt.unwrap_or(false);
 
-        let upload = match build_stream(&file_path, encrypt, &ctx.edge_key.master_key_b64).await {
+        let upload = match open_stream(&result, encrypt, &ctx.edge_key.master_key_b64).await {
             Ok(u) => u,
             Err(e) => {
                 error!("Stream build failed: {}", e);
@@ -74,6 +50,7 @@ impl StorageProvider for S3Provider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
@@ -87,69 +64,16 @@ impl StorageProvider for S3Provider {
                     error: Some(e.to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
 
-        let credentials = s3::config::Credentials::new(
-            config.access_key.clone(),
-            config.secret_key.clone(),
-            None,
-            None,
-            "static-creds",
-        );
-
-        let region = Region::new(config.region.clone().unwrap_or("us-east-1".to_string()));
-
-        let endpoint = if let Some(port) = &config.port {
-            if port.trim().is_empty() {
-                format!(
-                    "{}://{}",
-                    if config.ssl { "https" } else { "http" },
-                    config.end_point_url
-                )
-            } else {
-                format!(
-                    "{}://{}:{}",
-                    if config.ssl { "https" } else { "http" },
-                    config.end_point_url,
-                    port
-                )
-            }
-        } else {
-            format!(
-                "{}://{}",
-                if config.ssl { "https" } else { "http" },
-                config.end_point_url
-            )
-        };
-
-        info!("S3 endpoint to {}", &endpoint);
-
-        let retry_config = RetryConfig::standard()
-            .with_max_attempts(5)
-            .with_initial_backoff(Duration::from_millis(200))
-            .with_max_backoff(Duration::from_secs(5))
-            .with_reconnect_mode(ReconnectMode::ReuseAllConnections);
-
-        let sdk_config = s3::config::Builder::new()
-            .retry_config(retry_config)
-            .credentials_provider(credentials)
-            .region(region)
-            .force_path_style(true)
-            // S3-compatible endpoints (MinIO, Garage, RustFS, Synology, ...) reject the
-            // default CRC32 integrity checksums the SDK attaches to multipart uploads.
-            // Only send checksums when the operation actually requires them.
-            .request_checksum_calculation(RequestChecksumCalculation::WhenRequired)
-            .endpoint_url(endpoint)
-            .behavior_version(BehaviorVersion::latest())
-            .build();
-
-        let client = s3::Client::from_conf(sdk_config);
+        let client = build_client(&config);
 
         const PART_SIZE: usize = 100 * 1024 * 1024; // 100 MiB
 
-        let file_name = full_file_name(encrypt);
+        let file_name = full_file_name(encrypt, result.codec);
 
         info!("Uploading file {}", file_name);
 
@@ -178,6 +102,7 @@ impl StorageProvider for S3Provider {
                     error: Some(detail),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
@@ -191,6 +116,7 @@ impl StorageProvider for S3Provider {
                     error: Some("No upload ID returned".to_string()),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 };
             }
         };
@@ -219,6 +145,7 @@ impl StorageProvider for S3Provider {
                         error: Some(format!("Stream error: {}", e)),
                         remote_file_path: None,
                         total_size: None,
+                        checksums: None,
                     };
                 }
             };
@@ -273,6 +200,7 @@ impl StorageProvider for S3Provider {
                             error: Some(detail),
                             remote_file_path: None,
                             total_size: None,
+                            checksums: None,
                         };
                     }
                 }
@@ -295,6 +223,7 @@ impl StorageProvider for S3Provider {
                 error: Some("No parts were uploaded".to_string()),
                 remote_file_path: None,
                 total_size: None,
+                checksums: None,
             };
         }
 
@@ -316,12 +245,23 @@ impl StorageProvider for S3Provider {
                     "Successfully completed multipart upload: {}",
                     remote_file_path
                 );
+                let checksums = upload.digests.checksums();
+                let size = upload.read.get();
+                if let Err(e) =
+                    store_checksums(&client, bucket, &remote_file_path, size, &checksums).await
+                {
+                    warn!(
+                        "Could not store checksums on s3://{}/{}: {}",
+                        bucket, remote_file_path, e
+                    );
+                }
                 UploadResult {
                     storage_id: storage.id.clone(),
                     success: true,
                     error: None,
                     remote_file_path: Some(remote_file_path),
-                    total_size: Some(total_size),
+                    total_size: Some(size),
+                    checksums: Some(checksums),
                 }
             }
             Err(e) => {
@@ -340,8 +280,193 @@ impl StorageProvider for S3Provider {
                     error: Some(detail),
                     remote_file_path: None,
                     total_size: None,
+                    checksums: None,
                 }
             }
         }
     }
+
+    async fn upload_sidecar(
+        &self,
+        storage: &DatabaseStorage,
+        remote_path: &str,
+        body: Bytes,
+    ) -> anyhow::Result<bool> {
+        let config: S3ProviderConfig = storage.clone().config.try_into()?;
+        build_client(&config)
+            .put_object()
+            .bucket(&config.bucket_name)
+            .key(remote_path)
+            .content_type("application/json")
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", DisplayErrorContext(&e)))?;
+        Ok(true)
+    }
+}
+
+fn build_client(config: &S3ProviderConfig) -> s3::Client {
+    let credentials = s3::config::Credentials::new(
+        config.access_key.clone(),
+        config.secret_key.clone(),
+        None,
+        None,
+        "static-creds",
+    );
+
+    let region = Region::new(config.region.clone().unwrap_or("us-east-1".to_string()));
+
+    let endpoint = if let Some(port) = &config.port {
+        if port.trim().is_empty() {
+            format!(
+                "{}://{}",
+                if config.ssl { "https" } else { "http" },
+                config.end_point_url
+            )
+        } else {
+            format!(
+                "{}://{}:{}",
+                if config.ssl { "https" } else { "http" },
+                config.end_point_url,
+                port
+            )
+        }
+    } else {
+        format!(
+            "{}://{}",
+            if config.ssl { "https" } else { "http" },
+            config.end_point_url
+        )
+    };
+
+    info!("S3 endpoint to {}", &endpoint);
+
+    let retry_config = RetryConfig::standard()
+        .with_max_attempts(5)
+        .with_initial_backoff(Duration::from_millis(200))
+        .with_max_backoff(Duration::from_secs(5))
+        .with_reconnect_mode(ReconnectMode::ReuseAllConnections);
+
+    let sdk_config = s3::config::Builder::new()
+        .retry_config(retry_config)
+        .credentials_provider(credentials)
+        .region(region)
+        .force_path_style(true)
+        // S3-compatible endpoints (MinIO, Garage, RustFS, Synology, ...) reject the
+        // default CRC32 integrity checksums the SDK attaches to multipart uploads.
+        // Only send checksums when the operation actually requires them.
+        .request_checksum_calculation(RequestChecksumCalculation::WhenRequired)
+        .endpoint_url(endpoint)
+        .behavior_version(BehaviorVersion::latest())
+        .build();
+
+    s3::Client::from_conf(sdk_config)
+}
+
+/// Largest object a single `CopyObject` accepts.
+const COPY_OBJECT_LIMIT: u64 = 5 * 1024 * 1024 * 1024;
+const COPY_PART_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Multipart uploads fix their metadata when they are created, before the
+/// checksums are known, so the object is copied onto itself with them.
+/// Objects above the `CopyObject` limit are copied part by part.
+async fn store_checksums(
+    client: &s3::Client,
+    bucket: &str,
+    key: &str,
+    size: u64,
+    checksums: &Checksums,
+) -> anyhow::Result<()> {
+    let source = copy_source(bucket, key);
+    if size <= COPY_OBJECT_LIMIT {
+        client
+            .copy_object()
+            .bucket(bucket)
+            .key(key)
+            .copy_source(&source)
+            .metadata_directive(MetadataDirective::Replace)
+            .set_metadata(Some(checksums.metadata()))
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", DisplayErrorContext(&e)))?;
+        return Ok(());
+    }
+
+    let upload_id = client
+        .create_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .set_metadata(Some(checksums.metadata()))
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", DisplayErrorContext(&e)))?
+        .upload_id
+        .ok_or_else(|| anyhow::anyhow!("No upload ID returned"))?;
+
+    let copied = async {
+        let mut parts = Vec::new();
+        for (i, start) in (0..size).step_by(COPY_PART_SIZE as usize).enumerate() {
+            let end = (start + COPY_PART_SIZE).min(size) - 1;
+            let part_number = i as i32 + 1;
+            let resp = client
+                .upload_part_copy()
+                .bucket(bucket)
+                .key(key)
+                .upload_id(&upload_id)
+                .part_number(part_number)
+                .copy_source(&source)
+                .copy_source_range(format!("bytes={start}-{end}"))
+                .send()
+                .await
+                .map_err(|e| anyhow::anyhow!("{}", DisplayErrorContext(&e)))?;
+            let e_tag = resp.copy_part_result.and_then(|r| r.e_tag);
+            parts.push(
+                CompletedPart::builder()
+                    .set_e_tag(e_tag)
+                    .part_number(part_number)
+                    .build(),
+            );
+        }
+        client
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", DisplayErrorContext(&e)))?;
+        anyhow::Ok(())
+    }
+    .await;
+
+    if copied.is_err() {
+        let _ = client
+            .abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .send()
+            .await;
+    }
+    copied
+}
+
+/// `bucket/key` with the key percent-encoded, as `x-amz-copy-source` expects.
+pub fn copy_source(bucket: &str, key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for b in key.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{b:02X}")),
+        }
+    }
+    format!("{bucket}/{encoded}")
 }
@@ -16,6 +16,7 @@ pub struct Settings {
     pub timezone: String,
     pub log: String,
     pub chunk_size: usize, // bytes
+    pub docker_discovery: String,
 }
 
 impl Settings {
@@ -64,7 +65,8 @@ impl Settings {
             pooling: pooling_seconds,
             timezone: tz,
             log: env::var("LOG").unwrap_or_else(|_| "info".into()),
-            chunk_size
+            chunk_size,
+            docker_discovery: env::var("DOCKER_DISCOVERY").unwrap_or_else(|_| "off".into()),
         }
     }
 }
@@ -0,0 +1,106 @@
+use crate::domain::cassandra::connection::{
+    Credentials, data_dir, idempotent_schema, live_table_dir, nodetool_args, snapshot_dirs,
+    sstableloader_command, table_name, tables,
+};
+use crate::services::config::{DatabaseConfig, DbType};
+use crate::tests::db_config;
+
+use serde_json::json;
+use std::path::Path;
+use tempfile::TempDir;
+
+const TABLE_ID: &str = "5a1c395e8f1a11eebc6f1d9b8d3f8f4e";
+
+#[test]
+fn table_name_strips_table_id() {
+    assert_eq!(table_name(&format!("clicks-{TABLE_ID}")), Some("clicks"));
+    assert_eq!(table_name(&format!("page-views-{TABLE_ID}")), Some("page-views"));
+    assert_eq!(table_name("clicks"), None);
+    assert_eq!(table_name("clicks-backup"), None);
+}
+
+#[test]
+fn snapshot_dirs_only_returns_tagged_tables() {
+    let tmp = TempDir::new().unwrap();
+    let ks = tmp.path().join("events");
+    std::fs::create_dir_all(ks.join(format!("clicks-{TABLE_ID}/snapshots/tag-1"))).unwrap();
+    std::fs::create_dir_all(ks.join(format!("views-{TABLE_ID}/snapshots/other"))).unwrap();
+
+    let dirs = snapshot_dirs(tmp.path(), "events", "tag-1");
+    assert_eq!(dirs.len(), 1);
+    assert_eq!(dirs[0].0, "clicks");
+
+    assert_eq!(
+        live_table_dir(tmp.path(), "events", "views"),
+        Some(ks.join(format!("views-{TABLE_ID}")))
+    );
+    assert_eq!(live_table_dir(tmp.path(), "events", "missing"), None);
+}
+
+#[test]
+fn idempotent_schema_adds_if_not_exists_once() {
+    let cql = "CREATE KEYSPACE events WITH replication = {};\n\
+               CREATE TABLE events.clicks (id uuid PRIMARY KEY);\n\
+               CREATE TABLE IF NOT EXISTS events.views (id uuid PRIMARY KEY);";
+    let out = idempotent_schema(cql);
+
+    assert!(out.contains("CREATE KEYSPACE IF NOT EXISTS events"));
+    assert!(out.contains("CREATE TABLE IF NOT EXISTS events.clicks"));
+    assert!(out.contains("CREATE TABLE IF NOT EXISTS events.views"));
+    assert!(!out.contains("IF NOT EXISTS IF NOT EXISTS"));
+}
+
+#[test]
+fn nodetool_args_use_password_file() {
+    let mut cfg = DatabaseConfig {
+        host: "cassandra-0".to_string(),
+        port: 7199,
+        ..db_config(DbType::Cassandra, json!({}))
+    };
+    let anon = Credentials::write(&cfg).unwrap();
+    assert_eq!(nodetool_args(&cfg, &anon), vec!["-h", "cassandra-0", "-p", "7199"]);
+
+    cfg.username = "cassandra".into();
+    cfg.password = "s3cret".into();
+    let creds = Credentials::write(&cfg).unwrap();
+    let args = nodetool_args(&cfg, &creds);
+
+    assert!(args.contains(&"-pwf".to_string()));
+    assert!(!args.iter().any(|a| a.contains("s3cret")));
+    let pwf = creds.nodetool_pwf.as_ref().unwrap();
+    assert_eq!(std::fs::read_to_string(pwf).unwrap(), "cassandra s3cret\n");
+}
+
+#[test]
+fn sstableloader_password_is_not_an_argument() {
+    let mut cfg = DatabaseConfig {
+        host: "cassandra-0".to_string(),
+        ..db_config(DbType::Cassandra, json!({}))
+    };
+    let anon = sstableloader_command(&cfg, Path::new("/tmp/ks/t"));
+    assert_eq!(anon.get_program(), "sstableloader");
+
+    cfg.username = "cassandra".into();
+    cfg.password = "s3cret".into();
+    let cmd = sstableloader_command(&cfg, Path::new("/tmp/ks/t"));
+    assert!(!cmd.get_args().any(|a| a.to_string_lossy().contains("s3cret")));
+    assert!(cmd.get_args().any(|a| a == "cassandra"));
+    assert!(
+        cmd.get_envs()
+            .any(|(k, v)| k == "SSTABLELOADER_PASSWORD" && v == Some("s3cret".as_ref()))
+    );
+}
+
+#[test]
+fn data_dir_and_tables_follow_config() {
+    assert_eq!(
+        data_dir(&db_config(DbType::Scylladb, json!({}))),
+        Path::new("/var/lib/scylla/data")
+    );
+    let mut cfg = db_config(DbType::Cassandra, json!({ "tables": ["clicks"] }));
+    assert_eq!(data_dir(&cfg), Path::new("/var/lib/cassandra/data"));
+    assert_eq!(tables(&cfg), vec!["clicks".to_string()]);
+
+    cfg.path = "/mnt/data".into();
+    assert_eq!(data_dir(&cfg), Path::new("/mnt/data"));
+}
@@ -0,0 +1,163 @@
+use crate::domain::couchdb::connection::{ConflictMode, db_url, filter_databases};
+use crate::domain::couchdb::{backup, restore};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::{DatabaseConfig, DbType};
+use crate::tests::{db_config, init_tracing_for_test};
+
+use serde_json::json;
+use std::sync::Arc;
+use tempfile::TempDir;
+use wiremock::matchers::{body_partial_json, method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn db_url_encodes_database_name() {
+    let mut cfg = DatabaseConfig {
+        port: 5984,
+        host: "couch".into(),
+        ..db_config(DbType::Couchdb, json!({}))
+    };
+    assert_eq!(db_url(&cfg, "a/b+c"), "http://couch:5984/a%2Fb%2Bc");
+
+    cfg.options.insert("tls".into(), json!(true));
+    assert_eq!(db_url(&cfg, "orders"), "https://couch:5984/orders");
+
+    let dbs = vec!["_users".to_string(), "orders".to_string()];
+    assert_eq!(filter_databases(&cfg, dbs.clone()), vec!["orders".to_string()]);
+    cfg.options.insert("include_system_dbs".into(), json!(true));
+    assert_eq!(filter_databases(&cfg, dbs), vec!["_users".to_string(), "orders".to_string()]);
+
+    cfg.options.insert("conflict_mode".into(), json!("nope"));
+    assert!(ConflictMode::from_config(&cfg).is_err());
+    cfg.options.insert("conflict_mode".into(), json!("preserve"));
+    assert_eq!(ConflictMode::from_config(&cfg).unwrap(), ConflictMode::Preserve);
+}
+
+#[tokio::test]
+async fn couchdb_backup_pages_and_restore_skips_conflicts() {
+    init_tracing_for_test();
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "db_name": "orders", "update_seq": "3-abc"
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/orders/_all_docs"))
+        .and(query_param("include_docs", "true"))
+        .and(query_param_is_missing("startkey"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "rows": [
+                { "id": "a", "doc": { "_id": "a", "_rev": "1-x", "n": 1 } },
+                { "id": "b", "doc": { "_id": "b", "_rev": "1-y", "n": 2 } }
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/orders/_all_docs"))
+        .and(query_param("startkey", "\"b\""))
+        .and(query_param("skip", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "rows": [ { "id": "c", "doc": { "_id": "c", "_rev": "1-z", "n": 3 } } ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let cfg = DatabaseConfig {
+        database: "orders".to_string(),
+        host: server.address().ip().to_string(),
+        port: server.address().port(),
+        ..db_config(DbType::Couchdb, json!({ "batch_size": 2, "conflict_mode": "skip" }))
+    };
+    let tmp = TempDir::new().unwrap();
+    let archive = backup::run(cfg.clone(), tmp.path().to_path_buf(), ".tar", Arc::new(JobLogger::new()))
+        .await
+        .unwrap();
+    assert!(archive.exists());
+
+    Mock::given(method("PUT"))
+        .and(path("/orders_copy"))
+        .respond_with(ResponseTemplate::new(412))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/orders_copy/_bulk_docs"))
+        .and(body_partial_json(json!({ "docs": [ { "_id": "a", "n": 1 }, { "_id": "b", "n": 2 } ] })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!([
+            { "id": "a", "ok": true, "rev": "1-x" },
+            { "id": "b", "error": "conflict", "reason": "Document update conflict." }
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/orders_copy/_bulk_docs"))
+        .and(body_partial_json(json!({ "docs": [ { "_id": "c", "n": 3 } ] })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!([
+            { "id": "c", "ok": true, "rev": "1-z" }
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let restore_cfg = DatabaseConfig { database: "orders_copy".into(), ..cfg };
+    restore::run(restore_cfg, archive, Arc::new(JobLogger::new()))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn couchdb_restore_fail_mode_errors_on_conflict() {
+    init_tracing_for_test();
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "update_seq": "1-a" })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/orders/_all_docs"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "rows": [ { "id": "a", "doc": { "_id": "a", "_rev": "1-x" } } ]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/orders"))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/orders/_bulk_docs"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!([
+            { "id": "a", "error": "conflict", "reason": "Document update conflict." }
+        ])))
+        .mount(&server)
+        .await;
+
+    let cfg = DatabaseConfig {
+        database: "orders".to_string(),
+        host: server.address().ip().to_string(),
+        port: server.address().port(),
+        ..db_config(DbType::Couchdb, json!({ "conflict_mode": "fail" }))
+    };
+    let tmp = TempDir::new().unwrap();
+    let archive = backup::run(cfg.clone(), tmp.path().to_path_buf(), ".tar", Arc::new(JobLogger::new()))
+        .await
+        .unwrap();
+
+    let err = restore::run(cfg, archive, Arc::new(JobLogger::new())).await.unwrap_err();
+    assert!(err.to_string().contains("already exist"), "error was: {err}");
+}
@@ -0,0 +1,99 @@
+use crate::domain::docker_compose::project::{
+    PROJECT_LABEL, ProjectContainer, VolumeEntry, parse_depends_on, start_order,
+};
+use bollard::models::{ContainerSummary, ContainerSummaryStateEnum, Volume};
+use std::collections::HashMap;
+
+fn container(name: &str, service: &str, depends_on: &[&str]) -> ProjectContainer {
+    ProjectContainer {
+        id: format!("id-{name}"),
+        name: name.to_string(),
+        service: service.to_string(),
+        depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+        running: true,
+    }
+}
+
+fn names(containers: &[ProjectContainer]) -> Vec<&str> {
+    containers.iter().map(|c| c.name.as_str()).collect()
+}
+
+#[test]
+fn parse_depends_on_keeps_service_names() {
+    assert_eq!(
+        parse_depends_on("db:service_healthy:false,cache:service_started:true"),
+        vec!["db", "cache"]
+    );
+    assert!(parse_depends_on("").is_empty());
+}
+
+#[test]
+fn start_order_puts_dependencies_first() {
+    let containers = vec![
+        container("app-web-1", "web", &["api"]),
+        container("app-api-2", "api", &["db", "cache"]),
+        container("app-api-1", "api", &["db", "cache"]),
+        container("app-db-1", "db", &[]),
+        container("app-cache-1", "cache", &[]),
+    ];
+
+    assert_eq!(
+        names(&start_order(&containers)),
+        vec!["app-cache-1", "app-db-1", "app-api-1", "app-api-2", "app-web-1"]
+    );
+}
+
+#[test]
+fn start_order_survives_cycles_and_unknown_services() {
+    let containers = vec![
+        container("a-1", "a", &["b"]),
+        container("b-1", "b", &["a"]),
+        container("c-1", "c", &["external"]),
+    ];
+
+    let ordered = start_order(&containers);
+    assert_eq!(ordered.len(), 3);
+    assert_eq!(ordered[0].name, "c-1");
+}
+
+#[test]
+fn project_container_from_summary() {
+    let mut labels = HashMap::new();
+    labels.insert("com.docker.compose.service".to_string(), "web".to_string());
+    labels.insert("com.docker.compose.depends_on".to_string(), "db:service_started:false".to_string());
+    let summary = ContainerSummary {
+        id: Some("abc".to_string()),
+        names: Some(vec!["/app-web-1".to_string()]),
+        labels: Some(labels),
+        state: Some(ContainerSummaryStateEnum::EXITED),
+        ..Default::default()
+    };
+
+    let c = ProjectContainer::from_summary(&summary).unwrap();
+    assert_eq!(c.name, "app-web-1");
+    assert_eq!(c.service, "web");
+    assert_eq!(c.depends_on, vec!["db"]);
+    assert!(!c.running);
+}
+
+#[test]
+fn volume_entry_renamed_for_other_project() {
+    let mut labels = HashMap::new();
+    labels.insert(PROJECT_LABEL.to_string(), "app".to_string());
+    labels.insert("com.docker.compose.volume".to_string(), "pgdata".to_string());
+    let volume = Volume {
+        name: "app_pgdata".to_string(),
+        driver: "local".to_string(),
+        labels,
+        ..Default::default()
+    };
+
+    let entry = VolumeEntry::from_volume(&volume);
+    assert_eq!(entry.file, "volumes/app_pgdata.tar");
+    assert_eq!(entry.for_project("app", "app").name, "app_pgdata");
+
+    let copy = entry.for_project("app", "app-verify");
+    assert_eq!(copy.name, "app-verify_pgdata");
+    assert_eq!(copy.labels[PROJECT_LABEL], "app-verify");
+    assert_eq!(copy.labels["com.docker.compose.volume"], "pgdata");
+}
@@ -277,3 +277,233 @@ async fn sweep_removes_labeled_helpers() {
 
     docker.remove_volume(&vol, None::<RemoveVolumeOptions>).await.ok();
 }
+
+mod metadata {
+    use crate::domain::docker_volume::metadata::{VolumeMeta, read_entry, write_entry};
+    use crate::domain::docker_volume::restore::{creation_meta, target_volume};
+    use std::io::{Read, Seek, SeekFrom};
+
+    fn meta() -> VolumeMeta {
+        let mut meta = VolumeMeta {
+            name: "app_pgdata".to_string(),
+            driver: "local".to_string(),
+            ..Default::default()
+        };
+        meta.labels.insert("com.docker.compose.project".to_string(), "app".to_string());
+        meta
+    }
+
+    #[test]
+    fn metadata_entry_precedes_volume_tar() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("volume.tar");
+
+        let mut out = std::fs::File::create(&path).unwrap();
+        write_entry(&mut out, &meta()).unwrap();
+        let mut builder = tar::Builder::new(out);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, "vol/hello.txt", &b"hello"[..]).unwrap();
+        builder.into_inner().unwrap();
+
+        let (read, offset) = read_entry(&path).unwrap().unwrap();
+        assert_eq!(read, meta());
+
+        let mut file = std::fs::File::open(&path).unwrap();
+        file.seek(SeekFrom::Start(offset)).unwrap();
+        let mut rest = tar::Archive::new(file);
+        let mut entry = rest.entries().unwrap().next().unwrap().unwrap();
+        assert_eq!(entry.path().unwrap().to_string_lossy(), "vol/hello.txt");
+        let mut content = String::new();
+        entry.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "hello");
+    }
+
+    #[test]
+    fn legacy_archive_has_no_metadata() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("volume.tar");
+        let mut builder = tar::Builder::new(std::fs::File::create(&path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(0);
+        header.set_cksum();
+        builder.append_data(&mut header, "vol/empty", &b""[..]).unwrap();
+        builder.into_inner().unwrap();
+
+        assert!(read_entry(&path).unwrap().is_none());
+    }
+
+    #[test]
+    fn target_volume_defaults_to_configured_volume() {
+        let mut cfg = super::volume_config("app_pgdata");
+        assert_eq!(target_volume(&cfg), "app_pgdata");
+
+        cfg.options.insert("restore_volume_name".to_string(), "app_pgdata_verify".into());
+        assert_eq!(target_volume(&cfg), "app_pgdata_verify");
+    }
+
+    #[test]
+    fn creation_meta_drops_bind_options_and_owner_labels_for_copies() {
+        let mut bind = meta();
+        bind.driver_opts.insert("device".to_string(), "/srv/pg".to_string());
+        bind.labels.insert("io.portabase.backup".to_string(), "true".to_string());
+        bind.labels.insert("team".to_string(), "data".to_string());
+
+        let (same, dropped) = creation_meta(Some(bind.clone()), true);
+        assert!(!dropped);
+        assert_eq!(same.driver_opts["device"], "/srv/pg");
+        assert_eq!(same.labels, bind.labels);
+
+        let (copy, dropped) = creation_meta(Some(bind), false);
+        assert!(dropped);
+        assert!(copy.driver_opts.is_empty());
+        assert!(!copy.labels.contains_key("com.docker.compose.project"));
+        assert!(!copy.labels.contains_key("io.portabase.backup"));
+        assert_eq!(copy.labels["team"], "data");
+
+        assert_eq!(creation_meta(None, false).0, VolumeMeta::default());
+    }
+}
+
+mod endpoint {
+    use crate::domain::docker_volume::endpoint::{Endpoint, TlsFiles, ssh_args};
+    use std::path::{Path, PathBuf};
+
+    fn with_options(options: serde_json::Value) -> crate::services::config::DatabaseConfig {
+        let mut cfg = super::volume_config("data");
+        cfg.options = serde_json::from_value(options).unwrap();
+        cfg
+    }
+
+    #[test]
+    fn local_without_docker_host() {
+        assert_eq!(Endpoint::from_config(&with_options(serde_json::json!({}))).unwrap(), Endpoint::Local);
+    }
+
+    #[test]
+    fn podman_socket_paths() {
+        let expected = Endpoint::Unix(PathBuf::from("/run/podman/podman.sock"));
+        for host in ["unix:///run/podman/podman.sock", "/run/podman/podman.sock"] {
+            let cfg = with_options(serde_json::json!({ "docker_host": host }));
+            assert_eq!(Endpoint::from_config(&cfg).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn tcp_with_cert_dir() {
+        let cfg = with_options(serde_json::json!({
+            "docker_host": "tcp://10.0.0.5:2376",
+            "docker_cert_path": "/certs/host-a",
+            "docker_tls_ca": "/certs/shared-ca.pem"
+        }));
+        assert_eq!(
+            Endpoint::from_config(&cfg).unwrap(),
+            Endpoint::Tcp {
+                addr: "tcp://10.0.0.5:2376".to_string(),
+                tls: Some(TlsFiles {
+                    ca: PathBuf::from("/certs/shared-ca.pem"),
+                    cert: PathBuf::from("/certs/host-a/cert.pem"),
+                    key: PathBuf::from("/certs/host-a/key.pem"),
+                }),
+            }
+        );
+
+        let partial = with_options(serde_json::json!({
+            "docker_host": "tcp://10.0.0.5:2376",
+            "docker_tls_cert": "/certs/cert.pem"
+        }));
+        assert!(Endpoint::from_config(&partial).is_err());
+    }
+
+    #[test]
+    fn ssh_url_with_custom_socket() {
+        let cfg = with_options(serde_json::json!({
+            "docker_host": "ssh://core@host-b:2222/run/user/1000/podman/podman.sock"
+        }));
+        assert_eq!(
+            Endpoint::from_config(&cfg).unwrap(),
+            Endpoint::Ssh {
+                destination: "core@host-b".to_string(),
+                port: Some(2222),
+                socket: "/run/user/1000/podman/podman.sock".to_string(),
+            }
+        );
+
+        let cfg = with_options(serde_json::json!({ "docker_host": "ssh://host-c" }));
+        assert!(matches!(
+            Endpoint::from_config(&cfg).unwrap(),
+            Endpoint::Ssh { socket, port: None, .. } if socket == "/var/run/docker.sock"
+        ));
+    }
+
+    #[test]
+    fn unsupported_scheme_rejected() {
+        let cfg = with_options(serde_json::json!({ "docker_host": "npipe:////./pipe/docker_engine" }));
+        assert!(Endpoint::from_config(&cfg).is_err());
+    }
+
+    #[test]
+    fn ssh_args_forward_remote_socket() {
+        let args = ssh_args("core@host-b", Some(2222), Some("/keys/id_ed25519"), Path::new("/tmp/x/docker.sock"), "/var/run/docker.sock");
+        assert!(args.windows(2).any(|w| w == ["-p", "2222"]));
+        assert!(args.windows(2).any(|w| w == ["-i", "/keys/id_ed25519"]));
+        assert!(args.windows(2).any(|w| w == ["-L", "/tmp/x/docker.sock:/var/run/docker.sock"]));
+        assert_eq!(args.last().unwrap(), "core@host-b");
+    }
+}
+
+mod consistency {
+    use crate::domain::docker_volume::consistency::{Consistency, first_pass_script, second_pass_script};
+
+    fn with_options(options: serde_json::Value) -> crate::services::config::DatabaseConfig {
+        let mut cfg = super::volume_config("data");
+        cfg.options = serde_json::from_value(options).unwrap();
+        cfg
+    }
+
+    #[test]
+    fn defaults_to_stop() {
+        assert_eq!(Consistency::from_config(&with_options(serde_json::json!({}))).unwrap(), Consistency::Stop);
+    }
+
+    #[test]
+    fn parses_strategies() {
+        let pause = with_options(serde_json::json!({ "consistency": "pause" }));
+        assert_eq!(Consistency::from_config(&pause).unwrap(), Consistency::Pause);
+
+        let two_pass = with_options(serde_json::json!({ "consistency": "two_pass" }));
+        assert_eq!(Consistency::from_config(&two_pass).unwrap(), Consistency::TwoPass);
+
+        let exec = with_options(serde_json::json!({
+            "consistency": "exec",
+            "quiesce_command": "fsfreeze -f /data",
+            "unquiesce_command": "fsfreeze -u /data"
+        }));
+        assert_eq!(
+            Consistency::from_config(&exec).unwrap(),
+            Consistency::Exec {
+                quiesce: "fsfreeze -f /data".to_string(),
+                unquiesce: Some("fsfreeze -u /data".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_or_unknown() {
+        let exec = with_options(serde_json::json!({ "consistency": "exec" }));
+        assert!(Consistency::from_config(&exec).is_err());
+
+        let unknown = with_options(serde_json::json!({ "consistency": "snapshot" }));
+        assert!(Consistency::from_config(&unknown).is_err());
+    }
+
+    #[test]
+    fn two_pass_scripts_share_the_marker() {
+        assert!(first_pass_script().contains("touch /stage/.portabase-pass1"));
+        let second = second_pass_script();
+        assert!(second.contains("-newer /stage/.portabase-pass1"));
+        assert!(second.trim_end().ends_with("rm -f /stage/.portabase-pass1"));
+    }
+}
@@ -0,0 +1,31 @@
+use crate::domain::dragonfly::snapshot::{Strategy, snapshot_files, uses_df_format};
+use crate::services::config::DbType;
+use crate::tests::db_config;
+
+use serde_json::json;
+use tempfile::TempDir;
+
+#[test]
+fn strategy_and_format_from_options() {
+    let default = db_config(DbType::Dragonfly, json!({}));
+    assert_eq!(Strategy::from_config(&default), Strategy::Snapshot);
+    assert!(!uses_df_format(&default));
+
+    let options = json!({ "dragonfly_mode": "replica", "snapshot_format": "df" });
+    let replica = db_config(DbType::Dragonfly, options);
+    assert_eq!(Strategy::from_config(&replica), Strategy::Replica);
+    assert!(uses_df_format(&replica));
+}
+
+#[test]
+fn snapshot_files_match_basename_only() {
+    let tmp = TempDir::new().unwrap();
+    for name in ["snap-1-summary.dfs", "snap-1-0000.dfs", "snap-2.rdb", "dump.rdb"] {
+        std::fs::write(tmp.path().join(name), "").unwrap();
+    }
+
+    let files = snapshot_files(tmp.path(), "snap-1");
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().all(|f| f.file_name().unwrap().to_string_lossy().starts_with("snap-1")));
+    assert!(snapshot_files(&tmp.path().join("missing"), "snap-1").is_empty());
+}
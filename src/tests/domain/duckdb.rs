@@ -0,0 +1,35 @@
+use crate::domain::duckdb::connection::{find_export_dir, quote_literal, wal_path};
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn find_export_dir_locates_nested_load_sql() {
+    let tmp = TempDir::new().unwrap();
+    let nested = tmp.path().join("abc_export");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(nested.join("schema.sql"), "").unwrap();
+    std::fs::write(nested.join("load.sql"), "").unwrap();
+
+    assert_eq!(find_export_dir(tmp.path()), Some(nested));
+}
+
+#[test]
+fn find_export_dir_none_without_load_sql() {
+    let tmp = TempDir::new().unwrap();
+    std::fs::write(tmp.path().join("schema.sql"), "").unwrap();
+
+    assert_eq!(find_export_dir(tmp.path()), None);
+}
+
+#[test]
+fn quote_literal_escapes_single_quotes() {
+    assert_eq!(quote_literal("/tmp/it's"), "'/tmp/it''s'");
+}
+
+#[test]
+fn wal_path_appends_suffix() {
+    assert_eq!(
+        wal_path(Path::new("/data/a.duckdb")),
+        Path::new("/data/a.duckdb.wal")
+    );
+}
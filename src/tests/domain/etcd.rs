@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tempfile::TempDir;
+use testcontainers::core::{IntoContainerPort, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, GenericImage, ImageExt};
+
+use crate::domain::etcd::connection::{auth_env, endpoint, etcdctl_args, restore_args};
+use crate::domain::factory::DatabaseFactory;
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::{DatabaseConfig, DbType};
+use crate::tests::init_tracing_for_test;
+
+fn base_config(host: &str, port: u16) -> DatabaseConfig {
+    DatabaseConfig {
+        name: "Test etcd".to_string(),
+        database: "".to_string(),
+        db_type: DbType::Etcd,
+        username: "".to_string(),
+        password: "".to_string(),
+        port,
+        host: host.to_string(),
+        generated_id: "5e0b7a3c-2c41-4d7e-9a0f-7f1f5c0d8e21".to_string(),
+        path: "".to_string(),
+        max_packet_size: "".to_string(),
+        volume_name: "".to_string(),
+        container_name: None,
+        options: HashMap::new(),
+    }
+}
+
+async fn create_config() -> (ContainerAsync<GenericImage>, DatabaseConfig) {
+    let container = GenericImage::new("quay.io/coreos/etcd", "v3.5.17")
+        .with_exposed_port(2379.tcp())
+        .with_wait_for(WaitFor::message_on_stderr("ready to serve client requests"))
+        .with_cmd([
+            "etcd",
+            "--listen-client-urls=http://0.0.0.0:2379",
+            "--advertise-client-urls=http://0.0.0.0:2379",
+        ])
+        .start()
+        .await
+        .expect("etcd started");
+
+    let host = container.get_host().await.unwrap().to_string();
+    let port = container.get_host_port_ipv4(2379).await.unwrap();
+
+    (container, base_config(&host, port))
+}
+
+#[test]
+fn etcd_endpoint_switches_to_https_with_tls_options() {
+    let mut cfg = base_config("etcd-0", 2379);
+    assert_eq!(endpoint(&cfg), "http://etcd-0:2379");
+
+    cfg.options.insert("ca_cert".into(), "/certs/ca.pem".into());
+    cfg.options.insert("client_cert".into(), "/certs/client.pem".into());
+    cfg.options.insert("client_key".into(), "/certs/client-key.pem".into());
+    assert_eq!(endpoint(&cfg), "https://etcd-0:2379");
+
+    let args = etcdctl_args(&cfg);
+    assert!(args.contains(&"--cacert=/certs/ca.pem".to_string()));
+    assert!(args.contains(&"--cert=/certs/client.pem".to_string()));
+    assert!(args.contains(&"--key=/certs/client-key.pem".to_string()));
+}
+
+#[test]
+fn etcd_credentials_are_passed_via_env() {
+    let mut cfg = base_config("etcd-0", 2379);
+    cfg.username = "root".into();
+    cfg.password = "s3cret".into();
+
+    assert!(etcdctl_args(&cfg).iter().all(|a| !a.contains("s3cret")));
+    let env = auth_env(&cfg);
+    assert!(env.contains(&("ETCDCTL_USER".to_string(), "root".to_string())));
+    assert!(env.contains(&("ETCDCTL_PASSWORD".to_string(), "s3cret".to_string())));
+}
+
+#[test]
+fn etcd_restore_args_forward_cluster_identity() {
+    let mut cfg = base_config("etcd-0", 2379);
+    cfg.options.insert("name".into(), "etcd-0".into());
+    cfg.options.insert("initial_cluster".into(), "etcd-0=http://etcd-0:2380".into());
+
+    let args = restore_args(&cfg);
+    assert_eq!(
+        args,
+        vec![
+            "--name=etcd-0".to_string(),
+            "--initial-cluster=etcd-0=http://etcd-0:2380".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn etcd_ping_test() {
+    init_tracing_for_test();
+
+    let (_container, config) = create_config().await;
+
+    let db = DatabaseFactory::create_for_backup(config).await;
+    assert!(db.ping().await.unwrap_or(false));
+}
+
+#[tokio::test]
+async fn etcd_backup_restore_test() {
+    init_tracing_for_test();
+
+    let (_container, mut config) = create_config().await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let db = DatabaseFactory::create_for_backup(config.clone()).await;
+    let snapshot = db
+        .backup(temp_dir.path(), Arc::new(JobLogger::new()))
+        .await
+        .unwrap();
+    assert!(snapshot.is_file());
+
+    let data_dir = temp_dir.path().join("restored.etcd");
+    config.path = data_dir.to_string_lossy().to_string();
+    let db = DatabaseFactory::create_for_restore(config, &snapshot).await;
+    db.restore(&snapshot, Arc::new(JobLogger::new())).await.unwrap();
+
+    assert!(data_dir.join("member").is_dir());
+}
@@ -0,0 +1,114 @@
+use crate::domain::factory::Database;
+use crate::domain::filesystem::connection::{RestoreMode, SymlinkPolicy};
+use crate::domain::filesystem::database::FilesystemDatabase;
+use crate::domain::filesystem::filter::{Filter, matches};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::{DatabaseConfig, DbType};
+use crate::tests::db_config;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn write(root: &Path, rel: &str, content: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn glob_matching() {
+    assert!(matches("*.log", "a/b/app.log"));
+    assert!(!matches("*.log", "a/b/app.log.1"));
+    assert!(matches("cache/**", "cache/x/y.bin"));
+    assert!(!matches("cache/*", "cache/x/y.bin"));
+    assert!(matches("a/**/b.txt", "a/b.txt"));
+    assert!(matches("a/**/b.txt", "a/x/y/b.txt"));
+    assert!(matches("data/file?.db", "data/file1.db"));
+    assert!(!matches("data/file?.db", "data/file10.db"));
+}
+
+#[test]
+fn filter_without_includes_keeps_everything_not_excluded() {
+    let filter = Filter::new(vec![], vec!["*.tmp".to_string()]);
+    assert!(filter.is_included("any/file"));
+    assert!(filter.is_excluded("any/file.tmp"));
+}
+
+#[test]
+fn options_parse_and_reject_unknown_values() {
+    let cfg = db_config(DbType::Filesystem, json!({ "symlinks": "follow", "restore_mode": "merge" }));
+    assert_eq!(SymlinkPolicy::from_config(&cfg).unwrap(), SymlinkPolicy::Follow);
+    assert_eq!(RestoreMode::from_config(&cfg).unwrap(), RestoreMode::Merge);
+
+    let cfg = db_config(DbType::Filesystem, json!({}));
+    assert_eq!(SymlinkPolicy::from_config(&cfg).unwrap(), SymlinkPolicy::Preserve);
+    assert_eq!(RestoreMode::from_config(&cfg).unwrap(), RestoreMode::Wipe);
+
+    let cfg = db_config(DbType::Filesystem, json!({ "symlinks": "bogus", "restore_mode": "merg" }));
+    assert!(SymlinkPolicy::from_config(&cfg).is_err());
+    assert!(RestoreMode::from_config(&cfg).is_err());
+}
+
+#[tokio::test]
+async fn backup_and_wipe_restore_roundtrip() {
+    let source = TempDir::new().unwrap();
+    write(source.path(), "keep/a.txt", "a");
+    write(source.path(), "keep/nested/b.txt", "b");
+    write(source.path(), "scratch.tmp", "tmp");
+    write(source.path(), "cache/blob", "cache");
+    std::os::unix::fs::symlink("keep/a.txt", source.path().join("link")).unwrap();
+
+    let options = json!({ "exclude": ["*.tmp", "cache"], "preserve_ownership": false });
+    let backup_dir = TempDir::new().unwrap();
+    let db = FilesystemDatabase::new(DatabaseConfig {
+        path: source.path().to_string_lossy().to_string(),
+        ..db_config(DbType::Filesystem, options.clone())
+    });
+    let archive = db.backup(backup_dir.path(), Arc::new(JobLogger::new())).await.unwrap();
+    assert!(archive.to_string_lossy().ends_with(".tar"));
+
+    let target = TempDir::new().unwrap();
+    write(target.path(), "stale.txt", "old");
+    let db = FilesystemDatabase::new(DatabaseConfig {
+        path: target.path().to_string_lossy().to_string(),
+        ..db_config(DbType::Filesystem, options)
+    });
+    db.restore(&archive, Arc::new(JobLogger::new())).await.unwrap();
+
+    let root = target.path();
+    assert_eq!(std::fs::read_to_string(root.join("keep/nested/b.txt")).unwrap(), "b");
+    assert!(std::fs::symlink_metadata(root.join("link")).unwrap().file_type().is_symlink());
+    assert!(!root.join("stale.txt").exists());
+    assert!(!root.join("scratch.tmp").exists());
+    assert!(!root.join("cache").exists());
+    assert_eq!(std::fs::read_dir(root).unwrap().count(), 2);
+}
+
+#[tokio::test]
+async fn merge_restore_keeps_existing_files() {
+    let source = TempDir::new().unwrap();
+    write(source.path(), "conf/app.conf", "new");
+    write(source.path(), "conf/other.txt", "ignored");
+
+    let options = json!({ "include": ["*.conf"], "restore_mode": "merge", "preserve_ownership": false });
+    let backup_dir = TempDir::new().unwrap();
+    let db = FilesystemDatabase::new(DatabaseConfig {
+        path: source.path().to_string_lossy().to_string(),
+        ..db_config(DbType::Filesystem, options.clone())
+    });
+    let archive = db.backup(backup_dir.path(), Arc::new(JobLogger::new())).await.unwrap();
+
+    let target = TempDir::new().unwrap();
+    write(target.path(), "conf/app.conf", "old");
+    write(target.path(), "local.txt", "mine");
+    let db = FilesystemDatabase::new(DatabaseConfig {
+        path: target.path().to_string_lossy().to_string(),
+        ..db_config(DbType::Filesystem, options)
+    });
+    db.restore(&archive, Arc::new(JobLogger::new())).await.unwrap();
+
+    assert_eq!(std::fs::read_to_string(target.path().join("conf/app.conf")).unwrap(), "new");
+    assert!(!target.path().join("conf/other.txt").exists());
+    assert!(target.path().join("local.txt").exists());
+}
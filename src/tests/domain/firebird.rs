@@ -1,7 +1,7 @@
 use crate::domain::factory::DatabaseFactory;
 use crate::services::config::{DatabaseConfig, DbType};
 use crate::tests::init_tracing_for_test;
-use crate::utils::compress::{compress_to_tar_gz_large, decompress_large_tar_gz};
+use crate::utils::compress::{Compression, compress_archive, decompress_large_tar_gz};
 use std::path::PathBuf;
 use std::time::Duration;
 use tempfile::TempDir;
@@ -77,7 +77,7 @@ async fn firebird_backup_restore_test() {
 
     assert!(file_path.is_file());
 
-    let compression = compress_to_tar_gz_large(&file_path, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await.unwrap();
+    let compression = compress_archive(&file_path, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await.unwrap();
     assert!(compression.compressed_path.is_file());
 
     let files = decompress_large_tar_gz(
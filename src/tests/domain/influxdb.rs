@@ -0,0 +1,137 @@
+use crate::domain::factory::DatabaseFactory;
+use crate::domain::influxdb::connection::{
+    backup_args, bucket_id, bucket_missing, delete_bucket_args, find_backup_dir, influx_env, list_bucket_args,
+    rename_bucket_args, replaced_bucket, restore_args, restore_bucket, staging_bucket,
+};
+use crate::services::config::{DatabaseConfig, DbType};
+use crate::tests::{db_config, init_tracing_for_test};
+
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn influx_env_carries_token_and_org() {
+    let cfg = DatabaseConfig {
+        host: "influxdb".to_string(),
+        port: 8086,
+        ..db_config(DbType::Influxdb, json!({ "token": "op-token", "org": "ops" }))
+    };
+    let env = influx_env(&cfg);
+
+    assert!(env.contains(&("INFLUX_HOST".into(), "http://influxdb:8086".into())));
+    assert!(env.contains(&("INFLUX_TOKEN".into(), "op-token".into())));
+    assert!(env.contains(&("INFLUX_ORG".into(), "ops".into())));
+}
+
+#[test]
+fn influx_token_falls_back_to_password() {
+    let mut cfg = db_config(DbType::Influxdb, json!({}));
+    cfg.password = "from-password".into();
+
+    assert!(influx_env(&cfg).contains(&("INFLUX_TOKEN".into(), "from-password".into())));
+}
+
+#[test]
+fn backup_and_restore_args_select_bucket() {
+    let mut cfg = db_config(DbType::Influxdb, json!({}));
+    assert!(backup_args(&cfg).is_empty());
+    cfg.database = "telegraf".into();
+    assert_eq!(backup_args(&cfg), vec!["--bucket=telegraf"]);
+
+    let cfg = DatabaseConfig {
+        database: "telegraf".into(),
+        ..db_config(DbType::Influxdb, json!({ "restore_bucket": "telegraf_restored", "skip_verify": true }))
+    };
+    assert_eq!(
+        restore_args(&cfg, restore_bucket(&cfg)),
+        vec!["--skip-verify", "--bucket=telegraf", "--new-bucket=telegraf_restored"]
+    );
+
+    let full = DatabaseConfig {
+        database: "telegraf".into(),
+        ..db_config(DbType::Influxdb, json!({ "full_restore": true }))
+    };
+    assert_eq!(restore_args(&full, None), vec!["--full"]);
+}
+
+#[test]
+fn in_place_restore_goes_through_a_staging_bucket() {
+    let cfg = DatabaseConfig {
+        database: "telegraf".into(),
+        ..db_config(DbType::Influxdb, json!({ "skip_verify": true }))
+    };
+    assert_eq!(replaced_bucket(&cfg), Some("telegraf"));
+
+    let staging = staging_bucket("telegraf");
+    assert!(staging.starts_with("telegraf_restore_"));
+    assert_ne!(staging, staging_bucket("telegraf"));
+    assert_eq!(
+        restore_args(&cfg, Some(&staging)),
+        vec!["--skip-verify".to_string(), "--bucket=telegraf".to_string(), format!("--new-bucket={staging}")]
+    );
+
+    assert_eq!(
+        list_bucket_args(&cfg, "telegraf"),
+        vec!["bucket", "list", "--name=telegraf", "--json", "--skip-verify"]
+    );
+    assert_eq!(
+        delete_bucket_args(&cfg, "telegraf"),
+        vec!["bucket", "delete", "--name=telegraf", "--skip-verify"]
+    );
+    assert_eq!(
+        rename_bucket_args(&cfg, "0a1b2c", "telegraf"),
+        vec!["bucket", "update", "--id=0a1b2c", "--name=telegraf", "--skip-verify"]
+    );
+
+    for options in [json!({ "restore_bucket": "copy" }), json!({ "full_restore": true })] {
+        let cfg = DatabaseConfig { database: "telegraf".into(), ..db_config(DbType::Influxdb, options) };
+        assert_eq!(replaced_bucket(&cfg), None);
+    }
+    assert_eq!(replaced_bucket(&db_config(DbType::Influxdb, json!({}))), None);
+}
+
+#[test]
+fn bucket_lookup_reads_list_output() {
+    let list = r#"[{"id": "0a1b2c", "name": "telegraf", "retentionRules": []}]"#;
+    assert_eq!(bucket_id(list, "telegraf").unwrap(), Some("0a1b2c".to_string()));
+    assert_eq!(bucket_id("[]", "telegraf").unwrap(), None);
+    assert!(bucket_id("Error: unauthorized", "telegraf").is_err());
+
+    let missing = r#"Error: failed to list buckets: 404 Not Found: bucket "telegraf" not found"#;
+    assert!(bucket_missing(missing, "telegraf"));
+    assert!(!bucket_missing("Error: 401 Unauthorized: token not found", "telegraf"));
+}
+
+#[test]
+fn find_backup_dir_locates_manifest() {
+    let tmp = TempDir::new().unwrap();
+    let nested = tmp.path().join("abc_export");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(nested.join("20240101T000000Z.manifest"), "{}").unwrap();
+
+    assert_eq!(find_backup_dir(tmp.path()), Some(nested));
+    assert_eq!(find_backup_dir(&tmp.path().join("abc_export").join("missing")), None);
+}
+
+#[tokio::test]
+async fn influxdb_ping_uses_health_endpoint() {
+    init_tracing_for_test();
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .and(header("Authorization", "Token op-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "pass" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut cfg = db_config(DbType::Influxdb, json!({ "token": "op-token" }));
+    cfg.host = server.address().ip().to_string();
+    cfg.port = server.address().port();
+
+    let db = DatabaseFactory::create_for_backup(cfg).await;
+    assert!(db.ping().await.unwrap());
+}
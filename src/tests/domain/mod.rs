@@ -8,3 +8,12 @@ mod valkey;
 mod firebird;
 mod mssql;
 mod docker_volume;
+mod docker_compose;
+mod etcd;
+mod duckdb;
+mod couchdb;
+mod influxdb;
+mod neo4j;
+mod cassandra;
+mod dragonfly;
+mod filesystem;
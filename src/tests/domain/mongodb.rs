@@ -100,3 +100,49 @@ async fn mongodb_backup_restore_test() {
         }
     }
 }
+
+mod exec_tests {
+    use crate::domain::mongodb::exec::{dump_command, local_uri, restore_command};
+    use crate::services::config::{DatabaseConfig, DbType};
+
+    fn cfg() -> DatabaseConfig {
+        DatabaseConfig {
+            name: "mongo".to_string(),
+            database: "app".to_string(),
+            db_type: DbType::MongoDB,
+            username: "root".to_string(),
+            password: "pw".to_string(),
+            port: 37017,
+            host: "mongo.example".to_string(),
+            generated_id: "8d4e1b7c-1f9e-4d8a-9f4e-2a0b7e6c5d31".to_string(),
+            path: String::new(),
+            max_packet_size: String::new(),
+            volume_name: String::new(),
+            container_name: Some("mongo-1".to_string()),
+            options: Default::default(),
+        }
+    }
+
+    #[test]
+    fn uri_targets_server_inside_container() {
+        let uri = local_uri(&cfg()).unwrap();
+        assert!(uri.contains("@localhost:37017/app"));
+        assert!(!uri.contains("mongo.example"));
+
+        let default_port = DatabaseConfig { port: 0, ..cfg() };
+        assert!(local_uri(&default_port).unwrap().contains("@localhost:27017/app"));
+    }
+
+    #[test]
+    fn archive_streams_over_stdio() {
+        let dump = dump_command(&cfg()).unwrap();
+        assert!(dump.args.contains(&"--archive".to_string()));
+
+        let dry = restore_command(&cfg(), None).unwrap();
+        assert!(dry.args.contains(&"--dryRun".to_string()));
+
+        let restore = restore_command(&cfg(), Some("legacy")).unwrap();
+        assert!(restore.args.contains(&"--nsFrom=legacy.*".to_string()));
+        assert!(restore.args.contains(&"--nsTo=app.*".to_string()));
+    }
+}
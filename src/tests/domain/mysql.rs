@@ -1,7 +1,7 @@
 use crate::domain::factory::DatabaseFactory;
 use crate::services::config::{DatabaseConfig, DbType};
 use crate::tests::init_tracing_for_test;
-use crate::utils::compress::{compress_to_tar_gz_large, decompress_large_tar_gz};
+use crate::utils::compress::{Compression, compress_archive, decompress_large_tar_gz};
 use oauth2::url;
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -66,7 +66,7 @@ async fn mysql_backup_restore_test() {
 
     assert!(file_path.is_file());
 
-    let compression = compress_to_tar_gz_large(&file_path, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await.unwrap();
+    let compression = compress_archive(&file_path, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await.unwrap();
     assert!(compression.compressed_path.is_file());
 
     let files = decompress_large_tar_gz(compression.compressed_path.as_path(), temp_dir.path())
@@ -96,3 +96,58 @@ async fn mysql_backup_restore_test() {
         }
     }
 }
+
+mod exec_tests {
+    use crate::domain::mysql::exec::{Flavor, dump_command, recreate_command, restore_command};
+    use crate::services::config::{DatabaseConfig, DbType};
+
+    fn cfg() -> DatabaseConfig {
+        DatabaseConfig {
+            name: "shop".to_string(),
+            database: "shop".to_string(),
+            db_type: DbType::Mysql,
+            username: "root".to_string(),
+            password: "pw".to_string(),
+            port: 0,
+            host: String::new(),
+            generated_id: "0f1bb8f2-35a0-4c91-8098-e36873d3ce31".to_string(),
+            path: String::new(),
+            max_packet_size: "512M".to_string(),
+            volume_name: String::new(),
+            container_name: Some("shop-db".to_string()),
+            options: Default::default(),
+        }
+    }
+
+    #[test]
+    fn flavors_use_their_own_clients() {
+        let mysql = dump_command(&cfg(), Flavor::Mysql);
+        assert_eq!(mysql.args[0], "mysqldump");
+        assert!(mysql.args.contains(&"--set-gtid-purged=OFF".to_string()));
+        assert_eq!(mysql.args.last().map(String::as_str), Some("shop"));
+
+        let maria = dump_command(&cfg(), Flavor::Mariadb);
+        assert_eq!(maria.args[0], "mariadb-dump");
+        assert!(!maria.args.contains(&"--set-gtid-purged=OFF".to_string()));
+        assert_eq!(restore_command(&cfg(), Flavor::Mariadb).args[0], "mariadb");
+    }
+
+    #[test]
+    fn password_goes_through_mysql_pwd() {
+        for cmd in [dump_command(&cfg(), Flavor::Mysql), recreate_command(&cfg(), Flavor::Mysql)] {
+            assert_eq!(cmd.env, vec!["MYSQL_PWD=pw".to_string()]);
+            assert!(!cmd.args.contains(&"pw".to_string()));
+            assert!(!cmd.args.contains(&"--host".to_string()));
+        }
+    }
+
+    #[test]
+    fn recreate_escapes_backticks() {
+        let cfg = DatabaseConfig { database: "sh`op".to_string(), ..cfg() };
+        let cmd = recreate_command(&cfg, Flavor::Mysql);
+        assert_eq!(
+            cmd.args.last().map(String::as_str),
+            Some("DROP DATABASE IF EXISTS `sh``op`; CREATE DATABASE `sh``op`;")
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::domain::docker_volume::docker::{client, exec_in_container};
+use crate::domain::factory::DatabaseFactory;
+use crate::domain::neo4j::connection::{
+    database_name, dump_script, find_artifact, is_online, online_backup_script, restore_script,
+};
+use crate::services::backup::logger::JobLogger;
+use crate::services::config::{DatabaseConfig, DbType};
+use crate::tests::db_config;
+
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use testcontainers::core::{Mount, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{GenericImage, ImageExt};
+
+#[test]
+fn database_name_defaults_and_rejects_shell_chars() {
+    let mut cfg = db_config(DbType::Neo4j, json!({}));
+    assert_eq!(database_name(&cfg).unwrap(), "neo4j");
+    cfg.database = "movies-2".into();
+    assert_eq!(database_name(&cfg).unwrap(), "movies-2");
+    cfg.database = "x; rm -rf /".into();
+    assert!(database_name(&cfg).is_err());
+}
+
+#[test]
+fn scripts_target_the_mounted_volume() {
+    let dump = dump_script("neo4j");
+    assert!(dump.contains("server.directories.data=/vol"));
+    assert!(dump.contains("neo4j-admin database dump neo4j --to-path=/tmp/portabase-neo4j"));
+
+    let load = restore_script("movies", "movies.dump");
+    assert!(load.contains("neo4j-admin database load movies --from-path=/tmp/portabase-neo4j"));
+    assert!(load.contains("--overwrite-destination=true"));
+    assert!(load.ends_with("chown -R \"$(stat -c %u:%g /vol)\" /vol"));
+
+    let restore = restore_script("movies", "neo4j-2024-01-01T00-00-00.backup");
+    assert!(restore.contains(
+        "neo4j-admin database restore --from-path=/tmp/portabase-neo4j/neo4j-2024-01-01T00-00-00.backup"
+    ));
+}
+
+#[test]
+fn online_mode_uses_backup_port() {
+    let cfg = DatabaseConfig {
+        host: "neo4j".to_string(),
+        port: 6362,
+        ..db_config(DbType::Neo4j, json!({ "mode": "online" }))
+    };
+    assert!(is_online(&cfg));
+    assert!(!is_online(&db_config(DbType::Neo4j, json!({}))));
+
+    let script = online_backup_script(&cfg, "neo4j").unwrap();
+    assert!(script.ends_with(
+        "neo4j-admin database backup --from=neo4j:6362 --to-path=/tmp/portabase-neo4j neo4j"
+    ));
+
+    let mut hostile = cfg.clone();
+    hostile.host = "neo4j; rm -rf /".to_string();
+    assert!(online_backup_script(&hostile, "neo4j").is_err());
+}
+
+#[test]
+fn find_artifact_locates_nested_dump() {
+    let tmp = TempDir::new().unwrap();
+    let nested = tmp.path().join("portabase-neo4j");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(nested.join("neo4j.dump"), "").unwrap();
+
+    assert_eq!(find_artifact(tmp.path()), Some(nested.join("neo4j.dump")));
+}
+
+/// Runs a Cypher statement in the server container once it accepts
+/// connections again, returning its output.
+async fn cypher(container: &str, statement: &str) -> String {
+    let docker = client().expect("docker daemon required");
+    let command = format!("cypher-shell --format plain \"{statement}\"");
+    for _ in 0..60 {
+        if let Ok((0, output)) = exec_in_container(&docker, container, &command).await {
+            return output;
+        }
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+    panic!("Neo4j in {container} did not answer: {statement}");
+}
+
+#[tokio::test]
+async fn neo4j_dump_and_load_round_trip() {
+    let suffix = &uuid::Uuid::new_v4().to_string()[..8];
+    let volume = format!("portabase-test-neo4j-{suffix}");
+    let name = format!("portabase-test-neo4j-{suffix}");
+
+    let container = GenericImage::new("neo4j", "5-community")
+        .with_wait_for(WaitFor::message_on_stdout("Started."))
+        .with_env_var("NEO4J_AUTH", "none")
+        .with_mount(Mount::volume_mount(&volume, "/data"))
+        .with_container_name(&name)
+        .start()
+        .await
+        .expect("neo4j started");
+
+    cypher(&name, "CREATE (:Probe {v: 'before'})").await;
+
+    let cfg = DatabaseConfig {
+        volume_name: volume.clone(),
+        container_name: Some(name.clone()),
+        generated_id: uuid::Uuid::new_v4().to_string(),
+        ..db_config(DbType::Neo4j, json!({}))
+    };
+
+    let tmp = TempDir::new().unwrap();
+    let logger = Arc::new(JobLogger::new());
+    let db = DatabaseFactory::create_for_backup(cfg.clone()).await;
+    let file = db.backup(tmp.path(), logger.clone()).await.expect("backup succeeded");
+    assert!(file.is_file());
+
+    cypher(&name, "CREATE (:Probe {v: 'after'})").await;
+
+    let db = DatabaseFactory::create_for_restore(cfg, &file).await;
+    db.restore(&file, logger).await.expect("restore succeeded");
+
+    let output = cypher(&name, "MATCH (p:Probe) RETURN p.v ORDER BY p.v").await;
+    assert!(output.contains("before"), "output: {output}");
+    assert!(!output.contains("after"), "output: {output}");
+
+    drop(container);
+    if let Ok(docker) = client() {
+        docker
+            .remove_volume(&volume, None::<bollard::query_parameters::RemoveVolumeOptions>)
+            .await
+            .ok();
+    }
+}
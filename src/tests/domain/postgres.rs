@@ -5,7 +5,7 @@ use crate::domain::postgres::restore::prepare_archive;
 use crate::services::backup::logger::JobLogger;
 use crate::services::config::{DatabaseConfig, DbType};
 use crate::tests::init_tracing_for_test;
-use crate::utils::compress::{compress_to_tar_gz_large, decompress_large_tar_gz};
+use crate::utils::compress::{Compression, compress_archive, decompress_large_tar_gz};
 use oauth2::url;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -117,7 +117,7 @@ async fn postgres_backup_restore_test() {
 
     assert!(file_path.is_file());
 
-    let compression = compress_to_tar_gz_large(&file_path, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await.unwrap();
+    let compression = compress_archive(&file_path, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await.unwrap();
 
     assert!(compression.compressed_path.is_file());
 
@@ -223,7 +223,7 @@ async fn prepare_archive_fd_locates_toc_dir() {
     .await
     .unwrap();
 
-    let compression = compress_to_tar_gz_large(&backup_path, Arc::new(JobLogger::new()))
+    let compression = compress_archive(&backup_path, &Compression::default(), Arc::new(JobLogger::new()))
         .await
         .unwrap();
 
@@ -925,7 +925,7 @@ mod clean_mode_tests {
 }
 
 mod toc_tests {
-    use crate::domain::postgres::restore::toc_creates_public_schema;
+    use crate::domain::postgres::restore::{toc_creates_public_schema, toc_has_extension};
 
     #[test]
     fn toc_public_schema_detection() {
@@ -936,4 +936,142 @@ mod toc_tests {
         assert!(!toc_creates_public_schema(without_table));
         assert!(!toc_creates_public_schema(without_similar_schema));
     }
+
+    #[test]
+    fn toc_extension_detection() {
+        let toc = ";\n; Selected TOC Entries:\n;\n\
+                   2; 3079 17144 EXTENSION - timescaledb \n\
+                   4650; 0 0 COMMENT - EXTENSION timescaledb \n";
+        assert!(toc_has_extension(toc, "timescaledb"));
+        assert!(!toc_has_extension(toc, "postgis"));
+        assert!(!toc_has_extension("; 2; 3079 17144 EXTENSION - timescaledb", "timescaledb"));
+    }
+}
+
+mod timescale_tests {
+    use crate::domain::postgres::restore::check_timescaledb_compat;
+
+    #[test]
+    fn requires_extension_on_target() {
+        let err = check_timescaledb_compat(None, None, None).unwrap_err();
+        assert!(err.to_string().contains("not available"));
+    }
+
+    #[test]
+    fn installed_version_must_match_packaged() {
+        assert_eq!(check_timescaledb_compat(Some("2.14.2"), None, None).unwrap(), "2.14.2");
+        assert_eq!(check_timescaledb_compat(Some("2.14.2"), Some("2.14.2"), None).unwrap(), "2.14.2");
+        assert!(check_timescaledb_compat(Some("2.15.0"), Some("2.14.2"), None).is_err());
+    }
+
+    #[test]
+    fn pinned_source_version_is_enforced() {
+        assert!(check_timescaledb_compat(Some("2.14.2"), None, Some("2.14.2")).is_ok());
+        let err = check_timescaledb_compat(Some("2.15.0"), None, Some("2.14.2")).unwrap_err();
+        assert!(err.to_string().contains("mismatch"));
+    }
+}
+
+mod exec_tests {
+    use super::create_config;
+    use crate::domain::docker_exec::Execution;
+    use crate::domain::factory::DatabaseFactory;
+    use crate::domain::postgres::exec::{dump_command, restore_command};
+    use crate::domain::postgres::format::PostgresDumpFormat;
+    use crate::services::backup::logger::JobLogger;
+    use crate::services::config::DatabaseConfig;
+    use serde_json::json;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn exec_config(mut cfg: DatabaseConfig, container: &str) -> DatabaseConfig {
+        cfg.container_name = Some(container.to_string());
+        cfg.options.insert("execution".to_string(), json!("docker_exec"));
+        // Nothing may go through the published port.
+        cfg.host = "203.0.113.1".to_string();
+        cfg.port = 9;
+        cfg
+    }
+
+    fn base() -> DatabaseConfig {
+        exec_config(
+            DatabaseConfig {
+                name: "pg".to_string(),
+                database: "app".to_string(),
+                db_type: crate::services::config::DbType::Postgresql,
+                username: "app".to_string(),
+                password: "s3cret".to_string(),
+                port: 5432,
+                host: "db".to_string(),
+                generated_id: "40875631-e3d2-4dfe-a26b-2a347ecc64fd".to_string(),
+                path: String::new(),
+                max_packet_size: String::new(),
+                volume_name: String::new(),
+                container_name: None,
+                options: Default::default(),
+            },
+            "pg-1",
+        )
+    }
+
+    #[test]
+    fn execution_requires_container_name() {
+        let mut cfg = base();
+        assert_eq!(Execution::from_config(&cfg).unwrap(), Execution::Container("pg-1".to_string()));
+
+        cfg.container_name = None;
+        assert!(Execution::from_config(&cfg).is_err());
+
+        cfg.options.insert("execution".to_string(), json!("local"));
+        assert_eq!(Execution::from_config(&cfg).unwrap(), Execution::Local);
+
+        cfg.options.insert("execution".to_string(), json!("ssh"));
+        assert!(Execution::from_config(&cfg).is_err());
+    }
+
+    #[test]
+    fn password_is_passed_through_env_only() {
+        let cmd = dump_command(&base(), PostgresDumpFormat::Fc);
+        assert_eq!(cmd.args[0], "pg_dump");
+        assert!(cmd.env.contains(&"PGPASSWORD=s3cret".to_string()));
+        assert!(cmd.env.contains(&"PGDATABASE=app".to_string()));
+        assert!(!cmd.args.iter().any(|a| a.contains("s3cret")));
+        assert!(!cmd.args.iter().any(|a| a.contains("203.0.113.1")));
+    }
+
+    #[test]
+    fn restore_reads_archive_from_stdin() {
+        let cmd = restore_command(&base(), PostgresDumpFormat::Fc).unwrap();
+        assert_eq!(cmd.args[0], "pg_restore");
+        assert!(cmd.args.windows(2).any(|w| w == ["--dbname", "app"]));
+        assert!(cmd.args.contains(&"--clean".to_string()));
+
+        let fd = restore_command(&base(), PostgresDumpFormat::Fd).unwrap();
+        assert_eq!(&fd.args[..2], ["sh", "-c"]);
+        assert!(fd.args.contains(&"-j".to_string()));
+    }
+
+    #[test]
+    fn sql_clean_modes_are_rejected() {
+        for mode in ["drop_schemas", "drop_database"] {
+            let mut cfg = base();
+            cfg.options.insert("clean_mode".to_string(), json!(mode));
+            assert!(restore_command(&cfg, PostgresDumpFormat::Fc).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn exec_backup_restore_roundtrip() {
+        let (container, config) = create_config().await;
+        let config = exec_config(config, container.id());
+        let temp_dir = TempDir::new().unwrap();
+
+        let db = DatabaseFactory::create_for_backup(config.clone()).await;
+        assert!(db.ping().await.unwrap());
+        let file = db.backup(temp_dir.path(), Arc::new(JobLogger::new())).await.unwrap();
+        assert!(std::fs::metadata(&file).unwrap().len() > 0);
+
+        let db = DatabaseFactory::create_for_restore(config, &file).await;
+        db.restore(&file, Arc::new(JobLogger::new())).await.unwrap();
+    }
 }
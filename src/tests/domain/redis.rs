@@ -64,3 +64,136 @@ async fn redis_backup_test() {
 
     assert!(file_path.is_file());
 }
+
+mod connection {
+    use crate::domain::redis::connection::{
+        Mode, auth_env, connection_args, parse_cluster_nodes, parse_cluster_shards,
+        parse_sentinel_master, pick_sentinel_replica, sentinel_auth_env, sentinel_connection_args,
+    };
+    use crate::services::config::DbType;
+    use crate::tests::db_config;
+    use serde_json::json;
+
+    #[test]
+    fn mode_from_options() {
+        assert_eq!(Mode::from_config(&db_config(DbType::Redis, json!({}))).unwrap(), Mode::Standalone);
+        assert_eq!(
+            Mode::from_config(&db_config(DbType::Redis, json!({ "mode": "cluster" }))).unwrap(),
+            Mode::Cluster
+        );
+        assert_eq!(
+            Mode::from_config(&db_config(DbType::Redis, json!({
+                "mode": "sentinel", "sentinel_master": "mymaster", "sentinel_target": "replica"
+            })))
+            .unwrap(),
+            Mode::Sentinel { master: "mymaster".into(), replica: true }
+        );
+        assert!(Mode::from_config(&db_config(DbType::Redis, json!({ "mode": "sentinel" }))).is_err());
+    }
+
+    #[test]
+    fn cluster_shards_picks_healthy_masters() {
+        let json = r#"[
+            ["slots", [0, 5460], "nodes", [
+                ["id", "aaa", "port", 7000, "ip", "10.0.0.1", "endpoint", "redis-0", "role", "master", "health", "online"],
+                ["id", "bbb", "port", 7003, "ip", "10.0.0.4", "endpoint", "redis-3", "role", "replica", "health", "online"]
+            ]],
+            {"slots": [5461, 10922], "nodes": [
+                {"id": "ccc", "port": 7001, "ip": "10.0.0.2", "endpoint": "?", "role": "master", "health": "online"}
+            ]}
+        ]"#;
+        let masters = parse_cluster_shards(json).unwrap();
+
+        assert_eq!(masters.len(), 2);
+        assert_eq!((masters[0].host.as_str(), masters[0].port), ("redis-0", 7000));
+        assert_eq!(masters[0].slots, "0-5460");
+        assert_eq!((masters[1].host.as_str(), masters[1].port), ("10.0.0.2", 7001));
+    }
+
+    #[test]
+    fn cluster_nodes_fallback_skips_replicas_and_failed() {
+        let text = "\
+aaa 10.0.0.1:7000@17000 myself,master - 0 0 1 connected 0-5460
+bbb 10.0.0.4:7003@17003 slave aaa 0 0 1 connected
+ccc 10.0.0.2:7001@17001 master,fail - 0 0 2 disconnected 5461-10922
+ddd 10.0.0.3:7002@17002 master - 0 0 3 connected 10923-16383";
+        let masters = parse_cluster_nodes(text);
+
+        assert_eq!(masters.len(), 2);
+        assert_eq!(masters[0].id, "aaa");
+        assert_eq!(masters[1].port, 7002);
+        assert_eq!(masters[1].slots, "10923-16383");
+    }
+
+    #[test]
+    fn sentinel_replies() {
+        assert_eq!(
+            parse_sentinel_master("10.0.0.9\n6379\n"),
+            Some(("10.0.0.9".to_string(), 6379))
+        );
+        assert_eq!(parse_sentinel_master(""), None);
+
+        let replicas = r#"[
+            ["ip", "10.0.0.10", "port", "6379", "flags", "s_down,slave"],
+            ["ip", "10.0.0.11", "port", "6380", "flags", "slave"]
+        ]"#;
+        assert_eq!(
+            pick_sentinel_replica(replicas).unwrap(),
+            Some(("10.0.0.11".to_string(), 6380))
+        );
+    }
+
+    #[test]
+    fn password_is_passed_through_env_not_argv() {
+        let mut cfg = db_config(DbType::Redis, json!({ "sentinel_password": "sentinel-pw" }));
+        cfg.username = "backup".into();
+        cfg.password = "s3cret".into();
+
+        let args = connection_args(&cfg, "redis-0", 6379);
+        assert_eq!(args, vec!["-h", "redis-0", "-p", "6379", "--user", "backup"]);
+        assert!(!args.iter().any(|a| a.contains("s3cret")));
+        assert!(auth_env(&cfg).contains(&("REDISCLI_AUTH".into(), "s3cret".into())));
+
+        assert!(!sentinel_connection_args(&cfg).contains(&"--user".to_string()));
+        assert!(sentinel_auth_env(&cfg).contains(&("REDISCLI_AUTH".into(), "sentinel-pw".into())));
+    }
+
+    #[test]
+    fn tls_options_map_to_cli_flags() {
+        let cfg = db_config(DbType::Redis, json!({
+            "ca_cert": "/certs/ca.pem",
+            "client_cert": "/certs/client.pem",
+            "client_key": "/certs/client.key",
+            "sni": "redis.internal"
+        }));
+
+        assert_eq!(
+            connection_args(&cfg, "redis-0", 6380),
+            vec![
+                "-h", "redis-0", "-p", "6380", "--tls",
+                "--cacert", "/certs/ca.pem",
+                "--cert", "/certs/client.pem",
+                "--key", "/certs/client.key",
+                "--sni", "redis.internal",
+            ]
+        );
+        let plain = db_config(DbType::Redis, json!({}));
+        assert!(!connection_args(&plain, "redis-0", 6379).contains(&"--tls".to_string()));
+    }
+
+    #[test]
+    fn engine_version_field_per_type() {
+        use crate::domain::redis::info::{parse_info_field, version_field};
+
+        let info = "# Server\r\nredis_version:7.4.0\r\ndragonfly_version:df-v1.21.2\r\nredis_mode:standalone\r\n";
+        assert_eq!(
+            parse_info_field(info, version_field(&DbType::Dragonfly)).as_deref(),
+            Some("df-v1.21.2")
+        );
+        assert_eq!(
+            parse_info_field(info, version_field(&DbType::Keydb)).as_deref(),
+            Some("7.4.0")
+        );
+        assert_eq!(parse_info_field(info, version_field(&DbType::Valkey)), None);
+    }
+}
@@ -3,6 +3,7 @@ mod services;
 mod storage;
 mod utils;
 
+use crate::services::config::{DatabaseConfig, DbType};
 use once_cell::sync::Lazy;
 use tracing_subscriber;
 
@@ -16,3 +17,23 @@ static TRACING: Lazy<()> = Lazy::new(|| {
 fn init_tracing_for_test() -> () {
     Lazy::force(&TRACING);
 }
+
+/// Blank config of `db_type` with `options`; tests fill in the fields they
+/// exercise with struct update syntax.
+fn db_config(db_type: DbType, options: serde_json::Value) -> DatabaseConfig {
+    DatabaseConfig {
+        name: format!("Test {}", db_type.as_str()),
+        database: "".to_string(),
+        username: "".to_string(),
+        password: "".to_string(),
+        db_type,
+        port: 0,
+        host: "".to_string(),
+        generated_id: "4f5c2d1e-8a3b-4c6d-9e0f-1a2b3c4d5e6f".to_string(),
+        path: "".to_string(),
+        max_packet_size: "".to_string(),
+        volume_name: "".to_string(),
+        container_name: None,
+        options: serde_json::from_value(options).unwrap(),
+    }
+}
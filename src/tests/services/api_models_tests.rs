@@ -109,6 +109,7 @@ fn ping_result_deserializes_and_normalizes_storage_config_keys() {
     assert!(!result.databases[0].data.restore.action);
     assert!(result.databases[0].data.restore.file.is_none());
     assert!(result.databases[0].data.restore.meta_file.is_none());
+    assert!(result.databases[0].data.restore.sha256.is_none());
 }
 
 #[test]
@@ -63,6 +63,9 @@ async fn failed_upload_reports_failed_status_to_server() {
         db_type: DbType::Postgresql,
         status: "success".to_string(),
         backup_file: None,
+        stream: None,
+        codec: Default::default(),
+        dump: Default::default(),
         code: None,
     };
 
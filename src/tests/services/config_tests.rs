@@ -264,3 +264,580 @@ fn docker_volume_requires_volume_name() {
     let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
     assert!(err.contains("volume_name"), "error was: {err}");
 }
+
+#[test]
+fn parses_etcd_type_with_tls_options() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "platform-state",
+                    "type": "etcd",
+                    "host": "etcd-0",
+                    "port": 2379,
+                    "path": "/var/lib/etcd",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681",
+                    "options": {
+                        "ca_cert": "/certs/ca.pem",
+                        "client_cert": "/certs/client.pem",
+                        "client_key": "/certs/client-key.pem"
+                    }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+
+    assert_eq!(cfg.databases[0].db_type.as_str(), "etcd");
+    assert!(cfg.databases[0].username.is_empty());
+    assert_eq!(
+        cfg.databases[0].options.get("ca_cert").and_then(|v| v.as_str()),
+        Some("/certs/ca.pem")
+    );
+}
+
+#[test]
+fn etcd_requires_host() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "platform-state",
+                    "type": "etcd",
+                    "port": 2379,
+                    "path": "/var/lib/etcd",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("host"), "error was: {err}");
+}
+
+#[test]
+fn etcd_requires_path() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "platform-state",
+                    "type": "etcd",
+                    "host": "etcd-0",
+                    "port": 2379,
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("path"), "error was: {err}");
+}
+
+#[test]
+fn parses_duckdb_type() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "analytics",
+                    "type": "duckdb",
+                    "path": "/data/analytics.duckdb",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+
+    assert_eq!(cfg.databases[0].db_type.as_str(), "duckdb");
+    assert_eq!(cfg.databases[0].path, "/data/analytics.duckdb");
+    assert_eq!(cfg.databases[0].port, 0);
+}
+
+#[test]
+fn duckdb_requires_path() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "analytics",
+                    "type": "duckdb",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("path"), "error was: {err}");
+}
+
+#[test]
+fn parses_couchdb_type_without_database() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "documents",
+                    "type": "couchdb",
+                    "host": "couchdb",
+                    "port": 5984,
+                    "username": "admin",
+                    "password": "secret",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681",
+                    "options": { "conflict_mode": "skip" }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+
+    assert_eq!(cfg.databases[0].db_type.as_str(), "couchdb");
+    assert!(cfg.databases[0].database.is_empty());
+    assert_eq!(cfg.databases[0].port, 5984);
+}
+
+#[test]
+fn couchdb_rejects_unknown_conflict_mode() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "documents",
+                    "type": "couchdb",
+                    "host": "couchdb",
+                    "port": 5984,
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681",
+                    "options": { "conflict_mode": "overwirte" }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("conflict_mode 'overwirte'"), "error was: {err}");
+}
+
+#[test]
+fn parses_influxdb_type_with_token_option() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "metrics",
+                    "type": "influxdb",
+                    "host": "influxdb",
+                    "port": 8086,
+                    "database": "telegraf",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681",
+                    "options": { "token": "op-token", "org": "ops", "restore_bucket": "telegraf_restored" }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+
+    assert_eq!(cfg.databases[0].db_type.as_str(), "influxdb");
+    assert_eq!(cfg.databases[0].database, "telegraf");
+    assert_eq!(
+        cfg.databases[0].options.get("token").and_then(|v| v.as_str()),
+        Some("op-token")
+    );
+}
+
+#[test]
+fn neo4j_requires_volume_name() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "graph",
+                    "type": "neo4j",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("volume_name"), "error was: {err}");
+}
+
+#[test]
+fn parses_neo4j_type() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "graph",
+                    "type": "neo4j",
+                    "volume_name": "neo4j_data",
+                    "container_name": "neo4j",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+
+    assert_eq!(cfg.databases[0].db_type.as_str(), "neo4j");
+    assert_eq!(cfg.databases[0].database, "neo4j");
+    assert_eq!(cfg.databases[0].volume_name, "neo4j_data");
+}
+
+#[test]
+fn neo4j_online_mode_requires_host_not_volume() {
+    let service = ConfigService::new(test_context());
+
+    let missing_host = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "graph",
+                    "type": "neo4j",
+                    "volume_name": "neo4j_data",
+                    "options": { "mode": "online" },
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+    let err = service.load(Some(missing_host.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("'host'"), "error was: {err}");
+
+    let online = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "graph",
+                    "type": "neo4j",
+                    "host": "neo4j",
+                    "port": 6362,
+                    "options": { "mode": "online", "image": "neo4j:5-enterprise" },
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+    let cfg = service.load(Some(online.path().to_str().unwrap())).unwrap();
+    assert_eq!(cfg.databases[0].host, "neo4j");
+    assert_eq!(cfg.databases[0].port, 6362);
+    assert!(cfg.databases[0].volume_name.is_empty());
+}
+
+#[test]
+fn parses_scylladb_type_with_keyspace() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "events",
+                    "type": "scylladb",
+                    "host": "scylla-0",
+                    "port": 7199,
+                    "database": "events",
+                    "path": "/mnt/scylla/data",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681",
+                    "options": { "tables": ["clicks", "views"], "restore_mode": "refresh" }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+
+    assert_eq!(cfg.databases[0].db_type.as_str(), "scylladb");
+    assert_eq!(cfg.databases[0].database, "events");
+    assert_eq!(cfg.databases[0].path, "/mnt/scylla/data");
+}
+
+#[test]
+fn cassandra_requires_keyspace() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "events",
+                    "type": "cassandra",
+                    "host": "cassandra-0",
+                    "port": 7199,
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("database"), "error was: {err}");
+}
+
+#[test]
+fn parses_dragonfly_and_keydb_types() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "cache",
+                    "type": "dragonfly",
+                    "host": "dragonfly",
+                    "port": 6379,
+                    "path": "/mnt/dragonfly",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                },
+                {
+                    "name": "sessions",
+                    "type": "keydb",
+                    "host": "keydb",
+                    "port": 6379,
+                    "generated_id": "26678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+
+    assert_eq!(cfg.databases[0].db_type.as_str(), "dragonfly");
+    assert_eq!(cfg.databases[0].path, "/mnt/dragonfly");
+    assert_eq!(cfg.databases[1].db_type.as_str(), "keydb");
+    assert!(cfg.databases[1].database.is_empty());
+}
+
+#[test]
+fn parses_filesystem_type_with_globs() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "uploads",
+                    "type": "filesystem",
+                    "path": "/srv/uploads",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681",
+                    "options": { "exclude": ["*.tmp", "cache/**"], "restore_mode": "merge" }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+
+    assert_eq!(cfg.databases[0].db_type.as_str(), "filesystem");
+    assert_eq!(cfg.databases[0].path, "/srv/uploads");
+    assert!(cfg.databases[0].host.is_empty());
+    assert_eq!(cfg.databases[0].options["restore_mode"], "merge");
+}
+
+#[test]
+fn filesystem_requires_path() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "uploads",
+                    "type": "filesystem",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("path"), "error was: {err}");
+}
+
+#[test]
+fn filesystem_rejects_unknown_restore_mode() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "uploads",
+                    "type": "filesystem",
+                    "path": "/srv/uploads",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681",
+                    "options": { "restore_mode": "merg" }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("restore_mode 'merg'"), "error was: {err}");
+}
+
+#[test]
+fn parses_docker_compose_type() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "shop stack",
+                    "type": "docker-compose",
+                    "database": "shop",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+
+    assert_eq!(cfg.databases[0].db_type.as_str(), "docker-compose");
+    assert_eq!(cfg.databases[0].database, "shop");
+    assert_eq!(cfg.databases[0].port, 0);
+}
+
+#[test]
+fn docker_compose_requires_project_name() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "shop stack",
+                    "type": "docker-compose",
+                    "generated_id": "16678159-ff7e-4c97-8c83-0adeff214681"
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("database"), "error was: {err}");
+}
+
+#[test]
+fn docker_exec_makes_host_and_port_optional() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "app",
+                    "type": "postgresql",
+                    "database": "app",
+                    "username": "app",
+                    "password": "p",
+                    "container_name": "app-db-1",
+                    "generated_id": "2b0f3c55-5d0a-4a55-9a44-6f2f8b3e1c10",
+                    "options": { "execution": "docker_exec" }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let cfg = service.load(Some(file.path().to_str().unwrap())).unwrap();
+    assert_eq!(cfg.databases[0].host, "");
+    assert_eq!(cfg.databases[0].port, 0);
+}
+
+#[test]
+fn docker_exec_requires_container_name() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "app",
+                    "type": "mysql",
+                    "database": "app",
+                    "username": "root",
+                    "password": "p",
+                    "generated_id": "2b0f3c55-5d0a-4a55-9a44-6f2f8b3e1c10",
+                    "options": { "execution": "docker_exec" }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("container_name"), "error was: {err}");
+}
+
+#[test]
+fn zstd_level_out_of_range_is_rejected() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "app",
+                    "type": "sqlite",
+                    "path": "/data/app.db",
+                    "generated_id": "2b0f3c55-5d0a-4a55-9a44-6f2f8b3e1c10",
+                    "options": { "compression": "zstd", "compression_level": 23 }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("compression_level"), "error was: {err}");
+}
+
+#[test]
+fn long_distance_requires_zstd() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "app",
+                    "type": "sqlite",
+                    "path": "/data/app.db",
+                    "generated_id": "2b0f3c55-5d0a-4a55-9a44-6f2f8b3e1c10",
+                    "options": { "compression_level": 9, "compression_long_distance": true }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("zstd"), "error was: {err}");
+}
+
+#[test]
+fn compression_threads_must_be_positive() {
+    let file = write_json(
+        r#"{
+            "databases": [
+                {
+                    "name": "app",
+                    "type": "sqlite",
+                    "path": "/data/app.db",
+                    "generated_id": "2b0f3c55-5d0a-4a55-9a44-6f2f8b3e1c10",
+                    "options": { "compression_threads": 0 }
+                }
+            ]
+        }"#,
+    );
+
+    let service = ConfigService::new(test_context());
+    let err = service.load(Some(file.path().to_str().unwrap())).unwrap_err();
+    assert!(err.contains("compression_threads"), "error was: {err}");
+}
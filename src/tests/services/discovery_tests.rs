@@ -0,0 +1,216 @@
+use crate::services::config::{ConfigService, DbType, InputDatabaseConfig, InputDatabasesConfig};
+use crate::services::discovery::{
+    DATABASE_LABEL, DiscoveredContainer, DiscoveryMode, ID_LABEL, NAME_LABEL,
+    REDACTED_PASSWORD, TYPE_LABEL, detect_type, merge, parse_env, pick_host, propose,
+    proposal_changed, proposal_snippet, stable_id,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+fn container(image: &str, env: &[&str]) -> DiscoveredContainer {
+    DiscoveredContainer {
+        name: "app-db-1".to_string(),
+        image: image.to_string(),
+        env: parse_env(&env.iter().map(|s| s.to_string()).collect::<Vec<_>>()),
+        labels: HashMap::new(),
+        networks: BTreeMap::from([("app_default".to_string(), "172.20.0.3".to_string())]),
+    }
+}
+
+fn with_label(mut c: DiscoveredContainer, key: &str, value: &str) -> DiscoveredContainer {
+    c.labels.insert(key.to_string(), value.to_string());
+    c
+}
+
+#[test]
+fn detects_engine_from_image_reference() {
+    assert_eq!(detect_type("postgres:16-alpine"), Some(DbType::Postgresql));
+    assert_eq!(detect_type("docker.io/bitnami/postgresql:16"), Some(DbType::Postgresql));
+    assert_eq!(detect_type("registry:5000/library/mysql:8"), Some(DbType::Mysql));
+    assert_eq!(detect_type("mariadb@sha256:0123"), Some(DbType::Mariadb));
+    assert_eq!(detect_type("mongo"), Some(DbType::MongoDB));
+    assert_eq!(detect_type("valkey/valkey:8"), Some(DbType::Valkey));
+    assert_eq!(detect_type("redis:7"), Some(DbType::Redis));
+    assert_eq!(detect_type("nginx:latest"), None);
+    assert_eq!(detect_type("postgres-exporter"), None);
+}
+
+#[test]
+fn parses_discovery_modes() {
+    assert_eq!(DiscoveryMode::parse(""), (DiscoveryMode::Off, None));
+    assert_eq!(DiscoveryMode::parse("Propose"), (DiscoveryMode::Propose, None));
+    assert_eq!(DiscoveryMode::parse("labeled"), (DiscoveryMode::Labeled, None));
+    assert_eq!(DiscoveryMode::parse("all"), (DiscoveryMode::All, None));
+    assert_eq!(DiscoveryMode::parse("everything"), (DiscoveryMode::Off, Some("everything".to_string())));
+}
+
+#[test]
+fn labels_opt_containers_in_and_out() {
+    assert!(DiscoveryMode::All.accepts(None));
+    assert!(!DiscoveryMode::All.accepts(Some("false")));
+    assert!(!DiscoveryMode::Labeled.accepts(None));
+    assert!(DiscoveryMode::Labeled.accepts(Some("true")));
+    assert!(!DiscoveryMode::Labeled.accepts(Some("maybe")));
+    assert!(DiscoveryMode::Propose.accepts(None));
+    assert!(!DiscoveryMode::Off.accepts(Some("true")));
+}
+
+#[test]
+fn proposes_postgres_from_env() {
+    let c = container("postgres:16", &["POSTGRES_USER=app", "POSTGRES_PASSWORD=s3cr=t", "PATH=/usr/bin"]);
+    let entry = propose(&c, "agent-1", None).unwrap();
+
+    assert_eq!(entry.db_type, DbType::Postgresql);
+    assert_eq!(entry.name, "app-db-1");
+    assert_eq!(entry.username.as_deref(), Some("app"));
+    assert_eq!(entry.password.as_deref(), Some("s3cr=t"));
+    assert_eq!(entry.database.as_deref(), Some("app"));
+    assert_eq!(entry.host.as_deref(), Some("172.20.0.3"));
+    assert_eq!(entry.port, Some(5432));
+}
+
+#[test]
+fn postgres_without_password_is_skipped_unless_trusted() {
+    let c = container("postgres", &["POSTGRES_PASSWORD_FILE=/run/secrets/pg"]);
+    assert!(propose(&c, "agent-1", None).is_err());
+
+    let c = container("postgres", &["POSTGRES_HOST_AUTH_METHOD=trust"]);
+    let entry = propose(&c, "agent-1", None).unwrap();
+    assert_eq!(entry.username.as_deref(), Some("postgres"));
+    assert_eq!(entry.password.as_deref(), Some(""));
+}
+
+#[test]
+fn mysql_prefers_root_credentials() {
+    let c = container(
+        "mysql:8",
+        &["MYSQL_ROOT_PASSWORD=root-pw", "MYSQL_USER=app", "MYSQL_PASSWORD=pw", "MYSQL_DATABASE=shop"],
+    );
+    let entry = propose(&c, "agent-1", None).unwrap();
+    assert_eq!(entry.username.as_deref(), Some("root"));
+    assert_eq!(entry.password.as_deref(), Some("root-pw"));
+    assert_eq!(entry.database.as_deref(), Some("shop"));
+    assert_eq!(entry.port, Some(3306));
+}
+
+#[test]
+fn mariadb_reads_mariadb_then_mysql_env() {
+    let c = container("mariadb:11", &["MARIADB_USER=app", "MYSQL_PASSWORD=pw", "MARIADB_DATABASE=shop"]);
+    let entry = propose(&c, "agent-1", None).unwrap();
+    assert_eq!(entry.db_type, DbType::Mariadb);
+    assert_eq!(entry.username.as_deref(), Some("app"));
+    assert_eq!(entry.password.as_deref(), Some("pw"));
+}
+
+#[test]
+fn mysql_without_database_needs_label() {
+    let c = container("mysql", &["MYSQL_ROOT_PASSWORD=pw"]);
+    assert!(propose(&c, "agent-1", None).is_err());
+
+    let c = with_label(c, DATABASE_LABEL, "shop");
+    assert_eq!(propose(&c, "agent-1", None).unwrap().database.as_deref(), Some("shop"));
+}
+
+#[test]
+fn mongo_and_redis_credentials_are_optional() {
+    let c = container("mongo:7", &["MONGO_INITDB_DATABASE=app"]);
+    let entry = propose(&c, "agent-1", None).unwrap();
+    assert_eq!(entry.username, None);
+    assert_eq!(entry.port, Some(27017));
+
+    let c = container("bitnami/redis", &["REDIS_PASSWORD=pw"]);
+    let entry = propose(&c, "agent-1", None).unwrap();
+    assert_eq!(entry.password.as_deref(), Some("pw"));
+    assert_eq!(entry.database, None);
+}
+
+#[test]
+fn labels_override_type_name_and_id() {
+    let id = "6f1c1b56-4d0e-4a8e-9a54-8a3f6e0f1c11";
+    let c = container("myorg/pg-custom:1", &["POSTGRES_PASSWORD=pw"]);
+    let c = with_label(with_label(with_label(c, TYPE_LABEL, "postgres"), NAME_LABEL, "billing"), ID_LABEL, id);
+    let entry = propose(&c, "agent-1", None).unwrap();
+    assert_eq!(entry.db_type, DbType::Postgresql);
+    assert_eq!(entry.name, "billing");
+    assert_eq!(entry.generated_id, id);
+
+    let bad = with_label(container("postgres", &["POSTGRES_PASSWORD=pw"]), ID_LABEL, "nope");
+    assert!(propose(&bad, "agent-1", None).is_err());
+}
+
+#[test]
+fn generated_ids_are_stable_per_agent_and_container() {
+    let a = stable_id("agent-1", &DbType::Postgresql, "db");
+    assert_eq!(a, stable_id("agent-1", &DbType::Postgresql, "db"));
+    assert_ne!(a, stable_id("agent-2", &DbType::Postgresql, "db"));
+    assert_ne!(a, stable_id("agent-1", &DbType::Mysql, "db"));
+    assert!(uuid::Uuid::parse_str(&a).is_ok());
+}
+
+#[test]
+fn host_uses_name_on_shared_user_network() {
+    let mut c = container("postgres", &[]);
+    c.networks.insert("bridge".to_string(), "172.17.0.5".to_string());
+
+    let shared = BTreeSet::from(["app_default".to_string()]);
+    assert_eq!(pick_host(&c, Some(&shared)).as_deref(), Some("app-db-1"));
+
+    let bridge_only = BTreeSet::from(["bridge".to_string()]);
+    assert_eq!(pick_host(&c, Some(&bridge_only)).as_deref(), Some("172.17.0.5"));
+
+    let unrelated = BTreeSet::from(["other".to_string()]);
+    assert_eq!(pick_host(&c, Some(&unrelated)), None);
+
+    // Agent on the host: any container IP is routable.
+    assert_eq!(pick_host(&c, None).as_deref(), Some("172.20.0.3"));
+}
+
+#[test]
+fn merge_keeps_explicit_entries() {
+    let discovered = |name: &str, id: &str, host: &str| {
+        let c = container("postgres", &["POSTGRES_PASSWORD=pw"]);
+        let mut e = propose(&c, "agent-1", None).unwrap();
+        e.name = name.to_string();
+        e.generated_id = id.to_string();
+        e.host = Some(host.to_string());
+        e
+    };
+    let mut explicit: Vec<InputDatabaseConfig> = vec![discovered("main", "id-1", "db.internal")];
+
+    merge(
+        &mut explicit,
+        vec![
+            discovered("other", "id-1", "10.0.0.1"),
+            discovered("main", "id-2", "10.0.0.2"),
+            discovered("copy", "id-3", "db.internal"),
+            discovered("new", "id-4", "10.0.0.4"),
+        ],
+    );
+
+    let names: Vec<_> = explicit.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["main", "new"]);
+}
+
+#[test]
+fn proposal_renders_as_loadable_toml() {
+    let c = container("postgres", &["POSTGRES_PASSWORD=pw"]);
+    let snippet = proposal_snippet(&[propose(&c, "agent-1", None).unwrap()]).unwrap();
+    assert!(snippet.contains("[[databases]]"));
+    assert!(snippet.contains("type = \"postgresql\""));
+    assert!(!snippet.contains("\"pw\""));
+    assert!(snippet.contains(REDACTED_PASSWORD));
+
+    let parsed: InputDatabasesConfig = toml::from_str(&snippet).unwrap();
+    let config = ConfigService::resolve(parsed).unwrap();
+    assert_eq!(config.databases[0].host, "172.20.0.3");
+}
+
+#[test]
+fn proposal_is_logged_again_only_when_ids_change() {
+    let a = propose(&container("postgres", &["POSTGRES_PASSWORD=pw"]), "agent-test-a", None).unwrap();
+    let b = propose(&container("redis", &[]), "agent-test-a", None).unwrap();
+
+    proposal_changed(std::slice::from_ref(&a));
+    assert!(!proposal_changed(std::slice::from_ref(&a)));
+    assert!(proposal_changed(&[a.clone(), b.clone()]));
+    assert!(!proposal_changed(&[b, a]));
+}
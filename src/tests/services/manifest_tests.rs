@@ -0,0 +1,76 @@
+use crate::domain::factory::DumpInfo;
+use crate::domain::postgres::format::PostgresDumpFormat;
+use crate::services::backup::manifest::{BackupManifest, MANIFEST_VERSION};
+use crate::services::backup::models::{BackupResult, UploadResult};
+use crate::services::config::DbType;
+use crate::utils::compress::Codec;
+use crate::utils::stream::Checksums;
+
+fn backup_result() -> BackupResult {
+    BackupResult {
+        generated_id: "gen-1".to_string(),
+        db_type: DbType::Postgresql,
+        status: "success".to_string(),
+        backup_file: None,
+        stream: None,
+        codec: Codec::Zstd,
+        dump: DumpInfo {
+            server_version: Some("16.2".to_string()),
+            tool: Some("pg_dump".to_string()),
+            tool_version: Some("pg_dump (PostgreSQL) 16.2".to_string()),
+            format: Some(PostgresDumpFormat::Fd.as_str().to_string()),
+            duration_ms: Some(1200),
+        },
+        code: None,
+    }
+}
+
+fn upload_result(checksums: Option<Checksums>) -> UploadResult {
+    UploadResult {
+        storage_id: "storage-1".to_string(),
+        success: true,
+        error: None,
+        remote_file_path: Some("backups/2026-01-01/a.tar.zst.enc".to_string()),
+        total_size: Some(100),
+        checksums,
+    }
+}
+
+#[test]
+fn manifest_describes_the_upload() {
+    let checksums = Checksums {
+        sha256: "aa".to_string(),
+        plaintext_sha256: "bb".to_string(),
+        size: 140,
+    };
+    let manifest = BackupManifest::new(&backup_result(), &upload_result(Some(checksums)), true, 900).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&manifest.to_bytes().unwrap()).unwrap();
+
+    assert_eq!(json["version"], MANIFEST_VERSION);
+    assert_eq!(json["engine"], "postgresql");
+    assert_eq!(json["codec"], "zstd");
+    assert_eq!(json["dump"]["format"], "directory");
+    assert_eq!(json["dump"]["durationMs"], 1200);
+    assert_eq!(json["encryption"]["version"], 1);
+    assert_eq!(json["size"], 100);
+    assert_eq!(json["storedSize"], 140);
+    assert_eq!(json["plaintextSha256"], "bb");
+    assert_eq!(json["uploadDurationMs"], 900);
+
+    let parsed: BackupManifest = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, manifest);
+    assert_eq!(parsed.dump.format.as_deref().and_then(PostgresDumpFormat::parse), Some(PostgresDumpFormat::Fd));
+}
+
+#[test]
+fn manifest_requires_checksums() {
+    assert!(BackupManifest::new(&backup_result(), &upload_result(None), false, 0).is_none());
+}
+
+#[test]
+fn manifest_path_sits_next_to_the_archive() {
+    assert_eq!(
+        BackupManifest::path_for("backups/2026-01-01/a.tar.gz"),
+        "backups/2026-01-01/a.tar.gz.manifest.json"
+    );
+}
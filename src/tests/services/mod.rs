@@ -1,3 +1,7 @@
 mod api_models_tests;
 mod backup_uploader_tests;
 mod config_tests;
+mod discovery_tests;
+mod manifest_tests;
+mod restore_downloader_tests;
+mod verifier_tests;
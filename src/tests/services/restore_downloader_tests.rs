@@ -0,0 +1,262 @@
+use crate::core::context::Context;
+use crate::services::api::ApiClient;
+use crate::services::backup::logger::JobLogger;
+use crate::services::restore::service::RestoreService;
+use crate::utils::edge_key::EdgeKey;
+
+use openssl::sha::sha256;
+use std::sync::Arc;
+use tempfile::tempdir;
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const BODY: &[u8] = b"archive bytes";
+
+async fn serve_archive() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn service() -> RestoreService {
+    RestoreService::new(Arc::new(Context {
+        edge_key: EdgeKey {
+            server_url: String::new(),
+            agent_id: "agent-1".to_string(),
+            master_key_b64: String::new(),
+        },
+        api: ApiClient::new(String::new()),
+    }))
+}
+
+#[tokio::test]
+async fn download_accepts_matching_checksum() {
+    let server = serve_archive().await;
+    let tmp = tempdir().unwrap();
+    let expected = hex::encode(sha256(BODY)).to_uppercase();
+
+    let path = service()
+        .download_backup(
+            &format!("{}/backup.tar.gz", server.uri()),
+            tmp.path(),
+            Arc::new(JobLogger::new()),
+            None,
+            Some(expected),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(path).unwrap(), BODY);
+}
+
+#[tokio::test]
+async fn download_rejects_checksum_mismatch() {
+    let server = serve_archive().await;
+    let tmp = tempdir().unwrap();
+
+    let err = service()
+        .download_backup(
+            &format!("{}/backup.tar.gz", server.uri()),
+            tmp.path(),
+            Arc::new(JobLogger::new()),
+            None,
+            Some(hex::encode(sha256(b"something else"))),
+        )
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("checksum"));
+}
+
+#[tokio::test]
+async fn download_retries_after_server_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+        .mount(&server)
+        .await;
+    let tmp = tempdir().unwrap();
+
+    let path = service()
+        .download_backup(
+            &format!("{}/backup.tar.gz", server.uri()),
+            tmp.path(),
+            Arc::new(JobLogger::new()),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(path).unwrap(), BODY);
+}
+
+#[tokio::test]
+async fn download_resumes_a_short_body_with_a_range_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(&BODY[..6]))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz"))
+        .and(header("range", "bytes=6-"))
+        .respond_with(
+            ResponseTemplate::new(206)
+                .insert_header("content-range", format!("bytes 6-{}/{}", BODY.len() - 1, BODY.len()))
+                .set_body_bytes(&BODY[6..]),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let tmp = tempdir().unwrap();
+
+    let path = service()
+        .download_backup(
+            &format!("{}/backup.tar.gz", server.uri()),
+            tmp.path(),
+            Arc::new(JobLogger::new()),
+            Some(BODY.len().to_string()),
+            Some(hex::encode(sha256(BODY))),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(path).unwrap(), BODY);
+}
+
+#[tokio::test]
+async fn download_starts_over_when_the_resumed_range_is_wrong() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(&BODY[..6]))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz"))
+        .and(header("range", "bytes=6-"))
+        .respond_with(
+            ResponseTemplate::new(206)
+                .insert_header("content-range", format!("bytes 0-{}/{}", BODY.len() - 1, BODY.len()))
+                .set_body_bytes(BODY),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+        .mount(&server)
+        .await;
+    let tmp = tempdir().unwrap();
+
+    let path = service()
+        .download_backup(
+            &format!("{}/backup.tar.gz", server.uri()),
+            tmp.path(),
+            Arc::new(JobLogger::new()),
+            Some(BODY.len().to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(path).unwrap(), BODY);
+}
+
+#[tokio::test]
+async fn download_does_not_retry_client_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let tmp = tempdir().unwrap();
+
+    let result = service()
+        .download_backup(
+            &format!("{}/backup.tar.gz", server.uri()),
+            tmp.path(),
+            Arc::new(JobLogger::new()),
+            None,
+            None,
+        )
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn download_rejects_unexpected_size() {
+    let server = serve_archive().await;
+    let tmp = tempdir().unwrap();
+
+    let err = service()
+        .download_backup(
+            &format!("{}/backup.tar.gz", server.uri()),
+            tmp.path(),
+            Arc::new(JobLogger::new()),
+            Some("5".to_string()),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("size"));
+}
+
+#[tokio::test]
+async fn manifest_is_read_when_available() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/backup.tar.gz.manifest.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "version": 1,
+            "agentVersion": "1.0.0",
+            "generatedId": "gen-1",
+            "createdAt": "2026-01-01T00:00:00Z",
+            "engine": "mysql",
+            "dump": { "tool": "mysqldump", "format": "sql" },
+            "codec": "gzip",
+            "size": 13,
+            "storedSize": 13,
+            "sha256": "aa",
+            "plaintextSha256": "aa",
+            "uploadDurationMs": 5
+        })))
+        .mount(&server)
+        .await;
+
+    let manifest = service()
+        .download_manifest(
+            &format!("{}/backup.tar.gz.manifest.json", server.uri()),
+            Arc::new(JobLogger::new()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(manifest.dump.tool.as_deref(), Some("mysqldump"));
+    assert!(manifest.encryption.is_none());
+
+    let missing = service()
+        .download_manifest(&format!("{}/missing.json", server.uri()), Arc::new(JobLogger::new()))
+        .await;
+    assert!(missing.is_none());
+}
@@ -0,0 +1,57 @@
+use crate::domain::mysql::exec::Flavor;
+use crate::services::backup::verifier::{Engine, VerifyOptions};
+use crate::services::config::{DatabaseConfig, DbType};
+use crate::tests::db_config;
+
+use serde_json::json;
+
+#[test]
+fn verification_is_opt_in() {
+    assert_eq!(VerifyOptions::from_config(&db_config(DbType::Postgresql, json!({}))), None);
+
+    let cfg = db_config(
+        DbType::Postgresql,
+        json!({ "verify_restore": true, "verify_query": " SELECT count(*) FROM users ", "verify_image": "" }),
+    );
+    assert_eq!(
+        VerifyOptions::from_config(&cfg),
+        Some(VerifyOptions { image: None, query: Some("SELECT count(*) FROM users".to_string()) })
+    );
+}
+
+#[test]
+fn image_matches_server_version() {
+    assert_eq!(Engine::Postgres.image_for("16.2 (Debian 16.2-1.pgdg120+2)").unwrap(), "postgres:16.2");
+    assert_eq!(Engine::Mysql(Flavor::Mysql).image_for("8.0.36\n").unwrap(), "mysql:8.0.36");
+    assert_eq!(
+        Engine::Mysql(Flavor::Mariadb).image_for("10.11.6-MariaDB-1:10.11.6+maria~ubu2204").unwrap(),
+        "mariadb:10.11.6"
+    );
+    assert!(Engine::Postgres.image_for("").is_err());
+    assert!(Engine::for_type(&DbType::Redis).is_none());
+}
+
+#[test]
+fn target_restores_through_exec_with_fresh_credentials() {
+    let source = DatabaseConfig {
+        database: "app".to_string(),
+        password: "secret".to_string(),
+        ..db_config(
+            DbType::Mariadb,
+            json!({ "docker_host": "tcp://docker:2376", "keep_ownership": true, "verify_restore": true }),
+        )
+    };
+    let (target, env) = Engine::Mysql(Flavor::Mariadb).target(&source);
+
+    assert_ne!(target.generated_id, source.generated_id);
+    assert_ne!(target.password, source.password);
+    assert_eq!(target.username, "root");
+    assert_eq!(target.options.get("execution"), Some(&json!("docker_exec")));
+    assert_eq!(target.options.get("docker_host"), Some(&json!("tcp://docker:2376")));
+    assert!(!target.options.contains_key("keep_ownership"));
+    assert!(!target.options.contains_key("verify_restore"));
+    assert_eq!(
+        env,
+        vec![format!("MARIADB_ROOT_PASSWORD={}", target.password), "MARIADB_DATABASE=app".to_string()]
+    );
+}
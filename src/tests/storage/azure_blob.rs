@@ -231,7 +231,7 @@ async fn upload_stream_multi_block_roundtrip() {
         .collect();
     let body = Box::pin(stream::iter(chunks));
 
-    upload_stream_to_azure(&resolved, container, blob, body, 4 * 1024)
+    upload_stream_to_azure(&resolved, container, blob, body, 4 * 1024, None)
         .await
         .unwrap();
 
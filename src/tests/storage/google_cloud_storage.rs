@@ -70,7 +70,7 @@ async fn upload_stream_roundtrip_against_fake_gcs() {
         .chunks(1024)
         .map(|c| Ok(Bytes::copy_from_slice(c)))
         .collect();
-    let source = StreamSource::from_stream(Box::pin(stream::iter(chunks)), data.len() as u64);
+    let source = StreamSource::from_stream(Box::pin(stream::iter(chunks)), Some(data.len() as u64));
 
     let client = anon_client(&endpoint).await;
 
@@ -0,0 +1,21 @@
+use crate::services::api::models::agent::status::DatabaseStorage;
+use crate::services::storage::StorageProvider;
+use crate::services::storage::providers::local::LocalProvider;
+
+use bytes::Bytes;
+
+#[tokio::test]
+async fn manifests_are_not_stored_on_the_server() {
+    let storage = DatabaseStorage {
+        id: "local-1".to_string(),
+        config: toml::Value::Table(Default::default()),
+        provider: "local".to_string(),
+        folder_name: None,
+    };
+
+    let stored = LocalProvider
+        .upload_sidecar(&storage, "backups/db.tar.gz.manifest.json", Bytes::from_static(b"{}"))
+        .await
+        .unwrap();
+    assert!(!stored);
+}
@@ -1,2 +1,4 @@
 mod azure_blob;
 mod google_cloud_storage;
+mod local;
+mod s3;
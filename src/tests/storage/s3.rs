@@ -0,0 +1,7 @@
+use crate::services::storage::providers::s3::copy_source;
+
+#[test]
+fn copy_source_encodes_the_key() {
+    assert_eq!(copy_source("bucket", "backups/db.tar.gz"), "bucket/backups/db.tar.gz");
+    assert_eq!(copy_source("bucket", "my folder/a+b.tar"), "bucket/my%20folder/a%2Bb.tar");
+}
@@ -1,4 +1,4 @@
-use crate::utils::compress::{compress_to_tar_gz_large, decompress_large_tar_gz};
+use crate::utils::compress::{Compression, compress_archive, decompress_large_tar_gz};
 use anyhow::Result;
 use tempfile::tempdir;
 use tokio::fs::{read, write};
@@ -9,7 +9,7 @@ async fn compress_creates_tar_gz() -> Result<()> {
     let file_path = tmp.path().join("test.txt");
     write(&file_path, b"hello world").await?;
 
-    let result = compress_to_tar_gz_large(&file_path, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
+    let result = compress_archive(&file_path, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
     assert!(result.compressed_path.exists());
     assert_eq!(result.compressed_path.extension().unwrap(), "gz");
 
@@ -22,7 +22,7 @@ async fn compress_skips_existing_tar_gz() -> Result<()> {
     let file_path = tmp.path().join("already.tar.gz");
     write(&file_path, b"compressed").await?;
 
-    let result = compress_to_tar_gz_large(&file_path, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
+    let result = compress_archive(&file_path, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
     assert_eq!(result.compressed_path, file_path);
 
     Ok(())
@@ -34,7 +34,7 @@ async fn decompress_restores_file() -> Result<()> {
     let file_path = tmp.path().join("file.txt");
     write(&file_path, b"data for decompress").await?;
 
-    let compress_result = compress_to_tar_gz_large(&file_path, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
+    let compress_result = compress_archive(&file_path, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
     let output_dir = tmp.path().join("out");
     tokio::fs::create_dir_all(&output_dir).await?;
 
@@ -57,8 +57,8 @@ async fn decompress_multiple_files() -> Result<()> {
     write(&file2, b"file2").await?;
 
     // Compress both files individually (for simplicity in this test)
-    let compress1 = compress_to_tar_gz_large(&file1, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
-    let compress2 = compress_to_tar_gz_large(&file2, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
+    let compress1 = compress_archive(&file1, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
+    let compress2 = compress_archive(&file2, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
 
     let output_dir = tmp.path().join("out_multi");
     tokio::fs::create_dir_all(&output_dir).await?;
@@ -88,9 +88,7 @@ async fn compress_tar_is_not_double_wrapped() -> Result<()> {
         b.finish().await?;
     }
 
-    let result = compress_to_tar_gz_large(
-        &tar_path,
-        std::sync::Arc::new(crate::services::backup::logger::JobLogger::new()),
+    let result = compress_archive(&tar_path, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new()),
     )
     .await?;
     assert_eq!(result.compressed_path, tmp.path().join("volume.tar.gz"));
@@ -108,20 +106,190 @@ async fn compress_tar_is_not_double_wrapped() -> Result<()> {
 }
 
 #[tokio::test]
-async fn gunzip_to_file_restores_tar_byte_for_byte() -> Result<()> {
-    use crate::utils::compress::gunzip_to_file;
+async fn decompress_to_file_restores_tar_byte_for_byte() -> Result<()> {
+    use crate::utils::compress::decompress_to_file;
 
     let tmp = tempdir()?;
     let tar_path = tmp.path().join("input.tar");
     let original: Vec<u8> = (0u32..50_000).map(|n| (n % 256) as u8).collect();
     write(&tar_path, &original).await?;
 
-    let gz = compress_to_tar_gz_large(&tar_path, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
+    let gz = compress_archive(&tar_path, &Compression::default(), std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
 
     let out_tar = tmp.path().join("out.tar");
-    gunzip_to_file(&gz.compressed_path, &out_tar).await?;
+    decompress_to_file(&gz.compressed_path, &out_tar).await?;
 
     assert_eq!(read(&out_tar).await?, original);
 
     Ok(())
 }
+
+async fn collect_to_file(mut stream: crate::utils::stream::ByteStream, path: &std::path::Path) -> Result<()> {
+    use futures::StreamExt;
+    let mut bytes = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+    write(path, bytes).await?;
+    Ok(())
+}
+
+fn dump_of(file_name: &str, chunks: Vec<Vec<u8>>) -> crate::utils::stream::DumpStream {
+    let items = chunks.into_iter().map(|c| Ok(bytes::Bytes::from(c)));
+    crate::utils::stream::DumpStream {
+        file_name: file_name.to_string(),
+        stream: Box::pin(futures::stream::iter(items)),
+    }
+}
+
+#[tokio::test]
+async fn tar_stream_small_dump_matches_file_layout() -> Result<()> {
+    use crate::utils::compress::tar_stream;
+
+    let tmp = tempdir()?;
+    let archive = tmp.path().join("stream.tar.gz");
+    collect_to_file(tar_stream(dump_of("db.dump", vec![b"small dump".to_vec()]), Compression::default(), 1024), &archive).await?;
+
+    let out = tmp.path().join("out");
+    tokio::fs::create_dir_all(&out).await?;
+    let files = decompress_large_tar_gz(&archive, &out).await?;
+    assert_eq!(files, vec![out.join("db.dump")]);
+    assert_eq!(read(&files[0]).await?, b"small dump");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn tar_stream_parts_are_joined_on_extraction() -> Result<()> {
+    use crate::utils::compress::tar_stream;
+
+    let tmp = tempdir()?;
+    let original: Vec<u8> = (0u32..10_000).map(|n| (n * 7 % 251) as u8).collect();
+    let chunks = original.chunks(777).map(<[u8]>::to_vec).collect();
+    let archive = tmp.path().join("stream.tar.gz");
+    collect_to_file(tar_stream(dump_of("db.sql", chunks), Compression::default(), 4096), &archive).await?;
+
+    let out = tmp.path().join("out");
+    tokio::fs::create_dir_all(&out).await?;
+    let files = decompress_large_tar_gz(&archive, &out).await?;
+    assert_eq!(files, vec![out.join("db.sql")]);
+    assert_eq!(read(&files[0]).await?, original);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn zstd_archive_round_trips_with_long_distance() -> Result<()> {
+    use crate::utils::compress::Codec;
+
+    let tmp = tempdir()?;
+    let file_path = tmp.path().join("db.sql");
+    let original: Vec<u8> = (0u32..200_000).map(|n| (n % 97) as u8).collect();
+    write(&file_path, &original).await?;
+
+    let compression = Compression { codec: Codec::Zstd, level: Some(19), long_distance: true, ..Default::default() };
+    let result = compress_archive(&file_path, &compression, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
+    assert_eq!(result.compressed_path, tmp.path().join("db.tar.zst"));
+    assert_eq!(result.codec, Codec::Zstd);
+
+    let out = tmp.path().join("out");
+    tokio::fs::create_dir_all(&out).await?;
+    let files = decompress_large_tar_gz(&result.compressed_path, &out).await?;
+    assert_eq!(files, vec![out.join("db.sql")]);
+    assert_eq!(read(&files[0]).await?, original);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn codec_is_detected_from_magic_bytes_not_name() -> Result<()> {
+    use crate::utils::compress::{Codec, tar_stream};
+
+    let tmp = tempdir()?;
+    // Misnamed on purpose: restore must not trust the extension.
+    let archive = tmp.path().join("stream.tar.gz");
+    let zstd = Compression { codec: Codec::Zstd, ..Default::default() };
+    collect_to_file(tar_stream(dump_of("db.dump", vec![b"zstd dump".to_vec()]), zstd, 1024), &archive).await?;
+    assert_eq!(Codec::detect(&archive).await?, Codec::Zstd);
+
+    let out = tmp.path().join("out");
+    tokio::fs::create_dir_all(&out).await?;
+    let files = decompress_large_tar_gz(&archive, &out).await?;
+    assert_eq!(read(&files[0]).await?, b"zstd dump");
+
+    let garbage = tmp.path().join("garbage.tar.gz");
+    write(&garbage, b"not an archive").await?;
+    assert!(Codec::detect(&garbage).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn parallel_gzip_round_trips_as_one_member() -> Result<()> {
+    use crate::utils::compress::{Codec, compress_archive};
+
+    let tmp = tempdir()?;
+    let file_path = tmp.path().join("db.sql");
+    // Spans three 4 MiB blocks, compressed on separate threads.
+    let original: Vec<u8> = (0u32..9 * 1024 * 1024).map(|n| (n.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+    write(&file_path, &original).await?;
+
+    let compression = Compression { threads: 4, ..Default::default() };
+    let result = compress_archive(&file_path, &compression, std::sync::Arc::new(crate::services::backup::logger::JobLogger::new())).await?;
+    assert_eq!(result.compressed_path, tmp.path().join("db.tar.gz"));
+    assert_eq!(Codec::detect(&result.compressed_path).await?, Codec::Gzip);
+
+    let out = tmp.path().join("out");
+    tokio::fs::create_dir_all(&out).await?;
+    let files = decompress_large_tar_gz(&result.compressed_path, &out).await?;
+    assert_eq!(files, vec![out.join("db.sql")]);
+    assert_eq!(read(&files[0]).await?, original);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn multi_threaded_zstd_round_trips() -> Result<()> {
+    use crate::utils::compress::{Codec, tar_stream};
+
+    let tmp = tempdir()?;
+    let original: Vec<u8> = (0u32..300_000).map(|n| (n * 13 % 251) as u8).collect();
+    let chunks = original.chunks(65_536).map(<[u8]>::to_vec).collect();
+    let archive = tmp.path().join("stream.tar.zst");
+    let compression = Compression { codec: Codec::Zstd, threads: 3, ..Default::default() };
+    collect_to_file(tar_stream(dump_of("db.sql", chunks), compression, 100_000), &archive).await?;
+
+    let out = tmp.path().join("out");
+    tokio::fs::create_dir_all(&out).await?;
+    let files = decompress_large_tar_gz(&archive, &out).await?;
+    assert_eq!(read(&files[0]).await?, original);
+
+    Ok(())
+}
+
+#[test]
+fn blocking_gzip_writer_output_reads_back_in_order() -> Result<()> {
+    use crate::utils::compress::GzipWriter;
+    use std::io::{Read, Write};
+
+    let original: Vec<u8> = (0u32..9 * 1024 * 1024).map(|n| (n % 253) as u8).collect();
+    let compression = Compression { threads: 2, ..Default::default() };
+    let mut writer = GzipWriter::new(Vec::new(), &compression);
+    for chunk in original.chunks(1_000_003) {
+        writer.write_all(chunk)?;
+    }
+    let gz = writer.finish()?;
+
+    // A single-member decoder stops at the first member, so this only
+    // passes if every block landed in one gzip stream.
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(gz.as_slice()).read_to_end(&mut decoded)?;
+    assert_eq!(decoded, original);
+
+    let empty = GzipWriter::new(Vec::new(), &compression).finish()?;
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(empty.as_slice()).read_to_end(&mut decoded)?;
+    assert!(decoded.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::services::config::DbType;
+use crate::tests::db_config;
+use crate::utils::disk::{
+    BACKUP_MARGIN, Space, available_space, check_space, human_size, margin_option, restore_margin,
+};
+
+#[test]
+fn restore_margin_counts_the_decrypted_copy() {
+    assert_eq!(restore_margin(false), 2.0);
+    assert_eq!(restore_margin(true), 3.0);
+}
+
+#[test]
+fn human_size_units() {
+    assert_eq!(human_size(512), "512 B");
+    assert_eq!(human_size(2048), "2 KB");
+    assert_eq!(human_size(5 * 1024 * 1024), "5 MB");
+    assert_eq!(human_size(3 * 1024 * 1024 * 1024 / 2), "1.5 GB");
+}
+
+#[test]
+fn margin_option_requires_at_least_one() {
+    let mut cfg = db_config(DbType::Postgresql, serde_json::json!({}));
+    assert_eq!(margin_option(&cfg), Ok(None));
+
+    cfg.options.insert("disk_margin".into(), json!(1.5));
+    assert_eq!(margin_option(&cfg), Ok(Some(1.5)));
+
+    cfg.options.insert("disk_margin".into(), json!(0.5));
+    assert!(margin_option(&cfg).is_err());
+    cfg.options.insert("disk_margin".into(), json!("lots"));
+    assert!(margin_option(&cfg).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn check_space_fails_only_below_the_raw_size() {
+    let dir = tempdir().unwrap();
+    let available = available_space(dir.path()).unwrap();
+
+    assert_eq!(check_space(dir.path(), 1, BACKUP_MARGIN).unwrap(), Space::Enough);
+
+    let tight = check_space(dir.path(), available / 2 + 1, 4.0).unwrap();
+    assert!(matches!(tight, Space::Tight { .. }), "{tight:?}");
+
+    let err = check_space(dir.path(), u64::MAX, 1.0).unwrap_err();
+    assert_eq!(err.required, u64::MAX);
+    assert!(err.to_string().starts_with("Not enough disk space in"));
+}
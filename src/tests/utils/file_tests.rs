@@ -6,6 +6,7 @@ use tempfile::tempdir;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
+use crate::utils::compress::Codec;
 use crate::utils::file::{
     decrypt_file_stream_gcm, encrypt_file_stream_gcm, full_extension, full_file_name,
     full_file_path,
@@ -22,11 +23,13 @@ fn full_extension_returns_everything_after_first_dot() {
 
 #[test]
 fn full_file_name_matches_expected_suffix() {
-    let unencrypted = full_file_name(false);
-    let encrypted = full_file_name(true);
+    let unencrypted = full_file_name(false, Codec::Gzip);
+    let encrypted = full_file_name(true, Codec::Gzip);
+    let zstd = full_file_name(true, Codec::Zstd);
 
     assert!(unencrypted.ends_with(".tar.gz"));
     assert!(encrypted.ends_with(".tar.gz.enc"));
+    assert!(zstd.ends_with(".tar.zst.enc"));
 }
 
 #[test]
@@ -114,3 +117,27 @@ fn decrypt_json_gcm_rejects_short_input() {
     // 8 bytes base64 -> shorter than nonce(12)+tag(16)
     assert!(decrypt_json_gcm("AAAAAAAAAAA=", VECTOR_KEY_B64).is_err());
 }
+
+#[tokio::test]
+async fn encrypt_stream_round_trip_restores_chunked_input() -> Result<()> {
+    use crate::utils::file::encrypt_stream_gcm;
+
+    let tmp = tempdir()?;
+    let encrypted_path = tmp.path().join("cipher.bin");
+    let decrypted_path = tmp.path().join("plain.out");
+    let original: Vec<u8> = (0u32..5_000).map(|n| (n % 256) as u8).collect();
+    let chunks: Vec<std::io::Result<bytes::Bytes>> =
+        original.chunks(333).map(|c| Ok(bytes::Bytes::copy_from_slice(c))).collect();
+
+    let key = general_purpose::STANDARD.encode([4u8; 32]);
+    let mut encrypted_stream = encrypt_stream_gcm(futures::stream::iter(chunks), key.clone())?;
+    let mut encrypted_file = fs::File::create(&encrypted_path).await?;
+    while let Some(chunk) = encrypted_stream.next().await {
+        encrypted_file.write_all(&chunk?).await?;
+    }
+
+    decrypt_file_stream_gcm(encrypted_path, decrypted_path.clone(), key).await?;
+    assert_eq!(fs::read(decrypted_path).await?, original);
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 mod common_tests;
 mod compress_tests;
 mod deserializer;
+mod disk_tests;
 mod edge_key_tests;
 mod file_tests;
 mod normalize_cron_tests;
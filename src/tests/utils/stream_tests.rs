@@ -52,3 +52,118 @@ async fn build_stream_with_encryption_produces_decryptable_content() -> Result<(
 
     Ok(())
 }
+
+#[tokio::test]
+async fn build_stream_checksums_cover_plaintext_and_uploaded_bytes() -> Result<()> {
+    use openssl::sha::sha256;
+
+    let tmp = tempdir()?;
+    let file_path = tmp.path().join("plain.txt");
+    let content = b"checksummed upload stream";
+    fs::write(&file_path, content).await?;
+
+    let key = general_purpose::STANDARD.encode([7u8; 32]);
+    let mut upload = build_stream(&file_path, true, &key).await?;
+    let mut encrypted = Vec::new();
+    while let Some(chunk) = upload.stream.next().await {
+        encrypted.extend_from_slice(&chunk?);
+    }
+
+    let checksums = upload.digests.checksums();
+    assert_eq!(checksums.plaintext_sha256, hex::encode(sha256(content)));
+    assert_eq!(checksums.sha256, hex::encode(sha256(&encrypted)));
+
+    let mut plain = build_stream(&file_path, false, &String::new()).await?;
+    while let Some(chunk) = plain.stream.next().await {
+        chunk?;
+    }
+    let checksums = plain.digests.checksums();
+    assert_eq!(checksums.sha256, checksums.plaintext_sha256);
+    assert_eq!(checksums.sha256, hex::encode(sha256(content)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn fan_out_copies_every_chunk_to_each_consumer() -> Result<()> {
+    use crate::utils::stream::fan_out;
+
+    let chunks = vec![Ok(bytes::Bytes::from_static(b"ab")), Ok(bytes::Bytes::from_static(b"cd"))];
+    let outputs = fan_out(Box::pin(futures::stream::iter(chunks)), 3);
+    assert_eq!(outputs.len(), 3);
+
+    let collected = futures::future::join_all(outputs.into_iter().map(|mut s| async move {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = s.next().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+        bytes
+    }))
+    .await;
+
+    for bytes in collected {
+        assert_eq!(bytes, b"abcd");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn open_stream_counts_streamed_bytes_before_encryption() -> Result<()> {
+    use crate::services::backup::models::BackupResult;
+    use crate::services::config::DbType;
+    use crate::utils::stream::{SharedStream, open_stream};
+
+    let content = b"streamed archive bytes";
+    let source = futures::stream::iter(vec![Ok(bytes::Bytes::from_static(content))]);
+    let result = BackupResult {
+        generated_id: "gen".to_string(),
+        db_type: DbType::Postgresql,
+        status: "success".to_string(),
+        backup_file: None,
+        stream: Some(SharedStream::new(Box::pin(source))),
+        codec: Default::default(),
+        dump: Default::default(),
+        code: None,
+    };
+
+    let key = general_purpose::STANDARD.encode([3u8; 32]);
+    let mut upload = open_stream(&result, true, &key).await?;
+    assert_eq!(upload.size, None);
+
+    let mut encrypted = 0;
+    while let Some(chunk) = upload.stream.next().await {
+        encrypted += chunk?.len();
+    }
+
+    assert_eq!(upload.read.get(), content.len() as u64);
+    assert!(encrypted > content.len());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn command_stream_fails_on_non_zero_exit() -> Result<()> {
+    use crate::services::backup::logger::JobLogger;
+    use crate::utils::stream::command_stream;
+    use std::sync::Arc;
+
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c").arg("printf partial; echo boom >&2; exit 3");
+    let mut stream = command_stream(cmd, "sh".to_string(), Arc::new(JobLogger::new()))?;
+
+    let mut stdout = Vec::new();
+    let mut error = None;
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(bytes) => stdout.extend_from_slice(&bytes),
+            Err(e) => error = Some(e.to_string()),
+        }
+    }
+
+    assert_eq!(stdout, b"partial");
+    let error = error.expect("exit status should surface as an error");
+    assert!(error.contains("exit 3") && error.contains("boom"), "{error}");
+
+    Ok(())
+}
@@ -1,34 +1,561 @@
 use anyhow::{Context, Result};
-use async_compression::tokio::bufread::GzipDecoder;
-use async_compression::tokio::write::GzipEncoder as AsyncGzipEncoder;
+use bytes::{Bytes, BytesMut};
+use async_compression::Level;
+use async_compression::tokio::bufread::{GzipDecoder, ZstdDecoder};
+use async_compression::tokio::write::{GzipEncoder as AsyncGzipEncoder, ZstdEncoder as AsyncZstdEncoder};
+use async_compression::zstd::{CParameter, DParameter};
 use futures::StreamExt;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll, ready};
 use tokio::fs::File;
 use tokio::fs::create_dir_all;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::io::BufReader;
+use tokio::task::JoinHandle;
 use tokio_tar::Archive;
 use tokio_tar::Builder as TokioTarBuilder;
 use tracing::info;
 
 use crate::services::backup::logger::JobLogger;
+use crate::services::config::DatabaseConfig;
+use crate::utils::stream::{ByteStream, DumpStream};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Window used with long-distance matching: 128 MiB, the largest window
+/// decoders accept without raising their limit (same as `zstd --long`).
+const LONG_WINDOW_LOG: u32 = 27;
+
+const MAX_THREADS: u64 = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Codec {
+    #[default]
+    Gzip,
+    Zstd,
+}
+
+impl Codec {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Codec::Gzip => "gzip",
+            Codec::Zstd => "zstd",
+        }
+    }
+
+    /// Suffix of a tar archive compressed with this codec, without the dot.
+    pub fn tar_extension(self) -> &'static str {
+        match self {
+            Codec::Gzip => "tar.gz",
+            Codec::Zstd => "tar.zst",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Codec::Gzip => "gz",
+            Codec::Zstd => "zst",
+        }
+    }
+
+    /// Codec of an archive from its magic bytes, whatever its name.
+    pub async fn detect(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)
+            .await
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut magic = [0u8; 4];
+        let mut read = 0;
+        while read < magic.len() {
+            match file.read(&mut magic[read..]).await? {
+                0 => break,
+                n => read += n,
+            }
+        }
+
+        if read >= 4 && magic == ZSTD_MAGIC {
+            Ok(Codec::Zstd)
+        } else if read >= 2 && magic[..2] == GZIP_MAGIC {
+            Ok(Codec::Gzip)
+        } else {
+            anyhow::bail!("Unrecognised archive format for {}", path.display())
+        }
+    }
+
+    /// Codec implied by an already-compressed file's name.
+    fn from_file_name(name: &str) -> Option<Self> {
+        if name.ends_with(".tar.gz") {
+            Some(Codec::Gzip)
+        } else if name.ends_with(".tar.zst") {
+            Some(Codec::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// Archive compression for a database: `options.compression` (gzip or zstd),
+/// `options.compression_level`, `options.compression_threads` and, for zstd,
+/// `options.compression_long_distance`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    pub codec: Codec,
+    pub level: Option<i32>,
+    pub long_distance: bool,
+    /// Cores compression may use. Above one, gzip deflates blocks on separate
+    /// threads into a single member and zstd runs its own worker pool.
+    pub threads: usize,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            codec: Codec::default(),
+            level: None,
+            long_distance: false,
+            threads: 1,
+        }
+    }
+}
+
+impl Compression {
+    pub fn from_config(cfg: &DatabaseConfig) -> Result<Self> {
+        let codec = match cfg.options.get("compression").and_then(|v| v.as_str()) {
+            None | Some("gzip") => Codec::Gzip,
+            Some("zstd") => Codec::Zstd,
+            Some(other) => anyhow::bail!("Unknown compression '{other}', expected gzip or zstd"),
+        };
+
+        let level = match cfg.options.get("compression_level") {
+            None => None,
+            Some(v) => {
+                let level = v
+                    .as_i64()
+                    .ok_or_else(|| anyhow::anyhow!("compression_level must be an integer"))?;
+                let range = match codec {
+                    Codec::Gzip => 1..=9,
+                    Codec::Zstd => 1..=22,
+                };
+                if !range.contains(&level) {
+                    anyhow::bail!(
+                        "compression_level {level} is out of range for {} ({}-{})",
+                        codec.as_str(),
+                        range.start(),
+                        range.end()
+                    );
+                }
+                Some(level as i32)
+            }
+        };
+
+        let long_distance = cfg
+            .options
+            .get("compression_long_distance")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if long_distance && codec != Codec::Zstd {
+            anyhow::bail!("compression_long_distance requires compression 'zstd'");
+        }
+
+        let threads = match cfg.options.get("compression_threads") {
+            None => 1,
+            Some(v) => match v.as_u64() {
+                Some(n @ 1..=MAX_THREADS) => n as usize,
+                _ => anyhow::bail!("compression_threads must be an integer between 1 and {MAX_THREADS}"),
+            },
+        };
+
+        Ok(Self { codec, level, long_distance, threads })
+    }
+
+    fn level(&self) -> Level {
+        self.level.map(Level::Precise).unwrap_or(Level::Default)
+    }
+
+    fn gzip_level(&self) -> flate2::Compression {
+        self.level
+            .map(|l| flate2::Compression::new(l as u32))
+            .unwrap_or_default()
+    }
+}
+
+/// Compressing writer for the configured codec.
+pub enum ArchiveEncoder<W> {
+    Gzip(AsyncGzipEncoder<W>),
+    ParallelGzip(ParallelGzipEncoder<W>),
+    Zstd(AsyncZstdEncoder<W>),
+}
+
+impl<W: AsyncWrite + Unpin> ArchiveEncoder<W> {
+    pub fn new(inner: W, compression: &Compression) -> Self {
+        match compression.codec {
+            Codec::Gzip if compression.threads > 1 => Self::ParallelGzip(ParallelGzipEncoder::new(inner, compression)),
+            Codec::Gzip => Self::Gzip(AsyncGzipEncoder::with_quality(inner, compression.level())),
+            Codec::Zstd => {
+                let mut params = Vec::new();
+                if compression.threads > 1 {
+                    params.push(CParameter::nb_workers(compression.threads as u32));
+                }
+                if compression.long_distance {
+                    params.push(CParameter::enable_long_distance_matching(true));
+                    params.push(CParameter::window_log(LONG_WINDOW_LOG));
+                }
+                Self::Zstd(AsyncZstdEncoder::with_quality_and_params(inner, compression.level(), &params))
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        match self {
+            Self::Gzip(e) => e.get_mut(),
+            Self::ParallelGzip(e) => e.get_mut(),
+            Self::Zstd(e) => e.get_mut(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        match self {
+            Self::Gzip(e) => e.into_inner(),
+            Self::ParallelGzip(e) => e.into_inner(),
+            Self::Zstd(e) => e.into_inner(),
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for ArchiveEncoder<W> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut TaskContext<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Gzip(e) => Pin::new(e).poll_write(cx, buf),
+            Self::ParallelGzip(e) => Pin::new(e).poll_write(cx, buf),
+            Self::Zstd(e) => Pin::new(e).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Gzip(e) => Pin::new(e).poll_flush(cx),
+            Self::ParallelGzip(e) => Pin::new(e).poll_flush(cx),
+            Self::Zstd(e) => Pin::new(e).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Gzip(e) => Pin::new(e).poll_shutdown(cx),
+            Self::ParallelGzip(e) => Pin::new(e).poll_shutdown(cx),
+            Self::Zstd(e) => Pin::new(e).poll_shutdown(cx),
+        }
+    }
+}
+
+/// Uncompressed bytes per deflate block when compressing on several threads.
+const PARALLEL_BLOCK_SIZE: usize = 4 * 1024 * 1024;
+
+/// A deflated block and the CRC of its input.
+type DeflatedBlock = (Vec<u8>, flate2::Crc);
+
+/// Gzip header without name or mtime, OS unknown.
+const GZIP_HEADER: [u8; 10] = [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+
+/// Raw deflate of `data`, sync-flushed to a byte boundary so independently
+/// compressed blocks concatenate into one deflate stream; `last` ends the
+/// stream instead. Returns the block's CRC to combine into the trailer.
+fn deflate_block(data: &[u8], level: flate2::Compression, last: bool) -> io::Result<DeflatedBlock> {
+    let mut compress = flate2::Compress::new(level, false);
+    let flush = if last { flate2::FlushCompress::Finish } else { flate2::FlushCompress::Sync };
+    let mut out = Vec::with_capacity(data.len() / 2 + 64);
+    loop {
+        if out.capacity() - out.len() < 64 {
+            out.reserve(out.capacity().max(64));
+        }
+        let consumed = compress.total_in() as usize;
+        let status = compress.compress_vec(&data[consumed..], &mut out, flush).map_err(io::Error::other)?;
+        // Deflate is done once it stops short of the output space it was given.
+        let flushed = compress.total_in() as usize == data.len() && out.len() < out.capacity();
+        if status == flate2::Status::StreamEnd || (!last && flushed) {
+            break;
+        }
+    }
+    let mut crc = flate2::Crc::new();
+    crc.update(data);
+    Ok((out, crc))
+}
+
+/// Final empty deflate block followed by the gzip trailer for `crc`.
+fn gzip_end(level: flate2::Compression, crc: &flate2::Crc) -> io::Result<Vec<u8>> {
+    let (mut end, _) = deflate_block(&[], level, true)?;
+    end.extend_from_slice(&crc.sum().to_le_bytes());
+    end.extend_from_slice(&crc.amount().to_le_bytes());
+    Ok(end)
+}
+
+/// Gzip writer compressing up to `threads` blocks at once on the blocking
+/// pool. Like `pigz -i`, blocks are deflated independently and joined into a
+/// single gzip member, so any gzip decoder reads the output.
+pub struct ParallelGzipEncoder<W> {
+    inner: W,
+    level: flate2::Compression,
+    threads: usize,
+    block: Vec<u8>,
+    pending: VecDeque<JoinHandle<io::Result<DeflatedBlock>>>,
+    output: Vec<u8>,
+    output_pos: usize,
+    crc: flate2::Crc,
+    finished: bool,
+}
+
+impl<W: AsyncWrite + Unpin> ParallelGzipEncoder<W> {
+    pub fn new(inner: W, compression: &Compression) -> Self {
+        Self {
+            inner,
+            level: compression.gzip_level(),
+            threads: compression.threads.max(1),
+            block: Vec::with_capacity(PARALLEL_BLOCK_SIZE),
+            pending: VecDeque::new(),
+            output: GZIP_HEADER.to_vec(),
+            output_pos: 0,
+            crc: flate2::Crc::new(),
+            finished: false,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn submit(&mut self) {
+        let block = std::mem::replace(&mut self.block, Vec::with_capacity(PARALLEL_BLOCK_SIZE));
+        let level = self.level;
+        self.pending.push_back(tokio::task::spawn_blocking(move || deflate_block(&block, level, false)));
+    }
+
+    /// Writes out the oldest finished block, waiting for it if needed.
+    fn poll_next_block(&mut self, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        loop {
+            while self.output_pos < self.output.len() {
+                let n = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.output[self.output_pos..]))?;
+                if n == 0 {
+                    return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+                }
+                self.output_pos += n;
+            }
+
+            let Some(front) = self.pending.front_mut() else {
+                return Poll::Ready(Ok(()));
+            };
+            let (block, crc) = ready!(Pin::new(front).poll(cx)).map_err(io::Error::other)??;
+            self.pending.pop_front();
+            self.crc.combine(&crc);
+            self.output = block;
+            self.output_pos = 0;
+        }
+    }
+
+    fn poll_drain(&mut self, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        while !self.pending.is_empty() || self.output_pos < self.output.len() {
+            ready!(self.poll_next_block(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for ParallelGzipEncoder<W> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut TaskContext<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.block.len() >= PARALLEL_BLOCK_SIZE {
+            while this.pending.len() >= this.threads {
+                ready!(this.poll_next_block(cx))?;
+            }
+            this.submit();
+        }
+
+        let n = buf.len().min(PARALLEL_BLOCK_SIZE - this.block.len());
+        this.block.extend_from_slice(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.block.is_empty() {
+            this.submit();
+        }
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.finished {
+            if !this.block.is_empty() {
+                this.submit();
+            }
+            ready!(this.poll_drain(cx))?;
+            this.output = gzip_end(this.level, &this.crc)?;
+            this.output_pos = 0;
+            this.finished = true;
+        }
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+/// Blocking counterpart of [`ArchiveEncoder`] for gzip archives built with the
+/// `tar` crate inside `spawn_blocking`.
+pub enum GzipWriter<W: Write> {
+    Single(flate2::write::GzEncoder<W>),
+    Parallel {
+        inner: W,
+        level: flate2::Compression,
+        threads: usize,
+        blocks: Vec<Vec<u8>>,
+        crc: flate2::Crc,
+        started: bool,
+    },
+}
+
+impl<W: Write> GzipWriter<W> {
+    pub fn new(inner: W, compression: &Compression) -> Self {
+        let level = match compression.codec {
+            Codec::Gzip => compression.gzip_level(),
+            Codec::Zstd => flate2::Compression::default(),
+        };
+        if compression.threads > 1 {
+            Self::Parallel {
+                inner,
+                level,
+                threads: compression.threads,
+                blocks: Vec::new(),
+                crc: flate2::Crc::new(),
+                started: false,
+            }
+        } else {
+            Self::Single(flate2::write::GzEncoder::new(inner, level))
+        }
+    }
+
+    /// Compresses the buffered blocks, one thread each, and writes them in order.
+    fn write_blocks(&mut self) -> io::Result<()> {
+        let Self::Parallel { inner, level, blocks, crc, started, .. } = self else {
+            return Ok(());
+        };
+        if !*started {
+            inner.write_all(&GZIP_HEADER)?;
+            *started = true;
+        }
+        let level = *level;
+        let deflated = std::thread::scope(|scope| {
+            let handles: Vec<_> = blocks
+                .iter()
+                .map(|block| scope.spawn(move || deflate_block(block, level, false)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().map_err(|_| io::Error::other("gzip worker panicked"))?)
+                .collect::<io::Result<Vec<_>>>()
+        })?;
+        for (block, block_crc) in deflated {
+            inner.write_all(&block)?;
+            crc.combine(&block_crc);
+        }
+        blocks.clear();
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush()?;
+        match self {
+            Self::Single(encoder) => encoder.finish(),
+            Self::Parallel { mut inner, level, crc, .. } => {
+                inner.write_all(&gzip_end(level, &crc)?)?;
+                inner.flush()?;
+                Ok(inner)
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for GzipWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (threads, blocks) = match self {
+            Self::Single(encoder) => return encoder.write(buf),
+            Self::Parallel { threads, blocks, .. } => (*threads, blocks),
+        };
+
+        if blocks.last().is_none_or(|b| b.len() >= PARALLEL_BLOCK_SIZE) {
+            if blocks.len() >= threads {
+                self.write_blocks()?;
+                return self.write(buf);
+            }
+            blocks.push(Vec::with_capacity(PARALLEL_BLOCK_SIZE));
+        }
+        let block = blocks.last_mut().expect("block pushed above");
+        let n = buf.len().min(PARALLEL_BLOCK_SIZE - block.len());
+        block.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Single(encoder) => encoder.flush(),
+            Self::Parallel { .. } => {
+                self.write_blocks()?;
+                match self {
+                    Self::Parallel { inner, .. } => inner.flush(),
+                    Self::Single(_) => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Decompressing reader for `path`, picked from its magic bytes.
+async fn open_decoder(path: &Path) -> Result<Box<dyn AsyncRead + Unpin + Send>> {
+    let codec = Codec::detect(path).await?;
+    let file = File::open(path)
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let buf_reader = BufReader::with_capacity(8 * 1024 * 1024, file);
+
+    Ok(match codec {
+        Codec::Gzip => Box::new(GzipDecoder::new(buf_reader)),
+        // Accept windows beyond the default limit, should an archive use one.
+        Codec::Zstd => Box::new(ZstdDecoder::with_params(buf_reader, &[DParameter::window_log_max(31)])),
+    })
+}
 
 #[allow(dead_code)]
 pub struct CompressionResult {
     pub compressed_path: PathBuf,
+    /// Codec of `compressed_path`; archives that were already compressed keep theirs.
+    pub codec: Codec,
 }
 
-pub async fn compress_to_tar_gz_large(file: &PathBuf, logger: Arc<JobLogger>) -> Result<CompressionResult> {
-    if file
-        .file_name()
-        .and_then(|n| n.to_str())
-        .map(|n| n.ends_with(".tar.gz"))
-        .unwrap_or(false)
-    {
-        logger.log("info", format!("File {:?} is already a tar.gz, skipping compression", file));
+pub async fn compress_archive(
+    file: &PathBuf,
+    compression: &Compression,
+    logger: Arc<JobLogger>,
+) -> Result<CompressionResult> {
+    if let Some(codec) = file.file_name().and_then(|n| n.to_str()).and_then(Codec::from_file_name) {
+        logger.log("info", format!("File {:?} is already a compressed tar, skipping compression", file));
+        if codec != compression.codec {
+            logger.log(
+                "warn",
+                format!("Archive is already {}; configured {} compression is not applied", codec.as_str(), compression.codec.as_str()),
+            );
+        }
         return Ok(CompressionResult {
             compressed_path: file.clone(),
+            codec,
         });
     }
 
@@ -38,8 +565,8 @@ pub async fn compress_to_tar_gz_large(file: &PathBuf, logger: Arc<JobLogger>) ->
         .map(|n| n.ends_with(".tar"))
         .unwrap_or(false)
     {
-        let gz_path = PathBuf::from(format!("{}.gz", file.display()));
-        logger.log("info", format!("Input {:?} is a raw tar, gzipping directly", file));
+        let gz_path = PathBuf::from(format!("{}.{}", file.display(), compression.codec.extension()));
+        logger.log("info", format!("Input {:?} is a raw tar, compressing directly with {}", file, compression.codec.as_str()));
 
         let input = File::open(file)
             .await
@@ -49,23 +576,24 @@ pub async fn compress_to_tar_gz_large(file: &PathBuf, logger: Arc<JobLogger>) ->
         let output_file = File::create(&gz_path)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to create {:?}: {}", gz_path, e))?;
-        let mut encoder = AsyncGzipEncoder::new(output_file);
+        let mut encoder = ArchiveEncoder::new(output_file, compression);
 
         tokio::io::copy(&mut reader, &mut encoder)
             .await
-            .map_err(|e| anyhow::anyhow!("Gzip copy failed: {}", e))?;
+            .map_err(|e| anyhow::anyhow!("Compression copy failed: {}", e))?;
         encoder
             .shutdown()
             .await
-            .map_err(|e| anyhow::anyhow!("Gzip shutdown failed: {}", e))?;
+            .map_err(|e| anyhow::anyhow!("Compression shutdown failed: {}", e))?;
 
         logger.log("info", format!("Compressed {:?} to {:?}", file, gz_path));
         return Ok(CompressionResult {
             compressed_path: gz_path,
+            codec: compression.codec,
         });
     }
 
-    let tar_gz_path = file.with_extension("").with_extension("tar.gz");
+    let tar_gz_path = file.with_extension("").with_extension(compression.codec.tar_extension());
 
     let output_file = File::create(&tar_gz_path)
         .await
@@ -74,8 +602,7 @@ pub async fn compress_to_tar_gz_large(file: &PathBuf, logger: Arc<JobLogger>) ->
             anyhow::anyhow!("Failed to create output file {:?}: {}", tar_gz_path, e)
         })?;
 
-    let gzip_writer = AsyncGzipEncoder::new(output_file);
-    let mut tar_builder = TokioTarBuilder::new(gzip_writer);
+    let mut tar_builder = TokioTarBuilder::new(ArchiveEncoder::new(output_file, compression));
 
     let file_name = file
         .file_name()
@@ -100,19 +627,19 @@ pub async fn compress_to_tar_gz_large(file: &PathBuf, logger: Arc<JobLogger>) ->
             anyhow::anyhow!("Failed to finish tar: {}", e)
         })?;
 
-    let mut gzip = tar_builder
+    let mut encoder = tar_builder
         .into_inner()
         .await
         .map_err(|e| {
-            logger.log("error", format!("Failed to extract gzip encoder: {}", e));
-            anyhow::anyhow!("Failed to extract gzip encoder: {}", e)
+            logger.log("error", format!("Failed to extract encoder: {}", e));
+            anyhow::anyhow!("Failed to extract encoder: {}", e)
         })?;
 
-    gzip.shutdown()
+    encoder.shutdown()
         .await
         .map_err(|e| {
-            logger.log("error", format!("Gzip shutdown failed: {}", e));
-            anyhow::anyhow!("Gzip shutdown failed: {}", e)
+            logger.log("error", format!("Compression shutdown failed: {}", e));
+            anyhow::anyhow!("Compression shutdown failed: {}", e)
         })?;
 
 
@@ -120,17 +647,81 @@ pub async fn compress_to_tar_gz_large(file: &PathBuf, logger: Arc<JobLogger>) ->
 
     Ok(CompressionResult {
         compressed_path: tar_gz_path,
+        codec: compression.codec,
     })
 }
 
+/// Archive entries written by [`tar_stream`] for dumps larger than one part
+/// are named `<file>.part-NNNNN`; extraction joins them back into `<file>`.
+const PART_MARKER: &str = ".part-";
+
+/// Packs a dump stream into the same archive layout as [`compress_archive`]
+/// without touching disk. Tar headers need the entry size up front, so the
+/// dump is buffered `part_size` bytes at a time; a dump that fits in one part
+/// is stored under its own name.
+pub fn tar_stream(dump: DumpStream, compression: Compression, part_size: usize) -> ByteStream {
+    let DumpStream { file_name, mut stream } = dump;
+
+    Box::pin(async_stream::try_stream! {
+        let mut tar_builder = TokioTarBuilder::new(ArchiveEncoder::new(Vec::new(), &compression));
+        let mut buffer = BytesMut::with_capacity(part_size);
+        let mut part: u32 = 0;
+        let mut done = false;
+
+        while !done {
+            match stream.next().await {
+                Some(chunk) => buffer.extend_from_slice(&chunk?),
+                None => done = true,
+            }
+
+            while buffer.len() >= part_size || (done && (!buffer.is_empty() || part == 0)) {
+                let data = buffer.split_to(buffer.len().min(part_size));
+                let name = if done && part == 0 {
+                    file_name.clone()
+                } else {
+                    format!("{file_name}{PART_MARKER}{part:05}")
+                };
+
+                let mut header = tokio_tar::Header::new_gnu();
+                header.set_size(data.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(chrono::Utc::now().timestamp() as u64);
+                header.set_cksum();
+                tar_builder.append_data(&mut header, &name, &data[..]).await?;
+                part += 1;
+
+                let out = std::mem::take(tar_builder.get_mut().get_mut());
+                if !out.is_empty() {
+                    yield Bytes::from(out);
+                }
+            }
+        }
+
+        tar_builder.finish().await?;
+        let mut encoder = tar_builder.into_inner().await?;
+        encoder.shutdown().await?;
+        let out = encoder.into_inner();
+        if !out.is_empty() {
+            yield Bytes::from(out);
+        }
+    })
+}
+
+/// `<file>` for an entry written as `<file>.part-NNNNN`.
+fn part_target(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let (target, index) = name.rsplit_once(PART_MARKER)?;
+    if target.is_empty() || index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(path.with_file_name(target))
+}
+
 pub async fn decompress_large_tar_gz(
     tar_gz_path: &Path,
     output_dir: &Path,
 ) -> Result<Vec<PathBuf>> {
-    let file = File::open(tar_gz_path).await?;
-    let buf_reader = BufReader::with_capacity(8 * 1024 * 1024, file);
-    let decoder = GzipDecoder::new(buf_reader);
-    let mut archive = Archive::new(decoder);
+    let mut archive = Archive::new(open_decoder(tar_gz_path).await?);
 
     let mut extracted_files = Vec::new();
     let mut entries = archive.entries()?;
@@ -144,6 +735,17 @@ pub async fn decompress_large_tar_gz(
             create_dir_all(parent).await?;
         }
 
+        // Parts are stored in order, so each one is appended to its target.
+        if let Some(target) = part_target(&full_path) {
+            let mut out = tokio::fs::OpenOptions::new().create(true).append(true).open(&target).await?;
+            tokio::io::copy(&mut entry, &mut out).await?;
+            out.flush().await?;
+            if !extracted_files.contains(&target) {
+                extracted_files.push(target);
+            }
+            continue;
+        }
+
         entry.unpack(&full_path).await?;
         extracted_files.push(full_path);
     }
@@ -153,12 +755,9 @@ pub async fn decompress_large_tar_gz(
     Ok(extracted_files)
 }
 
-pub async fn gunzip_to_file(gz_path: &Path, out_path: &Path) -> Result<()> {
-    let file = File::open(gz_path)
-        .await
-        .with_context(|| format!("Failed to open {}", gz_path.display()))?;
-    let buf_reader = BufReader::with_capacity(8 * 1024 * 1024, file);
-    let mut decoder = GzipDecoder::new(buf_reader);
+/// Decompresses a gzip or zstd file as-is, without unpacking the tar inside.
+pub async fn decompress_to_file(compressed_path: &Path, out_path: &Path) -> Result<()> {
+    let mut decoder = open_decoder(compressed_path).await?;
 
     let out = File::create(out_path)
         .await
@@ -168,7 +767,6 @@ pub async fn gunzip_to_file(gz_path: &Path, out_path: &Path) -> Result<()> {
     tokio::io::copy(&mut decoder, &mut writer).await?;
     writer.shutdown().await?;
 
-    info!("Gunzipped {:?} into {:?}", gz_path, out_path);
+    info!("Decompressed {:?} into {:?}", compressed_path, out_path);
     Ok(())
 }
-
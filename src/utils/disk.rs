@@ -0,0 +1,107 @@
+use crate::services::config::DatabaseConfig;
+use std::path::{Path, PathBuf};
+
+/// A job's scratch space would not fit on the disk holding it.
+#[derive(Debug)]
+pub struct InsufficientSpace {
+    pub path: PathBuf,
+    pub required: u64,
+    pub available: u64,
+}
+
+impl std::fmt::Display for InsufficientSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Not enough disk space in {}: about {} needed, {} available",
+            self.path.display(),
+            human_size(self.required),
+            human_size(self.available)
+        )
+    }
+}
+
+impl std::error::Error for InsufficientSpace {}
+
+pub fn human_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 * 1024 {
+        format!("{:.1} GB", bytes as f64 / 1024.0 / 1024.0 / 1024.0)
+    } else if bytes >= 1024 * 1024 {
+        format!("{} MB", bytes / 1024 / 1024)
+    } else if bytes >= 1024 {
+        format!("{} KB", bytes / 1024)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+/// Bytes this process may still write on the filesystem holding `path`;
+/// `None` where that cannot be measured.
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is NUL-terminated and `stat` is a valid out pointer.
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+pub fn available_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Scratch space a backup wants, as a multiple of the data it dumps: the
+/// dump, then its archive next to it.
+pub const BACKUP_MARGIN: f64 = 2.0;
+
+/// Scratch space a restore wants, as a multiple of the archive: the download
+/// and the extracted dump, plus a decrypted copy when encrypted.
+pub fn restore_margin(encrypted: bool) -> f64 {
+    if encrypted { 3.0 } else { 2.0 }
+}
+
+/// `options.disk_margin`, overriding the default margin for a database.
+pub fn margin_option(cfg: &DatabaseConfig) -> Result<Option<f64>, String> {
+    match cfg.options.get("disk_margin") {
+        None => Ok(None),
+        Some(value) => match value.as_f64() {
+            Some(margin) if margin >= 1.0 => Ok(Some(margin)),
+            _ => Err(format!("disk_margin must be a number of at least 1, got {value}")),
+        },
+    }
+}
+
+/// How the free space compares with a job's needs.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Space {
+    Enough,
+    /// Enough for the job's raw data, short of the margin.
+    Tight { wanted: u64, available: u64 },
+}
+
+/// Checks the filesystem holding `path` for a job writing about `size`
+/// bytes. Fails only below `size`, since the estimate is rough; below
+/// `size * margin` the space is [`Space::Tight`]. Passes when free space
+/// cannot be measured.
+pub fn check_space(path: &Path, size: u64, margin: f64) -> Result<Space, InsufficientSpace> {
+    let Some(available) = available_space(path) else {
+        return Ok(Space::Enough);
+    };
+    if available < size {
+        return Err(InsufficientSpace {
+            path: path.to_path_buf(),
+            required: size,
+            available,
+        });
+    }
+    let wanted = (size as f64 * margin).min(u64::MAX as f64) as u64;
+    if available < wanted {
+        return Ok(Space::Tight { wanted, available });
+    }
+    Ok(Space::Enough)
+}
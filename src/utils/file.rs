@@ -12,21 +12,36 @@ use aes_gcm::aead::{Aead, KeyInit};
 use aes_gcm::{Aes256Gcm, Key, Nonce};
 use base64::Engine;
 use base64::engine::general_purpose;
-use bytes::Bytes;
-use futures::Stream;
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
 use rand::TryRngCore;
 use rand::rngs::OsRng;
 use tokio::io::{AsyncWriteExt, BufWriter};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::io::ReaderStream;
 use tracing::info;
+use crate::utils::compress::Codec;
 
-#[derive(Serialize, Deserialize)]
+/// Encryption of an uploaded archive, as recorded in its manifest: the header
+/// [`encrypt_stream_gcm`] writes, less its nonce.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
 pub struct EncryptionMetadataFile {
     pub version: u8,
     pub cipher: String,
-    pub encrypted_aes_key_b64: String,
-    pub iv_b64: String,
+    pub chunk_size: usize,
+}
+
+impl EncryptionMetadataFile {
+    /// The format [`encrypt_stream_gcm`] currently writes.
+    pub fn current() -> Self {
+        Self {
+            version: ENCRYPTION_VERSION,
+            cipher: ENCRYPTION_CIPHER.to_string(),
+            chunk_size: CHUNK_SIZE,
+        }
+    }
 }
 
 pub fn full_extension(path: &Path) -> String {
@@ -37,9 +52,9 @@ pub fn full_extension(path: &Path) -> String {
         .to_string()
 }
 
-pub fn full_file_name(encrypt: bool) -> String {
+pub fn full_file_name(encrypt: bool, codec: Codec) -> String {
     let uuid = Uuid::new_v4();
-    let base_name = format!("{}.{}", uuid, "tar.gz");
+    let base_name = format!("{}.{}", uuid, codec.tar_extension());
     if encrypt {
         format!("{}.enc", base_name)
     } else {
@@ -58,6 +73,8 @@ pub fn full_file_path(file_name: &String, folder_name: Option<&str>) -> String {
 }
 
 const CHUNK_SIZE: usize = 16 * 1024 * 1024;
+const ENCRYPTION_VERSION: u8 = 1;
+const ENCRYPTION_CIPHER: &str = "AES-256-GCM";
 
 #[derive(Serialize, Deserialize, Debug)]
 struct FileHeader {
@@ -71,10 +88,29 @@ pub async fn encrypt_file_stream_gcm(
     file_path: PathBuf,
     master_key_b64: String,
 ) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static> {
+    let file = File::open(&file_path).await?;
+    let input = ReaderStream::with_capacity(BufReader::new(file), CHUNK_SIZE);
+    encrypt_stream_gcm(input, master_key_b64)
+}
+
+/// Same framing as [`encrypt_file_stream_gcm`], for a plaintext stream of
+/// unknown length: input is regrouped into `CHUNK_SIZE` chunks as it arrives.
+/// An input error is forwarded and ends the stream.
+pub fn encrypt_stream_gcm<S>(
+    input: S,
+    master_key_b64: String,
+) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static>
+where
+    S: Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
+{
     let master_key_bytes = general_purpose::STANDARD
         .decode(master_key_b64)
         .map_err(|_| anyhow::anyhow!("Invalid base64"))?;
 
+    let key = Key::<Aes256Gcm>::try_from(master_key_bytes.as_slice())
+        .map_err(|_| anyhow::anyhow!("Invalid AES-256 key length"))?;
+    let cipher = Aes256Gcm::new(&key);
+
     let (tx, rx) = mpsc::channel(8);
 
     tokio::spawn(async move {
@@ -82,46 +118,65 @@ pub async fn encrypt_file_stream_gcm(
         let mut base_nonce = [0u8; 8];
         rng.try_fill_bytes(&mut base_nonce).unwrap();
 
-        let key = Key::<Aes256Gcm>::try_from(master_key_bytes.as_slice())
-            .map_err(|_| anyhow::anyhow!("Invalid AES-256 key length"))
-            .unwrap();
-
-        let cipher = Aes256Gcm::new(&key);
-
         let header = FileHeader {
-            version: 1,
-            cipher: "AES-256-GCM".to_string(),
+            version: ENCRYPTION_VERSION,
+            cipher: ENCRYPTION_CIPHER.to_string(),
             chunk_size: CHUNK_SIZE,
             base_nonce: base_nonce.to_vec(),
         };
         let header_json = serde_json::to_string(&header).unwrap();
-        tx.send(Ok(Bytes::from(header_json + "\n"))).await.unwrap();
-
-        let file = File::open(&file_path).await.unwrap();
-        let mut reader = BufReader::new(file);
-        let mut buffer = vec![0u8; CHUNK_SIZE];
-        let mut chunk_index: u32 = 0;
-
-        loop {
-            let n = reader.read(&mut buffer).await.unwrap();
-            if n == 0 {
-                break;
-            }
+        if tx.send(Ok(Bytes::from(header_json + "\n"))).await.is_err() {
+            return;
+        }
 
+        let seal = |chunk_index: u32, plaintext: &[u8]| -> Result<Bytes> {
             let mut nonce_bytes = [0u8; 12];
             nonce_bytes[..8].copy_from_slice(&base_nonce);
             nonce_bytes[8..].copy_from_slice(&chunk_index.to_be_bytes());
             let nonce = Nonce::try_from(&nonce_bytes[..])
-                .map_err(|_| anyhow::anyhow!("Invalid nonce length"))
-                .unwrap();
+                .map_err(|_| anyhow::anyhow!("Invalid nonce length"))?;
 
-            let ciphertext = cipher.encrypt(&nonce, &buffer[..n]).unwrap();
+            let ciphertext = cipher
+                .encrypt(&nonce, plaintext)
+                .map_err(|e| anyhow::anyhow!("AES-GCM encryption failed: {:?}", e))?;
             let mut out = Vec::with_capacity(4 + ciphertext.len());
             out.extend_from_slice(&(ciphertext.len() as u32).to_be_bytes());
             out.extend_from_slice(&ciphertext);
+            Ok(Bytes::from(out))
+        };
 
-            tx.send(Ok(Bytes::from(out))).await.unwrap();
-            chunk_index += 1;
+        let mut input = std::pin::pin!(input);
+        let mut buffer = BytesMut::with_capacity(CHUNK_SIZE);
+        let mut chunk_index: u32 = 0;
+
+        loop {
+            let next = input.next().await;
+            let done = match next {
+                Some(Ok(bytes)) => {
+                    buffer.extend_from_slice(&bytes);
+                    false
+                }
+                Some(Err(e)) => {
+                    let _ = tx.send(Err(e.into())).await;
+                    return;
+                }
+                None => true,
+            };
+
+            while buffer.len() >= CHUNK_SIZE || (done && !buffer.is_empty()) {
+                let take = buffer.len().min(CHUNK_SIZE);
+                let plaintext = buffer.split_to(take);
+                let sealed = seal(chunk_index, &plaintext);
+                let failed = sealed.is_err();
+                if tx.send(sealed).await.is_err() || failed {
+                    return;
+                }
+                chunk_index += 1;
+            }
+
+            if done {
+                break;
+            }
         }
     });
 
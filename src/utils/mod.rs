@@ -1,6 +1,7 @@
 pub mod common;
 pub mod compress;
 pub mod deserializer;
+pub mod disk;
 pub mod edge_key;
 pub mod file;
 pub mod locks;
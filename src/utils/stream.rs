@@ -1,54 +1,296 @@
-use crate::utils::file::encrypt_file_stream_gcm;
+use crate::services::backup::logger::JobLogger;
+use crate::services::backup::models::BackupResult;
+use crate::utils::file::encrypt_stream_gcm;
 use anyhow::Result;
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
+use openssl::sha::Sha256;
+use std::collections::HashMap;
 use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tokio::io::{AsyncReadExt};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::io::ReaderStream;
 use crate::settings::CONFIG;
 
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>;
+
 pub struct UploadStream {
-    pub stream: Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>,
+    pub stream: ByteStream,
+    /// Archive size when known before the upload starts (file-based backups).
+    pub size: Option<u64>,
+    /// Archive bytes read so far, counted before encryption.
+    pub read: ByteCounter,
+    /// Running digests of the archive and of the uploaded bytes.
+    pub digests: Digests,
+}
+
+#[derive(Clone, Default)]
+pub struct ByteCounter(Arc<AtomicU64>);
+
+impl ByteCounter {
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Running SHA-256 of the bytes a stream has yielded so far.
+#[derive(Clone)]
+pub struct Digest(Arc<Mutex<Sha256>>);
+
+impl Default for Digest {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Sha256::new())))
+    }
+}
+
+impl Digest {
+    fn update(&self, bytes: &[u8]) {
+        self.0.lock().unwrap().update(bytes);
+    }
+
+    /// Lowercase hex digest; final once the stream has been read to the end.
+    pub fn hex(&self) -> String {
+        hex::encode(self.0.lock().unwrap().clone().finish())
+    }
+
+    fn inspect(&self, stream: ByteStream) -> ByteStream {
+        let digest = self.clone();
+        Box::pin(stream.inspect(move |item| {
+            if let Ok(bytes) = item {
+                digest.update(bytes);
+            }
+        }))
+    }
 }
 
+/// Digests of an upload stream, read while it is consumed.
+#[derive(Clone, Default)]
+pub struct Digests {
+    /// The archive, before encryption.
+    plaintext: Digest,
+    /// The bytes actually uploaded.
+    uploaded: Digest,
+    uploaded_size: ByteCounter,
+}
+
+impl Digests {
+    /// Digests of everything streamed so far; call once the upload is done.
+    pub fn checksums(&self) -> Checksums {
+        Checksums {
+            sha256: self.uploaded.hex(),
+            plaintext_sha256: self.plaintext.hex(),
+            size: self.uploaded_size.get(),
+        }
+    }
+}
+
+/// SHA-256 digests of an uploaded artifact, as hex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksums {
+    /// The uploaded bytes, encrypted or not.
+    pub sha256: String,
+    /// The archive before encryption; equal to `sha256` for plain uploads.
+    pub plaintext_sha256: String,
+    /// Length of the uploaded bytes.
+    pub size: u64,
+}
+
+impl Checksums {
+    /// Object metadata entries, under names every provider accepts.
+    pub fn metadata(&self) -> HashMap<String, String> {
+        HashMap::from([
+            ("sha256".to_string(), self.sha256.clone()),
+            ("plaintext_sha256".to_string(), self.plaintext_sha256.clone()),
+        ])
+    }
+}
+
+impl UploadStream {
+    fn new(source: ByteStream, size: Option<u64>, encrypt: bool, master_key_b64: &str) -> Result<Self> {
+        let read = ByteCounter::default();
+        let counter = read.clone();
+        let digests = Digests::default();
+        let counted = digests.plaintext.inspect(Box::pin(source.inspect(move |item| {
+            if let Ok(bytes) = item {
+                counter.0.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+            }
+        })));
+
+        let stream: ByteStream = if encrypt {
+            let encrypted = encrypt_stream_gcm(counted, master_key_b64.to_string())?;
+            Box::pin(encrypted.map(|r| r.map_err(std::io::Error::other)))
+        } else {
+            counted
+        };
+        let stream = digests.uploaded.inspect(stream);
+        let uploaded_size = digests.uploaded_size.clone();
+        let stream: ByteStream = Box::pin(stream.inspect(move |item| {
+            if let Ok(bytes) = item {
+                uploaded_size.0.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+            }
+        }));
+
+        Ok(Self { stream, size, read, digests })
+    }
+}
 
+/// A single-consumer stream carried inside cloneable values such as
+/// [`BackupResult`]; the first `take` gets it.
+#[derive(Clone)]
+pub struct SharedStream(Arc<Mutex<Option<ByteStream>>>);
+
+impl SharedStream {
+    pub fn new(stream: ByteStream) -> Self {
+        Self(Arc::new(Mutex::new(Some(stream))))
+    }
+
+    pub fn take(&self) -> Option<ByteStream> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+impl std::fmt::Debug for SharedStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedStream")
+    }
+}
+
+/// Output of a dump tool writing to stdout, named as the file it would
+/// otherwise have written.
+pub struct DumpStream {
+    pub file_name: String,
+    pub stream: ByteStream,
+}
 
 pub async fn build_stream(
     file_path: &std::path::Path,
     encrypt: bool,
     master_key_b64: &String,
 ) -> Result<UploadStream> {
-    if encrypt {
-        let encrypted_stream =
-            encrypt_file_stream_gcm(file_path.to_path_buf(), master_key_b64.to_string()).await?;
-
-        let stream = Box::pin(
-            encrypted_stream
-                .map(|r| r.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))),
-        );
-
-        Ok(UploadStream { stream })
-    } else {
-        let mut file = tokio::fs::File::open(file_path).await?;
-
-        let stream = async_stream::stream! {
-            let mut buffer = vec![0u8; CONFIG.chunk_size];
-
-            loop {
-                let n = match file.read(&mut buffer).await {
-                    Ok(0) => break,
-                    Ok(n) => n,
-                    Err(e) => {
-                        yield Err(e);
+    let size = tokio::fs::metadata(file_path).await?.len();
+    let mut file = tokio::fs::File::open(file_path).await?;
+
+    let stream = async_stream::stream! {
+        let mut buffer = vec![0u8; CONFIG.chunk_size];
+
+        loop {
+            let n = match file.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) => {
+                    yield Err(e);
+                    break;
+                }
+            };
+
+            yield Ok(Bytes::copy_from_slice(&buffer[..n]));
+        }
+    };
+
+    UploadStream::new(Box::pin(stream), Some(size), encrypt, master_key_b64)
+}
+
+/// The upload stream for a backup result: its streamed archive when it has
+/// one, otherwise its backup file.
+pub async fn open_stream(result: &BackupResult, encrypt: bool, master_key_b64: &String) -> Result<UploadStream> {
+    if let Some(stream) = result.stream.as_ref().and_then(SharedStream::take) {
+        return UploadStream::new(stream, None, encrypt, master_key_b64);
+    }
+    match &result.backup_file {
+        Some(file_path) => build_stream(file_path, encrypt, master_key_b64).await,
+        None => anyhow::bail!("Missing backup file path"),
+    }
+}
+
+/// Copies `source` to `consumers` streams. Consumers that drop out are skipped;
+/// the rest are fed at the pace of the slowest one. A source error is passed
+/// to every consumer.
+pub fn fan_out(source: ByteStream, consumers: usize) -> Vec<ByteStream> {
+    let (senders, receivers): (Vec<_>, Vec<_>) = (0..consumers).map(|_| mpsc::channel(4)).unzip();
+
+    tokio::spawn(async move {
+        let mut source = source;
+        let mut senders = senders;
+
+        while let Some(item) = source.next().await {
+            match item {
+                Ok(bytes) => {
+                    let mut live = Vec::with_capacity(senders.len());
+                    for tx in senders {
+                        if tx.send(Ok(bytes.clone())).await.is_ok() {
+                            live.push(tx);
+                        }
+                    }
+                    senders = live;
+                    if senders.is_empty() {
                         break;
                     }
-                };
+                }
+                Err(e) => {
+                    for tx in &senders {
+                        let _ = tx.send(Err(std::io::Error::new(e.kind(), e.to_string()))).await;
+                    }
+                    break;
+                }
+            }
+        }
+    });
 
-                yield Ok(Bytes::copy_from_slice(&buffer[..n]));
+    receivers
+        .into_iter()
+        .map(|rx| Box::pin(ReceiverStream::new(rx)) as ByteStream)
+        .collect()
+}
+
+/// Runs `cmd` and streams its stdout. Once stdout closes the exit status is
+/// checked and logged under `label`; a failure ends the stream with an error.
+/// Dropping the stream kills the process.
+pub fn command_stream(mut cmd: tokio::process::Command, label: String, logger: Arc<JobLogger>) -> Result<ByteStream> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to start {label}: {e}"))?;
+
+    let stdout = child.stdout.take().ok_or_else(|| anyhow::anyhow!("{label} has no stdout"))?;
+    let mut stderr = child.stderr.take().ok_or_else(|| anyhow::anyhow!("{label} has no stderr"))?;
+    let stderr_task = tokio::spawn(async move {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output).await;
+        output
+    });
+
+    let start = Instant::now();
+    let stream = async_stream::stream! {
+        let mut stdout = ReaderStream::with_capacity(stdout, CONFIG.chunk_size);
+        while let Some(chunk) = stdout.next().await {
+            let failed = chunk.is_err();
+            yield chunk;
+            if failed {
+                return;
             }
-        };
+        }
 
-        Ok(UploadStream {
-            stream: Box::pin(stream),
-        })
-    }
+        let status = child.wait().await;
+        let stderr = stderr_task.await.unwrap_or_default();
+        let duration_ms = start.elapsed().as_millis() as f64;
+        let exit_code = status.as_ref().ok().and_then(|s| s.code()).unwrap_or(-1);
+        let output = if stderr.is_empty() { None } else { Some(stderr.clone()) };
+        logger.log_command(label.clone(), output, Some(exit_code), Some(duration_ms));
+
+        if exit_code != 0 {
+            let lines: Vec<&str> = stderr.lines().collect();
+            let tail = lines[lines.len().saturating_sub(5)..].join("\n");
+            yield Err(std::io::Error::other(format!("{label} failed (exit {exit_code}): {tail}")));
+        }
+    };
+
+    Ok(Box::pin(stream))
 }
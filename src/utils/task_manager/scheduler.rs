@@ -79,7 +79,7 @@ pub async fn execute_task(
             let ctx = Arc::new(Context::new());
             let config_service = ConfigService::new(ctx.clone());
             let backup_service = BackupService::new(ctx.clone());
-            let config = config_service.load(None).unwrap();
+            let config = config_service.load_with_discovery().await.unwrap();
 
             let metadata_obj = metadata
                 .into_iter()
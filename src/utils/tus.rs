@@ -9,14 +9,17 @@ pub async fn upload_to_tus_stream_with_headers<S>(
     encrypted_stream: S,
     tus_endpoint: &str,
     extra_headers: HeaderMap,
-    total_size: u64,
+    total_size: Option<u64>,
 ) -> Result<()>
 where
     S: Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
 {
     let client = reqwest::Client::new();
 
-    info!("File size: {}", total_size);
+    match total_size {
+        Some(size) => info!("File size: {}", size),
+        None => info!("File size: unknown until the stream ends"),
+    }
     info!("Endpoint URL: {}", tus_endpoint);
 
     let mut create_headers = HeaderMap::new();
@@ -139,6 +142,12 @@ where
     }
 
     let mut finalize_headers = extra_headers.clone();
+    if !finalize_headers.contains_key("X-File-Size") {
+        finalize_headers.insert(
+            "X-File-Size",
+            HeaderValue::from_str(&offset.to_string()).context("Invalid file size header")?,
+        );
+    }
     finalize_headers.insert("Tus-Resumable", HeaderValue::from_static("1.0.0"));
     finalize_headers.insert(
         "Upload-Offset",